@@ -13,7 +13,7 @@ fn main() {
     // run the task
     let result: PredictiveAccuracy<_> = task.run(|train, test| {
         // train classifier
-        let nbc: NaiveBayesClassifier<u8> = train.map(|(x, y)| (x, y)).collect();
+        let nbc: NaiveBayesClassifier<u8> = train.map(|(x, y)| (x.iter(), y)).collect();
 
         // test classifier
         let y_out: Vec<_> = test.map(|x| nbc.predict(x)).collect();
@@ -0,0 +1,19 @@
+extern crate openml;
+
+use openml::example_flows::knn_flow;
+use openml::prelude::*;
+use openml::{PredictiveAccuracy, SupervisedClassification, F1};
+
+fn main() {
+    // Load "Supervised Classification on iris" task (https://www.openml.org/t/59)
+    let task = SupervisedClassification::from_openml(59).unwrap();
+
+    println!("Task: {}", task.name());
+
+    // run the task with a 5-nearest-neighbors classifier
+    let accuracy: PredictiveAccuracy<u8> = task.run(knn_flow(5));
+    let f1: F1<u8> = task.run(knn_flow(5));
+
+    println!("Classification Accuracy: {}", accuracy.result());
+    println!("F1 Score: {}", f1.result());
+}
@@ -0,0 +1,17 @@
+extern crate openml;
+
+use openml::example_flows::linear_regression_flow;
+use openml::prelude::*;
+use openml::{RootMeanSquaredError, SupervisedRegression};
+
+fn main() {
+    // Load "Supervised Regression on liver-disorders" task (https://www.openml.org/t/52948)
+    let task = SupervisedRegression::from_openml(52948).unwrap();
+
+    println!("Task: {}", task.name());
+
+    // run the task with a multivariate linear regression
+    let result: RootMeanSquaredError<f64> = task.run(linear_regression_flow());
+
+    println!("Root Mean Squared Error: {}", result.result());
+}
@@ -13,7 +13,7 @@ fn main() {
     // run the task
     let result: RootMeanSquaredError<_> = task.run(|train, test| {
         // train model
-        let model: NaiveLinearRegression = train.map(|(x, y)| (x, y)).collect();
+        let model: NaiveLinearRegression = train.map(|(x, y)| (x.iter(), y)).collect();
 
         // test model
         let y_out: Vec<_> = test.map(|x| model.predict(x)).collect();
@@ -0,0 +1,342 @@
+//! Anonymization of data sets before they are shared
+//!
+//! An `Anonymizer` lists transformations of individual columns: identifier columns can be dropped
+//! or replaced by salted hashes, and numeric columns can be quantized to a coarser resolution.
+//! Applying it to a data set yields the transformed data in ARFF format, ready for upload, and a
+//! report of every transformation that was applied.
+//!
+//! ```
+//! # extern crate arff;
+//! # extern crate openml;
+//! use openml::anonymize::Anonymizer;
+//!
+//! let data = arff::dynamic::DataSet::from_str(
+//!     "@RELATION patients\n\
+//!      @ATTRIBUTE name STRING\n\
+//!      @ATTRIBUTE age NUMERIC\n\
+//!      @DATA\n\
+//!      'Alice', 34\n\
+//!      'Bob', 58\n",
+//! )
+//! .unwrap();
+//!
+//! let (arff, report) = Anonymizer::new()
+//!     .hash("name", "secret salt")
+//!     .quantize("age", 10.0)
+//!     .apply(&data)
+//!     .unwrap();
+//!
+//! assert!(!arff.contains("Alice"));
+//! assert_eq!(report.len(), 2);
+//! ```
+//!
+//! Hashes are computed with the standard library's SipHash. They hide identifiers from casual
+//! readers, but a salt that is kept secret is required to prevent dictionary attacks.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Write;
+use std::hash::{Hash, Hasher};
+
+use arff::dynamic::{DataSet, Value};
+
+use crate::error::{Error, Result};
+
+/// A transformation of one column
+#[derive(Debug, Clone, PartialEq)]
+enum Rule {
+    Drop,
+    Hash { salt: String },
+    Quantize { step: f64 },
+}
+
+/// Description of a transformation that was applied to a column
+#[derive(Debug, Clone, PartialEq)]
+pub enum Transformation {
+    /// the column was removed
+    Dropped { column: String },
+    /// the values of the column were replaced by hashes
+    Hashed { column: String, n_values: usize },
+    /// the values of the column were rounded to multiples of `step`; `n_changed` values differ
+    /// from the original ones
+    Quantized {
+        column: String,
+        step: f64,
+        n_changed: usize,
+    },
+}
+
+/// Configurable list of column transformations
+#[derive(Debug, Clone, Default)]
+pub struct Anonymizer {
+    rules: Vec<(String, Rule)>,
+}
+
+impl Anonymizer {
+    /// create an anonymizer that leaves all columns unchanged
+    pub fn new() -> Self {
+        Anonymizer::default()
+    }
+
+    /// remove a column
+    pub fn drop(mut self, column: &str) -> Self {
+        self.rules.push((column.to_owned(), Rule::Drop));
+        self
+    }
+
+    /// replace the values of a column by hashes of the values and `salt`. Equal values get equal
+    /// hashes, so the column can still be used to group rows. Missing values stay missing.
+    pub fn hash(mut self, column: &str, salt: &str) -> Self {
+        self.rules.push((
+            column.to_owned(),
+            Rule::Hash {
+                salt: salt.to_owned(),
+            },
+        ));
+        self
+    }
+
+    /// round the values of a numeric column to the nearest multiple of `step`
+    pub fn quantize(mut self, column: &str, step: f64) -> Self {
+        assert!(step > 0.0, "quantization step must be positive");
+        self.rules
+            .push((column.to_owned(), Rule::Quantize { step }));
+        self
+    }
+
+    /// apply the transformations to a data set and return it in ARFF format, together with a
+    /// report of the applied transformations (in the order of the columns).
+    ///
+    /// Fails if a configured column does not exist, or if a non-numeric column is quantized.
+    pub fn apply(&self, data: &DataSet) -> Result<(String, Vec<Transformation>)> {
+        let names: Vec<_> = data.col_names().collect();
+        for (column, _) in &self.rules {
+            if !names.contains(&column.as_str()) {
+                return Err(column_error(column, "no such column"));
+            }
+        }
+
+        let mut report = vec![];
+        let mut columns = vec![];
+
+        for (idx, &name) in names.iter().enumerate() {
+            let values: Vec<_> = (0..data.n_rows()).map(|row| data.item(row, idx)).collect();
+
+            let column = match self.rules.iter().find(|(c, _)| c == name).map(|(_, r)| r) {
+                None => OutputColumn::from_values(name, &values),
+                Some(Rule::Drop) => {
+                    report.push(Transformation::Dropped {
+                        column: name.to_owned(),
+                    });
+                    continue;
+                }
+                Some(Rule::Hash { salt }) => {
+                    let hashed: Vec<_> = values
+                        .iter()
+                        .map(|v| format_value(v).map(|v| hash_value(salt, &v)))
+                        .collect();
+                    report.push(Transformation::Hashed {
+                        column: name.to_owned(),
+                        n_values: hashed.iter().filter(|v| v.is_some()).count(),
+                    });
+                    OutputColumn::strings(name, hashed)
+                }
+                Some(&Rule::Quantize { step }) => {
+                    let mut n_changed = 0;
+                    let mut quantized = Vec::with_capacity(values.len());
+                    for v in &values {
+                        let x = match numeric(v) {
+                            Some(x) => x,
+                            None if *v == Value::Missing => {
+                                quantized.push(None);
+                                continue;
+                            }
+                            None => return Err(column_error(name, "column is not numeric")),
+                        };
+                        let q = (x / step).round() * step;
+                        if q != x {
+                            n_changed += 1;
+                        }
+                        quantized.push(Some(q.to_string()));
+                    }
+                    report.push(Transformation::Quantized {
+                        column: name.to_owned(),
+                        step,
+                        n_changed,
+                    });
+                    OutputColumn {
+                        name: name.to_owned(),
+                        kind: "NUMERIC".to_owned(),
+                        values: quantized,
+                    }
+                }
+            };
+            columns.push(column);
+        }
+
+        Ok((write_arff(data.name(), &columns, data.n_rows()), report))
+    }
+}
+
+/// A column of already formatted ARFF values
+struct OutputColumn {
+    name: String,
+    kind: String,
+    values: Vec<Option<String>>,
+}
+
+impl OutputColumn {
+    /// format the values of an unchanged column; the attribute type is taken from the first
+    /// value that is not missing
+    fn from_values(name: &str, values: &[Value]) -> Self {
+        let kind = match values.iter().find(|v| **v != Value::Missing) {
+            Some(Value::String(_)) => "STRING".to_owned(),
+            Some(Value::Nominal(_, categories)) => {
+                let categories: Vec<_> = categories.iter().map(|c| quote(c)).collect();
+                format!("{{{}}}", categories.join(","))
+            }
+            _ => "NUMERIC".to_owned(),
+        };
+
+        OutputColumn {
+            name: name.to_owned(),
+            kind,
+            values: values
+                .iter()
+                .map(|v| match v {
+                    Value::String(_) | Value::Nominal(..) => format_value(v).map(|s| quote(&s)),
+                    _ => format_value(v),
+                })
+                .collect(),
+        }
+    }
+
+    fn strings(name: &str, values: Vec<Option<String>>) -> Self {
+        OutputColumn {
+            name: name.to_owned(),
+            kind: "STRING".to_owned(),
+            values: values.into_iter().map(|v| v.map(|s| quote(&s))).collect(),
+        }
+    }
+}
+
+fn write_arff(relation: &str, columns: &[OutputColumn], n_rows: usize) -> String {
+    let mut out = String::new();
+    writeln!(out, "@RELATION {}", quote(relation)).unwrap();
+    for column in columns {
+        writeln!(out, "@ATTRIBUTE {} {}", quote(&column.name), column.kind).unwrap();
+    }
+    writeln!(out, "@DATA").unwrap();
+
+    for row in 0..n_rows {
+        let values: Vec<_> = columns
+            .iter()
+            .map(|c| c.values[row].as_deref().unwrap_or("?"))
+            .collect();
+        writeln!(out, "{}", values.join(",")).unwrap();
+    }
+    out
+}
+
+/// format a value as plain text, or return `None` if it is missing
+fn format_value(v: &Value) -> Option<String> {
+    match *v {
+        Value::Missing => None,
+        Value::String(s) => Some(s.to_owned()),
+        Value::Nominal(code, categories) => Some(categories[code].clone()),
+        _ => numeric(v).map(|x| x.to_string()),
+    }
+}
+
+fn numeric(v: &Value) -> Option<f64> {
+    match *v {
+        Value::U8(x) => Some(x.into()),
+        Value::U16(x) => Some(x.into()),
+        Value::U32(x) => Some(x.into()),
+        Value::U64(x) => Some(x as f64),
+        Value::I8(x) => Some(x.into()),
+        Value::I16(x) => Some(x.into()),
+        Value::I32(x) => Some(x.into()),
+        Value::I64(x) => Some(x as f64),
+        Value::F64(x) => Some(x),
+        _ => None,
+    }
+}
+
+fn hash_value(salt: &str, value: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    salt.hash(&mut hasher);
+    value.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// quote a name or value for ARFF, if it contains characters with special meaning
+pub(crate) fn quote(s: &str) -> String {
+    let special = |c: char| c.is_whitespace() || ",'\"{}%?\\".contains(c);
+    if !s.is_empty() && !s.contains(special) {
+        return s.to_owned();
+    }
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn column_error(column: &str, message: &str) -> Error {
+    Error::ColumnError {
+        column: column.to_owned(),
+        message: message.to_owned(),
+    }
+}
+
+#[test]
+fn anonymize() {
+    let data = DataSet::from_str(
+        "@RELATION test\n\
+         @ATTRIBUTE id NUMERIC\n\
+         @ATTRIBUTE name STRING\n\
+         @ATTRIBUTE income NUMERIC\n\
+         @ATTRIBUTE class {yes,no}\n\
+         @DATA\n\
+         1,'Alice',1234.5,yes\n\
+         2,'Bob',?,no\n\
+         3,'Alice',2000,no\n",
+    )
+    .unwrap();
+
+    let (arff, report) = Anonymizer::new()
+        .drop("id")
+        .hash("name", "salt")
+        .quantize("income", 1000.0)
+        .apply(&data)
+        .unwrap();
+
+    assert_eq!(
+        report,
+        vec![
+            Transformation::Dropped {
+                column: "id".to_owned()
+            },
+            Transformation::Hashed {
+                column: "name".to_owned(),
+                n_values: 3
+            },
+            Transformation::Quantized {
+                column: "income".to_owned(),
+                step: 1000.0,
+                n_changed: 1
+            },
+        ]
+    );
+
+    let result = DataSet::from_str(&arff).unwrap();
+    assert_eq!(result.n_cols(), 3);
+    assert_eq!(result.n_rows(), 3);
+    assert_eq!(numeric(&result.item(0, 1)), Some(1000.0));
+    assert_eq!(result.item(1, 1), Value::Missing);
+    assert_eq!(result.item(0, 0), result.item(2, 0));
+    assert_ne!(result.item(0, 0), result.item(1, 0));
+    assert_eq!(result.item(1, 2), data.item(1, 3));
+
+    assert!(Anonymizer::new()
+        .quantize("name", 1.0)
+        .apply(&data)
+        .is_err());
+    assert!(Anonymizer::new().drop("age").apply(&data).is_err());
+}
@@ -144,7 +144,7 @@ impl fmt::Debug for NormalDistribution {
 
 #[test]
 fn nbc() {
-    let data = vec![
+    let data = [
         (vec![1.0, 2.0], 'A'),
         (vec![2.0, 1.0], 'A'),
         (vec![1.0, 5.0], 'B'),
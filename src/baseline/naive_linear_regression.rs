@@ -103,7 +103,7 @@ impl NaiveLinearRegression {
 
 #[test]
 fn nbc_flat() {
-    let data = vec![
+    let data = [
         (vec![1.0, 2.0], 3.0),
         (vec![2.0, 1.0], 3.0),
         (vec![1.0, 5.0], 3.0),
@@ -120,7 +120,7 @@ fn nbc_flat() {
 
 #[test]
 fn nbc_slope() {
-    let data = vec![
+    let data = [
         (vec![1.0, 2.0], 8.0),
         (vec![2.0, 1.0], 9.0),
         (vec![1.0, 5.0], 5.0),
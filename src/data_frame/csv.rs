@@ -0,0 +1,89 @@
+//! CSV export of data frames
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::error::Result;
+
+use super::{dates, ColumnValues, DataFrame};
+
+impl DataFrame {
+    /// write the data frame as CSV: a header line with the column names, then one line per row.
+    /// Nominal values are written as their labels, dates in ISO 8601 format and missing values as
+    /// empty fields. Fields are
+    /// quoted as described in RFC 4180 if necessary.
+    pub fn write_csv<W: Write>(&self, writer: W) -> Result<()> {
+        let mut writer = BufWriter::new(writer);
+
+        let header: Vec<_> = self.columns.iter().map(|c| quote_csv(&c.name)).collect();
+        writeln!(writer, "{}", header.join(","))?;
+
+        for row in 0..self.n_rows() {
+            let fields: Vec<_> = self
+                .columns
+                .iter()
+                .map(|c| match c.values {
+                    ColumnValues::Numeric(ref v) => v[row].map(|x| x.to_string()),
+                    ColumnValues::Nominal {
+                        ref levels,
+                        ref codes,
+                    } => codes[row].map(|code| quote_csv(&levels[code])),
+                    ColumnValues::String(ref v) => v[row].as_ref().map(|s| quote_csv(s)),
+                    ColumnValues::Date(ref v) => v[row].map(|d| {
+                        d.format(&format!("{}%.f", dates::DEFAULT_CHRONO_FORMAT))
+                            .to_string()
+                    }),
+                })
+                .map(Option::unwrap_or_default)
+                .collect();
+            writeln!(writer, "{}", fields.join(","))?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// write the data frame to a CSV file; see `write_csv`
+    pub fn save_csv<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.write_csv(File::create(path)?)
+    }
+}
+
+fn quote_csv(s: &str) -> String {
+    if s.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+#[test]
+fn write_csv() {
+    use super::Column;
+
+    let frame = DataFrame::new(
+        "test",
+        vec![
+            Column::new("x", ColumnValues::Numeric(vec![Some(1.5), None])),
+            Column::new(
+                "class",
+                ColumnValues::Nominal {
+                    levels: vec!["a,b".to_owned(), "c".to_owned()],
+                    codes: vec![Some(0), Some(1)],
+                },
+            ),
+            Column::new(
+                "text",
+                ColumnValues::String(vec![None, Some("say \"hi\"".to_owned())]),
+            ),
+        ],
+    );
+
+    let mut out = vec![];
+    frame.write_csv(&mut out).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "x,class,text\n1.5,\"a,b\",\n,c,\"say \"\"hi\"\"\"\n"
+    );
+}
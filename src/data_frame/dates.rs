@@ -0,0 +1,249 @@
+//! Support for ARFF `DATE` attributes
+//!
+//! The ARFF parser does not know date attributes, so date columns are converted to numeric
+//! columns of epoch seconds before the data is parsed. Data frames turn these columns back into
+//! date columns.
+
+use std::borrow::Cow;
+
+use arff::dynamic::DataSet as ArffDataSet;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+
+use crate::error::{Error, Result};
+use crate::sparse::{split_token, split_values, unquote};
+
+/// date format that ARFF uses if an attribute does not declare one, in Java notation
+pub(crate) const DEFAULT_FORMAT: &str = "yyyy-MM-dd'T'HH:mm:ss";
+
+/// date format of `DEFAULT_FORMAT` in chrono notation
+pub(crate) const DEFAULT_CHRONO_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// parse an ARFF data set that may contain date attributes. Returns the data set, with date
+/// columns as numeric columns of epoch seconds, and the names of the date columns.
+pub(crate) fn parse_arff(input: &str) -> Result<(ArffDataSet, Vec<String>)> {
+    let (input, dates) = replace_dates(input)?;
+    Ok((ArffDataSet::from_str(&input)?, dates))
+}
+
+/// convert date attributes to numeric attributes, and date values to epoch seconds. The input is
+/// only copied if it contains date attributes.
+fn replace_dates(input: &str) -> Result<(Cow<'_, str>, Vec<String>)> {
+    let mut names = vec![];
+    let mut formats = vec![];
+    let mut n_attributes = 0;
+    let mut header_lines = vec![];
+    let mut data_start = None;
+
+    for (line_start, line) in line_offsets(input) {
+        let trimmed = line.trim();
+        let lower = trimmed.to_lowercase();
+        if lower.starts_with("@data") {
+            data_start = Some(line_start + line.len());
+            header_lines.push(Cow::Borrowed(line));
+            break;
+        }
+        if lower.starts_with("@attribute") {
+            let (name, kind) = split_token(trimmed["@attribute".len()..].trim_start());
+            let kind = kind.trim();
+            if kind.len() >= 4 && kind[..4].eq_ignore_ascii_case("date") {
+                let format = match kind[4..].trim() {
+                    "" => DEFAULT_FORMAT,
+                    format => unquote(format),
+                };
+                formats.push((
+                    n_attributes,
+                    chrono_format(format).map_err(|message| Error::ColumnError {
+                        column: unquote(name).to_owned(),
+                        message,
+                    })?,
+                ));
+                names.push(unquote(name).to_owned());
+                header_lines.push(Cow::Owned(format!("@ATTRIBUTE {} NUMERIC\n", name)));
+                n_attributes += 1;
+                continue;
+            }
+            n_attributes += 1;
+        }
+        header_lines.push(Cow::Borrowed(line));
+    }
+
+    let data_start = match (formats.is_empty(), data_start) {
+        (false, Some(start)) => start,
+        _ => return Ok((Cow::Borrowed(input), names)),
+    };
+
+    let mut out = header_lines.concat();
+    for line in input[data_start..].split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('%') {
+            out.push_str(line);
+            continue;
+        }
+        let mut values: Vec<Cow<'_, str>> = split_values(trimmed)
+            .into_iter()
+            .map(Cow::Borrowed)
+            .collect();
+        for (&(col, ref format), name) in formats.iter().zip(&names) {
+            let value = match values.get(col) {
+                Some(value) if *value != "?" => unquote(value),
+                _ => continue,
+            };
+            let date = parse_date(value, format).ok_or_else(|| Error::ColumnError {
+                column: name.clone(),
+                message: format!("invalid date {:?}", value),
+            })?;
+            values[col] = Cow::Owned(epoch_seconds(&date).to_string());
+        }
+        out.push_str(&values.join(","));
+        out.push('\n');
+    }
+
+    Ok((Cow::Owned(out), names))
+}
+
+/// iterate over the lines of a string, including line breaks, with their byte offsets
+fn line_offsets(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input.split_inclusive('\n').scan(0, |offset, line| {
+        let start = *offset;
+        *offset += line.len();
+        Some((start, line))
+    })
+}
+
+/// translate a date format in Java `SimpleDateFormat` notation, as used by ARFF, into chrono
+/// notation
+pub(crate) fn chrono_format(java: &str) -> std::result::Result<String, String> {
+    let chars: Vec<char> = java.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\'' {
+            // quoted literal text; two single quotes stand for one
+            i += 1;
+            if chars.get(i) == Some(&'\'') {
+                out.push('\'');
+                i += 1;
+                continue;
+            }
+            while i < chars.len() {
+                match (chars[i], chars.get(i + 1)) {
+                    ('\'', Some('\'')) => {
+                        out.push('\'');
+                        i += 2;
+                    }
+                    ('\'', _) => break,
+                    (c, _) => {
+                        push_literal(&mut out, c);
+                        i += 1;
+                    }
+                }
+            }
+            i += 1;
+            continue;
+        }
+        if !c.is_ascii_alphabetic() {
+            push_literal(&mut out, c);
+            i += 1;
+            continue;
+        }
+
+        let mut n = 1;
+        while chars.get(i + n) == Some(&c) {
+            n += 1;
+        }
+        i += n;
+        out.push_str(match (c, n) {
+            ('y', 2) => "%y",
+            ('y', _) => "%Y",
+            ('M', 1) | ('M', 2) => "%m",
+            ('M', 3) => "%b",
+            ('M', _) => "%B",
+            ('d', _) => "%d",
+            ('H', _) => "%H",
+            ('h', _) => "%I",
+            ('m', _) => "%M",
+            ('s', _) => "%S",
+            ('S', _) => "%3f",
+            ('a', _) => "%p",
+            ('E', 1..=3) => "%a",
+            ('E', _) => "%A",
+            ('z', _) | ('Z', _) | ('X', _) => "%z",
+            _ => return Err(format!("unsupported date pattern letter '{}'", c)),
+        });
+    }
+    Ok(out)
+}
+
+fn push_literal(out: &mut String, c: char) {
+    if c == '%' {
+        out.push_str("%%");
+    } else {
+        out.push(c);
+    }
+}
+
+/// parse a date in chrono notation; dates with a time zone are converted to UTC, and dates
+/// without a time of day are at midnight
+pub(crate) fn parse_date(value: &str, format: &str) -> Option<NaiveDateTime> {
+    if format.contains("%z") {
+        return DateTime::parse_from_str(value, format)
+            .ok()
+            .map(|d| d.naive_utc());
+    }
+    NaiveDateTime::parse_from_str(value, format)
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, format)
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })
+}
+
+/// convert a date to seconds since 1970-01-01T00:00:00 UTC
+pub(crate) fn epoch_seconds(date: &NaiveDateTime) -> f64 {
+    date.and_utc().timestamp_millis() as f64 / 1000.0
+}
+
+/// convert seconds since 1970-01-01T00:00:00 UTC to a date; the result is rounded to
+/// milliseconds
+pub(crate) fn from_epoch_seconds(seconds: f64) -> Option<NaiveDateTime> {
+    if !seconds.is_finite() {
+        return None;
+    }
+    DateTime::from_timestamp_millis((seconds * 1000.0).round() as i64).map(|d| d.naive_utc())
+}
+
+#[test]
+fn date_attributes() {
+    assert_eq!(
+        chrono_format(DEFAULT_FORMAT).unwrap(),
+        DEFAULT_CHRONO_FORMAT
+    );
+    assert_eq!(
+        chrono_format("dd.MM.yy 'at' hh:mm a, ''").unwrap(),
+        "%d.%m.%y at %I:%M %p, '"
+    );
+    assert!(chrono_format("yyyy-ww").is_err());
+
+    let (data, dates) = parse_arff(
+        "@RELATION dates\n\
+         @ATTRIBUTE x NUMERIC\n\
+         @ATTRIBUTE timestamp DATE\n\
+         @ATTRIBUTE 'day' date \"yyyy-MM-dd\"\n\
+         @DATA\n\
+         % comment\n\
+         1,1970-01-02T00:00:10,'2001-02-03'\n\
+         2,?,?\n",
+    )
+    .unwrap();
+    assert_eq!(dates, vec!["timestamp", "day"]);
+    assert_eq!(data.col(1).item(0).as_f64().unwrap(), 86410.0);
+    assert_eq!(
+        from_epoch_seconds(data.col(2).item(0).as_f64().unwrap()),
+        NaiveDate::from_ymd_opt(2001, 2, 3).and_then(|d| d.and_hms_opt(0, 0, 0))
+    );
+    assert_eq!(data.col(2).item(1), arff::dynamic::Value::Missing);
+
+    assert!(parse_arff("@RELATION r\n@ATTRIBUTE d DATE\n@DATA\n2001-13-01T00:00:00\n").is_err());
+}
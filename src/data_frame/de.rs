@@ -0,0 +1,354 @@
+//! Deserialization of data frames with serde
+//!
+//! The values of a data frame are visited row by row, like the ARFF deserializer does for ARFF
+//! data sets: a `Vec<X>` receives one `X` per row if `X` is a struct or tuple, and one `X` per
+//! value if `X` is a number. Nominal values deserialize into their labels or codes, and dates
+//! into epoch seconds or ISO 8601 strings.
+
+use arff::Error;
+use serde::de::{
+    self, Deserialize, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+};
+
+use super::{dates, CellValue, DataFrame};
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// deserialize the whole data frame
+pub(crate) fn from_frame<'a, T: Deserialize<'a>>(frame: &'a DataFrame) -> Result<T> {
+    T::deserialize(&mut Deserializer {
+        frame,
+        row: 0,
+        col: 0,
+        nested_sequence_depth: 0,
+    })
+}
+
+struct Deserializer<'de> {
+    frame: &'de DataFrame,
+    row: usize,
+    col: usize,
+    nested_sequence_depth: u8,
+}
+
+impl<'de> Deserializer<'de> {
+    fn peek(&self) -> Option<CellValue<'de>> {
+        if self.row >= self.frame.n_rows() {
+            return None;
+        }
+        Some(self.frame.row(self.row).get(self.col))
+    }
+
+    fn next(&mut self) -> Result<CellValue<'de>> {
+        let value = self.peek().ok_or(Error::Eof)?;
+        self.col += 1;
+        if self.col >= self.frame.n_cols() {
+            self.col = 0;
+            self.row += 1;
+        }
+        Ok(value)
+    }
+
+    fn next_f64(&mut self) -> Result<f64> {
+        match self.next()? {
+            CellValue::Missing => Err(Error::UnexpectedMissingValue),
+            CellValue::String(_) => Err(Error::ConversionError),
+            value => Ok(value.as_f64().expect("numeric value")),
+        }
+    }
+
+    /// get the next value as an integer; numbers must be integral and in range
+    fn next_int(&mut self, min: f64, max: f64) -> Result<f64> {
+        let x = self.next_f64()?;
+        if x.fract() != 0.0 || x < min || x > max {
+            return Err(Error::ConversionError);
+        }
+        Ok(x)
+    }
+
+    fn next_string(&mut self) -> Result<String> {
+        match self.next()? {
+            CellValue::Missing => Err(Error::UnexpectedMissingValue),
+            CellValue::String(s) | CellValue::Nominal { label: s, .. } => Ok(s.to_owned()),
+            CellValue::Date(d) => Ok(d.format(dates::DEFAULT_CHRONO_FORMAT).to_string()),
+            CellValue::Numeric(_) => Err(Error::UnexpectedType),
+        }
+    }
+}
+
+macro_rules! deserialize_int {
+    ($($method:ident, $visit:ident, $t:ty;)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+                visitor.$visit(self.next_int(<$t>::MIN as f64, <$t>::MAX as f64)? as $t)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.peek().ok_or(Error::Eof)? {
+            CellValue::Missing => {
+                self.next()?;
+                visitor.visit_none()
+            }
+            CellValue::Numeric(_) | CellValue::Date(_) => self.deserialize_f64(visitor),
+            CellValue::Nominal { .. } | CellValue::String(_) => self.deserialize_string(visitor),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let truthy = |s: &str| {
+            ["true", "yes", "y", "t"]
+                .iter()
+                .any(|t| s.eq_ignore_ascii_case(t))
+        };
+        let value = match self.next()? {
+            CellValue::Missing => return Err(Error::UnexpectedMissingValue),
+            CellValue::Numeric(x) => x > 0.0,
+            CellValue::String(s) | CellValue::Nominal { label: s, .. } => truthy(s),
+            CellValue::Date(_) => return Err(Error::ConversionError),
+        };
+        visitor.visit_bool(value)
+    }
+
+    deserialize_int! {
+        deserialize_i8, visit_i8, i8;
+        deserialize_i16, visit_i16, i16;
+        deserialize_i32, visit_i32, i32;
+        deserialize_i64, visit_i64, i64;
+        deserialize_u8, visit_u8, u8;
+        deserialize_u16, visit_u16, u16;
+        deserialize_u32, visit_u32, u32;
+        deserialize_u64, visit_u64, u64;
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_f32(self.next_f64()? as f32)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_f64(self.next_f64()?)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let s = self.next_string()?;
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => visitor.visit_char(c),
+            _ => Err(Error::ConversionError),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_string(self.next_string()?)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_string(self.next_string()?)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_byte_buf(self.next_string()?.into_bytes())
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_byte_buf(self.next_string()?.into_bytes())
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.peek() {
+            None => Err(Error::Eof),
+            Some(CellValue::Missing) => {
+                self.next()?;
+                visitor.visit_none()
+            }
+            Some(_) => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.next()?;
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.nested_sequence_depth += 1;
+        let row = self.row;
+        let result = visitor.visit_seq(SequenceAccessor { de: self, row });
+        self.nested_sequence_depth -= 1;
+        result
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value> {
+        Err(de::Error::custom(
+            "maps cannot be deserialized from data frames",
+        ))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_map(StructAccess {
+            de: self,
+            n_fields: fields.len(),
+        })
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_enum(self.next_string()?.into_deserializer())
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if self.peek().is_none() {
+            return Err(Error::Eof);
+        }
+        visitor.visit_str(&self.frame.columns[self.col].name)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.next()?;
+        visitor.visit_unit()
+    }
+}
+
+struct SequenceAccessor<'a, 'de: 'a> {
+    de: &'a mut Deserializer<'de>,
+    row: usize,
+}
+
+impl<'a, 'de> SeqAccess<'de> for SequenceAccessor<'a, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        // nested sequences end with the row
+        if self.de.nested_sequence_depth > 1 && self.de.row != self.row {
+            return Ok(None);
+        }
+        if self.de.peek().is_none() {
+            return Ok(None);
+        }
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+}
+
+struct StructAccess<'a, 'de: 'a> {
+    de: &'a mut Deserializer<'de>,
+    n_fields: usize,
+}
+
+impl<'a, 'de> MapAccess<'de> for StructAccess<'a, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        if self.n_fields == 0 || self.de.peek().is_none() {
+            return Ok(None);
+        }
+        self.n_fields -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        seed.deserialize(&mut *self.de)
+    }
+}
+
+#[test]
+fn deserialize() {
+    use super::{Column, ColumnValues};
+
+    let frame = DataFrame::new(
+        "test",
+        vec![
+            Column::new("int", ColumnValues::Numeric(vec![Some(1.0), Some(4.0)])),
+            Column::new("float", ColumnValues::Numeric(vec![Some(2.5), None])),
+            Column::new(
+                "text",
+                ColumnValues::String(vec![Some("three".to_owned()), Some("7".to_owned())]),
+            ),
+            Column::new(
+                "color",
+                ColumnValues::Nominal {
+                    levels: vec!["red".to_owned(), "green".to_owned(), "blue".to_owned()],
+                    codes: vec![Some(2), Some(0)],
+                },
+            ),
+        ],
+    );
+
+    let x: Vec<(u8, Option<f64>, String, String)> = from_frame(&frame).unwrap();
+    assert_eq!(
+        x,
+        vec![
+            (1, Some(2.5), "three".to_owned(), "blue".to_owned()),
+            (4, None, "7".to_owned(), "red".to_owned()),
+        ]
+    );
+
+    let rows: Vec<(u8, Option<f32>, String, usize)> = from_frame(&frame).unwrap();
+    assert_eq!(rows[1].3, 0);
+
+    // numbers must fit the integer type
+    let numeric = DataFrame::new("numeric", frame.columns()[1..2].to_vec());
+    assert!(from_frame::<Vec<Option<u8>>>(&numeric).is_err());
+    let flat: Vec<Option<f64>> = from_frame(&numeric).unwrap();
+    assert_eq!(flat, vec![Some(2.5), None]);
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    enum Color {
+        #[serde(rename = "red")]
+        Red,
+        #[serde(rename = "blue")]
+        Blue,
+    }
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Row {
+        int: f64,
+        float: Option<f64>,
+        text: String,
+        color: Color,
+    }
+    let rows: Vec<Row> = from_frame(&frame).unwrap();
+    assert_eq!(rows[0].color, Color::Blue);
+    assert_eq!(rows[1].text, "7");
+}
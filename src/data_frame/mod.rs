@@ -0,0 +1,728 @@
+//! Typed, column-oriented view of a data set
+//!
+//! Flows that take `&[f64]` rows lose the information which features are nominal or textual. A
+//! `DataFrame` keeps the ARFF attribute type of every column, and converts to numeric rows when a
+//! flow needs them.
+//!
+//! Missing values (`?` in ARFF) are `None` in the typed columns, and NaN when a column is
+//! converted to numbers. `missing_mask` and `complete_rows` tell where they are, and `impute`
+//! replaces them.
+//!
+//! Date attributes become `Date` columns of `chrono::NaiveDateTime` values in UTC. Flows that are
+//! passed the features directly see dates as seconds since 1970-01-01T00:00:00 UTC, and
+//! `with_epoch_seconds` does the same conversion for a data frame.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use arff::dynamic::{DataSet as ArffDataSet, Value};
+use chrono::NaiveDateTime;
+use num_traits::{AsPrimitive, Float};
+
+use crate::anonymize::quote;
+use crate::error::{Error, Result};
+
+mod csv;
+mod dates;
+mod de;
+#[cfg(feature = "parquet")]
+mod parquet;
+#[cfg(feature = "polars")]
+mod polars;
+mod records;
+mod rows;
+
+pub(crate) use self::de::from_frame;
+pub use self::rows::{CellValue, Row};
+
+/// The values of a column, by attribute type. Missing values are `None`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ColumnValues {
+    /// numeric (integer or real) attribute
+    Numeric(Vec<Option<f64>>),
+    /// nominal attribute; `codes` index into `levels`
+    Nominal {
+        levels: Vec<String>,
+        codes: Vec<Option<usize>>,
+    },
+    /// string attribute
+    String(Vec<Option<String>>),
+    /// date attribute, in UTC
+    Date(Vec<Option<NaiveDateTime>>),
+}
+
+/// The type of an attribute, without its values
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttributeType {
+    /// numeric (integer or real) attribute
+    Numeric,
+    /// nominal attribute with the given levels; values are passed to flows as indices into
+    /// `levels` when they are deserialized as numbers
+    Nominal { levels: Vec<String> },
+    /// string attribute
+    String,
+    /// date attribute
+    Date,
+}
+
+/// Name and type of a feature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Feature {
+    name: String,
+    attribute_type: AttributeType,
+}
+
+impl Feature {
+    /// create a feature description
+    pub fn new(name: &str, attribute_type: AttributeType) -> Self {
+        Feature {
+            name: name.to_owned(),
+            attribute_type,
+        }
+    }
+
+    /// get the attribute name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// get the attribute type
+    pub fn attribute_type(&self) -> &AttributeType {
+        &self.attribute_type
+    }
+
+    /// get the levels of a nominal feature
+    pub fn levels(&self) -> Option<&[String]> {
+        match self.attribute_type {
+            AttributeType::Nominal { ref levels } => Some(levels),
+            _ => None,
+        }
+    }
+}
+
+/// A named, typed column
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Column {
+    name: String,
+    values: ColumnValues,
+}
+
+impl Column {
+    /// create a column from its name and values
+    pub fn new(name: &str, values: ColumnValues) -> Self {
+        Column {
+            name: name.to_owned(),
+            values,
+        }
+    }
+
+    /// get the attribute name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// get the typed values
+    pub fn values(&self) -> &ColumnValues {
+        &self.values
+    }
+
+    /// get the values of a numeric column
+    pub fn as_numeric(&self) -> Option<&[Option<f64>]> {
+        match self.values {
+            ColumnValues::Numeric(ref v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// get the levels and codes of a nominal column
+    pub fn as_nominal(&self) -> Option<(&[String], &[Option<usize>])> {
+        match self.values {
+            ColumnValues::Nominal {
+                ref levels,
+                ref codes,
+            } => Some((levels, codes)),
+            _ => None,
+        }
+    }
+
+    /// get the raw values of a string column, e.g. to tokenize text before training
+    pub fn as_strings(&self) -> Option<&[Option<String>]> {
+        match self.values {
+            ColumnValues::String(ref v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// get the values of a date column
+    pub fn as_dates(&self) -> Option<&[Option<NaiveDateTime>]> {
+        match self.values {
+            ColumnValues::Date(ref v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// get the attribute type
+    pub fn attribute_type(&self) -> AttributeType {
+        match self.values {
+            ColumnValues::Numeric(_) => AttributeType::Numeric,
+            ColumnValues::Nominal { ref levels, .. } => AttributeType::Nominal {
+                levels: levels.clone(),
+            },
+            ColumnValues::String(_) => AttributeType::String,
+            ColumnValues::Date(_) => AttributeType::Date,
+        }
+    }
+
+    /// get the number of rows
+    pub fn len(&self) -> usize {
+        match self.values {
+            ColumnValues::Numeric(ref v) => v.len(),
+            ColumnValues::Nominal { ref codes, .. } => codes.len(),
+            ColumnValues::String(ref v) => v.len(),
+            ColumnValues::Date(ref v) => v.len(),
+        }
+    }
+
+    /// return `true` if the column has no rows
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// return `true` if the value in `row` is missing
+    pub fn is_missing(&self, row: usize) -> bool {
+        match self.values {
+            ColumnValues::Numeric(ref v) => v[row].is_none(),
+            ColumnValues::Nominal { ref codes, .. } => codes[row].is_none(),
+            ColumnValues::String(ref v) => v[row].is_none(),
+            ColumnValues::Date(ref v) => v[row].is_none(),
+        }
+    }
+
+    /// return a mask that is `true` for every missing value
+    pub fn missing_mask(&self) -> Vec<bool> {
+        (0..self.len()).map(|row| self.is_missing(row)).collect()
+    }
+
+    /// get the number of missing values
+    pub fn n_missing(&self) -> usize {
+        (0..self.len()).filter(|&row| self.is_missing(row)).count()
+    }
+
+    /// return a copy of the column with missing values replaced by a statistic of the values in
+    /// `rows`: the mean for numeric and date columns, and the most frequent value for nominal and
+    /// string columns. If all these values are missing too, `0`, the first level, the empty string
+    /// or 1970-01-01 is used.
+    pub fn impute(&self, rows: &[usize]) -> Column {
+        let values = match self.values {
+            ColumnValues::Numeric(ref v) => {
+                let known: Vec<f64> = rows.iter().filter_map(|&i| v[i]).collect();
+                let mean = if known.is_empty() {
+                    0.0
+                } else {
+                    known.iter().sum::<f64>() / known.len() as f64
+                };
+                ColumnValues::Numeric(v.iter().map(|x| Some(x.unwrap_or(mean))).collect())
+            }
+            ColumnValues::Nominal {
+                ref levels,
+                ref codes,
+            } => {
+                let mode = most_frequent(rows.iter().filter_map(|&i| codes[i])).unwrap_or(0);
+                ColumnValues::Nominal {
+                    levels: levels.clone(),
+                    codes: codes.iter().map(|c| Some(c.unwrap_or(mode))).collect(),
+                }
+            }
+            ColumnValues::String(ref v) => {
+                let mode = most_frequent(rows.iter().filter_map(|&i| v[i].as_ref()))
+                    .cloned()
+                    .unwrap_or_default();
+                ColumnValues::String(
+                    v.iter()
+                        .map(|s| Some(s.clone().unwrap_or_else(|| mode.clone())))
+                        .collect(),
+                )
+            }
+            ColumnValues::Date(ref v) => {
+                let known: Vec<f64> = rows
+                    .iter()
+                    .filter_map(|&i| v[i].as_ref().map(dates::epoch_seconds))
+                    .collect();
+                let mean = if known.is_empty() {
+                    0.0
+                } else {
+                    known.iter().sum::<f64>() / known.len() as f64
+                };
+                let mean = dates::from_epoch_seconds(mean).unwrap_or_default();
+                ColumnValues::Date(v.iter().map(|d| Some(d.unwrap_or(mean))).collect())
+            }
+        };
+        Column::new(&self.name, values)
+    }
+
+    /// convert the column to numbers; nominal values become their codes, dates become seconds
+    /// since 1970-01-01T00:00:00 UTC and missing values become NaN. Returns `None` for string
+    /// columns.
+    pub fn to_f64(&self) -> Option<Vec<f64>> {
+        self.to_numeric()
+    }
+
+    /// convert the column to numbers of type `T`, typically `f32` or `f64`; see `to_f64`
+    pub fn to_numeric<T>(&self) -> Option<Vec<T>>
+    where
+        T: Float + 'static,
+        f64: AsPrimitive<T>,
+    {
+        let nan = T::nan();
+        match self.values {
+            ColumnValues::Numeric(ref v) => Some(
+                v.iter()
+                    .map(|x| x.map(|x| x.as_()).unwrap_or(nan))
+                    .collect(),
+            ),
+            ColumnValues::Nominal { ref codes, .. } => Some(
+                codes
+                    .iter()
+                    .map(|c| c.map(|c| (c as f64).as_()).unwrap_or(nan))
+                    .collect(),
+            ),
+            ColumnValues::Date(ref v) => Some(
+                v.iter()
+                    .map(|d| d.map(|d| dates::epoch_seconds(&d).as_()).unwrap_or(nan))
+                    .collect(),
+            ),
+            ColumnValues::String(_) => None,
+        }
+    }
+
+    fn from_arff(col: &arff::dynamic::Column) -> Self {
+        let items: Vec<_> = (0..col.len()).map(|i| col.item(i)).collect();
+
+        // the type of a column can only be told from a value that is not missing
+        let values = match items.iter().find(|v| **v != Value::Missing) {
+            Some(Value::String(_)) => ColumnValues::String(
+                items
+                    .iter()
+                    .map(|v| v.as_str().ok().map(str::to_owned))
+                    .collect(),
+            ),
+            Some(Value::Nominal(_, levels)) => ColumnValues::Nominal {
+                levels: levels.to_vec(),
+                codes: items
+                    .iter()
+                    .map(|v| match *v {
+                        Value::Nominal(code, _) => Some(code),
+                        _ => None,
+                    })
+                    .collect(),
+            },
+            _ => ColumnValues::Numeric(items.iter().map(|v| v.as_f64().ok()).collect()),
+        };
+
+        Column::new(col.name(), values)
+    }
+}
+
+/// A data set as a list of typed columns of equal length
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DataFrame {
+    name: String,
+    columns: Vec<Column>,
+}
+
+impl DataFrame {
+    /// create a data frame from columns. Panics if the columns differ in length.
+    pub fn new(name: &str, columns: Vec<Column>) -> Self {
+        if let Some(first) = columns.first() {
+            assert!(
+                columns.iter().all(|c| c.len() == first.len()),
+                "all columns must have the same length"
+            );
+        }
+        DataFrame {
+            name: name.to_owned(),
+            columns,
+        }
+    }
+
+    /// convert a dynamically typed ARFF data set
+    pub fn from_arff(data: &ArffDataSet) -> Self {
+        let columns = (0..data.n_cols())
+            .map(|i| Column::from_arff(data.col(i)))
+            .collect();
+        DataFrame::new(data.name(), columns)
+    }
+
+    /// get the name of the data set
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// get the number of rows
+    pub fn n_rows(&self) -> usize {
+        self.columns.first().map(Column::len).unwrap_or(0)
+    }
+
+    /// get the number of columns
+    pub fn n_cols(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// estimate the number of bytes the values occupy on the heap
+    pub(crate) fn heap_size(&self) -> usize {
+        self.columns
+            .iter()
+            .map(|c| match c.values {
+                ColumnValues::Numeric(ref v) => v.len() * std::mem::size_of::<Option<f64>>(),
+                ColumnValues::Nominal {
+                    ref levels,
+                    ref codes,
+                } => {
+                    levels.iter().map(String::len).sum::<usize>()
+                        + codes.len() * std::mem::size_of::<Option<usize>>()
+                }
+                ColumnValues::String(ref v) => v
+                    .iter()
+                    .map(|s| {
+                        std::mem::size_of::<Option<String>>() + s.as_ref().map_or(0, String::len)
+                    })
+                    .sum(),
+                ColumnValues::Date(ref v) => v.len() * std::mem::size_of::<Option<NaiveDateTime>>(),
+            })
+            .sum()
+    }
+
+    /// get all columns
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// iterate over the column names
+    pub fn column_names(&self) -> impl Iterator<Item = &str> {
+        self.columns.iter().map(Column::name)
+    }
+
+    /// get a column by name
+    pub fn column(&self, name: &str) -> Option<&Column> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+
+    /// get the names and types of all columns
+    pub fn features(&self) -> Vec<Feature> {
+        self.columns
+            .iter()
+            .map(|c| Feature::new(&c.name, c.attribute_type()))
+            .collect()
+    }
+
+    /// return a mask that is `true` for every row without missing values
+    pub fn complete_rows(&self) -> Vec<bool> {
+        (0..self.n_rows())
+            .map(|row| self.columns.iter().all(|c| !c.is_missing(row)))
+            .collect()
+    }
+
+    /// return a copy of the data frame without the rows that contain missing values
+    pub fn drop_incomplete_rows(&self) -> DataFrame {
+        let complete = self.complete_rows();
+        let keep = |i: usize| complete[i];
+        let columns = self
+            .columns
+            .iter()
+            .map(|c| {
+                let values = match c.values {
+                    ColumnValues::Numeric(ref v) => ColumnValues::Numeric(filter_rows(v, keep)),
+                    ColumnValues::Nominal {
+                        ref levels,
+                        ref codes,
+                    } => ColumnValues::Nominal {
+                        levels: levels.clone(),
+                        codes: filter_rows(codes, keep),
+                    },
+                    ColumnValues::String(ref v) => ColumnValues::String(filter_rows(v, keep)),
+                    ColumnValues::Date(ref v) => ColumnValues::Date(filter_rows(v, keep)),
+                };
+                Column::new(&c.name, values)
+            })
+            .collect();
+        DataFrame::new(&self.name, columns)
+    }
+
+    /// return a copy of the data frame with missing values replaced by statistics of the values
+    /// in `rows`; see `Column::impute`. Pass the rows of a training set to keep testing data from
+    /// influencing the imputed values.
+    pub fn impute(&self, rows: &[usize]) -> DataFrame {
+        let columns = self.columns.iter().map(|c| c.impute(rows)).collect();
+        DataFrame::new(&self.name, columns)
+    }
+
+    /// return a copy of the data frame with missing numeric values replaced by NaN; missing
+    /// nominal and string values are kept.
+    pub fn with_nan_markers(&self) -> DataFrame {
+        let columns = self
+            .columns
+            .iter()
+            .map(|c| match c.values {
+                ColumnValues::Numeric(ref v) => Column::new(
+                    &c.name,
+                    ColumnValues::Numeric(v.iter().map(|x| Some(x.unwrap_or(f64::NAN))).collect()),
+                ),
+                _ => c.clone(),
+            })
+            .collect();
+        DataFrame::new(&self.name, columns)
+    }
+
+    /// return a copy of the data frame with date columns converted to numeric columns of seconds
+    /// since 1970-01-01T00:00:00 UTC, e.g. for numeric flows
+    pub fn with_epoch_seconds(&self) -> DataFrame {
+        let columns = self
+            .columns
+            .iter()
+            .map(|c| match c.values {
+                ColumnValues::Date(ref v) => Column::new(
+                    &c.name,
+                    ColumnValues::Numeric(
+                        v.iter()
+                            .map(|d| d.as_ref().map(dates::epoch_seconds))
+                            .collect(),
+                    ),
+                ),
+                _ => c.clone(),
+            })
+            .collect();
+        DataFrame::new(&self.name, columns)
+    }
+
+    /// turn the numeric columns with the given names, which hold epoch seconds, into date columns
+    pub(crate) fn with_date_columns(mut self, names: &[String]) -> DataFrame {
+        for column in &mut self.columns {
+            if let (true, ColumnValues::Numeric(ref v)) =
+                (names.contains(&column.name), &column.values)
+            {
+                column.values = ColumnValues::Date(
+                    v.iter()
+                        .map(|x| x.and_then(dates::from_epoch_seconds))
+                        .collect(),
+                );
+            }
+        }
+        self
+    }
+
+    /// write the data frame in ARFF format
+    pub fn to_arff(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "@RELATION {}", quote(&self.name)).unwrap();
+        for column in &self.columns {
+            let kind = match column.values {
+                ColumnValues::Numeric(_) => "NUMERIC".to_owned(),
+                ColumnValues::Nominal { ref levels, .. } => {
+                    let levels: Vec<_> = levels.iter().map(|l| quote(l)).collect();
+                    format!("{{{}}}", levels.join(","))
+                }
+                ColumnValues::String(_) => "STRING".to_owned(),
+                ColumnValues::Date(_) => "DATE".to_owned(),
+            };
+            writeln!(out, "@ATTRIBUTE {} {}", quote(&column.name), kind).unwrap();
+        }
+        writeln!(out, "@DATA").unwrap();
+
+        for row in 0..self.n_rows() {
+            let values: Vec<_> =
+                self.columns
+                    .iter()
+                    .map(|c| match c.values {
+                        ColumnValues::Numeric(ref v) => v[row].map(|x| x.to_string()),
+                        ColumnValues::Nominal {
+                            ref levels,
+                            ref codes,
+                        } => codes[row].map(|code| quote(&levels[code])),
+                        ColumnValues::String(ref v) => v[row].as_ref().map(|s| quote(s)),
+                        ColumnValues::Date(ref v) => v[row]
+                            .map(|d| quote(&d.format(dates::DEFAULT_CHRONO_FORMAT).to_string())),
+                    })
+                    .map(|v| v.unwrap_or_else(|| "?".to_owned()))
+                    .collect();
+            writeln!(out, "{}", values.join(",")).unwrap();
+        }
+        out
+    }
+
+    /// parse a data set in ARFF format, including date attributes
+    pub(crate) fn parse_arff(input: &str) -> Result<DataFrame> {
+        let (data, date_columns) = dates::parse_arff(input)?;
+        Ok(DataFrame::from_arff(&data).with_date_columns(&date_columns))
+    }
+
+    /// convert all columns to numbers and return the data in row-major order, e.g. for flows
+    /// that expect `&[f64]` rows. Nominal values become their codes, dates become epoch seconds
+    /// and missing values become NaN.
+    ///
+    /// Fails if there is a string column.
+    pub fn to_numeric_rows(&self) -> Result<Vec<Vec<f64>>> {
+        self.to_numeric_rows_as()
+    }
+
+    /// convert all columns to numbers of type `T` like `to_numeric_rows`. With `f32` the rows need
+    /// half the memory of `f64` rows.
+    pub fn to_numeric_rows_as<T>(&self) -> Result<Vec<Vec<T>>>
+    where
+        T: Float + 'static,
+        f64: AsPrimitive<T>,
+    {
+        let columns = self
+            .columns
+            .iter()
+            .map(|c| {
+                c.to_numeric().ok_or_else(|| Error::ColumnError {
+                    column: c.name.clone(),
+                    message: "string column cannot be converted to numbers".to_owned(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok((0..self.n_rows())
+            .map(|row| columns.iter().map(|c| c[row]).collect())
+            .collect())
+    }
+}
+
+fn filter_rows<T: Clone>(values: &[T], keep: impl Fn(usize) -> bool) -> Vec<T> {
+    values
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| keep(i))
+        .map(|(_, v)| v.clone())
+        .collect()
+}
+
+/// find the most frequent item; ties are broken in favor of the item seen first
+fn most_frequent<T: Eq + std::hash::Hash + Copy>(items: impl Iterator<Item = T>) -> Option<T> {
+    let mut counts = HashMap::new();
+    let mut order = vec![];
+    for item in items {
+        let count = counts.entry(item).or_insert(0);
+        if *count == 0 {
+            order.push(item);
+        }
+        *count += 1;
+    }
+    order.into_iter().rev().max_by_key(|item| counts[item])
+}
+
+#[test]
+fn from_arff() {
+    let data = ArffDataSet::from_str(
+        "@RELATION test\n\
+         @ATTRIBUTE size NUMERIC\n\
+         @ATTRIBUTE color {red,green}\n\
+         @ATTRIBUTE note STRING\n\
+         @DATA\n\
+         1,green,'a'\n\
+         2.5,?,?\n",
+    )
+    .unwrap();
+
+    let frame = DataFrame::from_arff(&data);
+    assert_eq!(frame.features()[1].levels().unwrap().len(), 2);
+    assert_eq!(frame.features()[2].attribute_type(), &AttributeType::String);
+    assert_eq!(frame.n_rows(), 2);
+    assert_eq!(
+        frame.column_names().collect::<Vec<_>>(),
+        vec!["size", "color", "note"]
+    );
+    assert_eq!(
+        frame.column("size").unwrap().values(),
+        &ColumnValues::Numeric(vec![Some(1.0), Some(2.5)])
+    );
+    assert_eq!(
+        frame.column("color").unwrap().values(),
+        &ColumnValues::Nominal {
+            levels: vec!["red".to_owned(), "green".to_owned()],
+            codes: vec![Some(1), None],
+        }
+    );
+    assert!(frame.column("note").unwrap().is_missing(1));
+
+    assert!(frame.to_numeric_rows().is_err());
+
+    let numeric = DataFrame::new("numeric", frame.columns()[..2].to_vec());
+    let rows = numeric.to_numeric_rows().unwrap();
+    assert_eq!(rows[0], vec![1.0, 1.0]);
+    assert!(rows[1][1].is_nan());
+    let rows: Vec<Vec<f32>> = numeric.to_numeric_rows_as().unwrap();
+    assert_eq!(rows[1][0], 2.5f32);
+}
+
+#[test]
+fn missing_values() {
+    let frame = DataFrame::new(
+        "test",
+        vec![
+            Column::new(
+                "x",
+                ColumnValues::Numeric(vec![Some(1.0), None, Some(3.0), Some(8.0)]),
+            ),
+            Column::new(
+                "c",
+                ColumnValues::Nominal {
+                    levels: vec!["a".to_owned(), "b".to_owned()],
+                    codes: vec![Some(1), Some(1), None, Some(0)],
+                },
+            ),
+        ],
+    );
+
+    assert_eq!(
+        frame.columns()[0].missing_mask(),
+        vec![false, true, false, false]
+    );
+    assert_eq!(frame.columns()[1].n_missing(), 1);
+    assert_eq!(frame.complete_rows(), vec![true, false, false, true]);
+    assert_eq!(frame.drop_incomplete_rows().n_rows(), 2);
+
+    // statistics are computed from the given rows only
+    let imputed = frame.impute(&[0, 1, 2]);
+    assert_eq!(
+        imputed.columns()[0].values(),
+        &ColumnValues::Numeric(vec![Some(1.0), Some(2.0), Some(3.0), Some(8.0)])
+    );
+    assert_eq!(imputed.complete_rows(), vec![true; 4]);
+    assert_eq!(
+        imputed.columns()[1].to_f64().unwrap(),
+        vec![1.0, 1.0, 1.0, 0.0]
+    );
+
+    let marked = DataFrame::parse_arff(&frame.with_nan_markers().to_arff()).unwrap();
+    assert!(marked.columns()[0].to_f64().unwrap()[1].is_nan());
+    assert!(marked.columns()[1].is_missing(2));
+    assert_eq!(DataFrame::parse_arff(&frame.to_arff()).unwrap(), frame);
+}
+
+#[test]
+fn date_columns() {
+    let day = chrono::NaiveDate::from_ymd_opt(1970, 1, 2)
+        .and_then(|d| d.and_hms_opt(0, 0, 10))
+        .unwrap();
+    let frame = DataFrame::new(
+        "test",
+        vec![Column::new(
+            "when",
+            ColumnValues::Date(vec![Some(day), None]),
+        )],
+    );
+
+    assert_eq!(frame.features()[0].attribute_type(), &AttributeType::Date);
+    assert_eq!(frame.columns()[0].to_f64().unwrap()[0], 86410.0);
+    assert_eq!(
+        frame.with_epoch_seconds().columns()[0].values(),
+        &ColumnValues::Numeric(vec![Some(86410.0), None])
+    );
+    assert_eq!(
+        frame.impute(&[0]).columns()[0].as_dates().unwrap(),
+        &[Some(day), Some(day)]
+    );
+
+    assert_eq!(DataFrame::parse_arff(&frame.to_arff()).unwrap(), frame);
+}
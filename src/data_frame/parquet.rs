@@ -0,0 +1,241 @@
+//! Reading data frames from Parquet files, enabled by the `parquet` feature
+
+use std::fs::File;
+
+use arrow_array::cast::AsArray;
+use arrow_array::temporal_conversions::as_datetime;
+use arrow_array::types::{
+    Date32Type, Date64Type, Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type,
+    TimestampMicrosecondType, TimestampMillisecondType, TimestampNanosecondType,
+    TimestampSecondType, UInt16Type, UInt32Type, UInt64Type, UInt8Type,
+};
+use arrow_array::{Array, RecordBatchReader};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::errors::ParquetError;
+
+use crate::error::{Error, Result};
+
+use super::{Column, ColumnValues, DataFrame};
+
+impl DataFrame {
+    /// read a Parquet file as written by OpenML. Numeric columns become numeric columns,
+    /// dictionary-encoded (categorical) and boolean columns become nominal columns, string
+    /// columns become string columns, and date and timestamp columns become date columns.
+    pub fn read_parquet(name: &str, file: File) -> Result<Self> {
+        let reader = ParquetRecordBatchReaderBuilder::try_new(file)?.build()?;
+        let schema = reader.schema();
+
+        let mut columns: Vec<_> = schema
+            .fields()
+            .iter()
+            .map(|field| Column::new(field.name(), ColumnValues::Numeric(vec![])))
+            .collect();
+        let mut first = true;
+
+        for batch in reader {
+            let batch = batch.map_err(ParquetError::from)?;
+            for (column, array) in columns.iter_mut().zip(batch.columns()) {
+                let values = convert(array.as_ref())
+                    .ok_or_else(|| column_error(&column.name, array.data_type()))?;
+                if first {
+                    column.values = values;
+                } else if !append(&mut column.values, values) {
+                    return Err(column_error(&column.name, array.data_type()));
+                }
+            }
+            first = false;
+        }
+
+        Ok(DataFrame::new(name, columns))
+    }
+}
+
+/// convert an Arrow array into column values, or return `None` if its type is not supported
+fn convert(array: &dyn Array) -> Option<ColumnValues> {
+    use arrow_array::types::ArrowPrimitiveType;
+    use arrow_schema::{DataType, TimeUnit};
+
+    fn numeric<T: ArrowPrimitiveType>(array: &dyn Array, f: fn(T::Native) -> f64) -> ColumnValues
+    where
+        T::Native: Copy,
+    {
+        ColumnValues::Numeric(array.as_primitive::<T>().iter().map(|v| v.map(f)).collect())
+    }
+
+    fn date<T: ArrowPrimitiveType>(array: &dyn Array) -> ColumnValues
+    where
+        i64: From<T::Native>,
+    {
+        ColumnValues::Date(
+            array
+                .as_primitive::<T>()
+                .iter()
+                .map(|v| v.and_then(|v| as_datetime::<T>(v.into())))
+                .collect(),
+        )
+    }
+
+    let values = match array.data_type() {
+        DataType::Float64 => numeric::<Float64Type>(array, |x| x),
+        DataType::Float32 => numeric::<Float32Type>(array, f64::from),
+        DataType::Int8 => numeric::<Int8Type>(array, f64::from),
+        DataType::Int16 => numeric::<Int16Type>(array, f64::from),
+        DataType::Int32 => numeric::<Int32Type>(array, f64::from),
+        DataType::Int64 => numeric::<Int64Type>(array, |x| x as f64),
+        DataType::UInt8 => numeric::<UInt8Type>(array, f64::from),
+        DataType::UInt16 => numeric::<UInt16Type>(array, f64::from),
+        DataType::UInt32 => numeric::<UInt32Type>(array, f64::from),
+        DataType::UInt64 => numeric::<UInt64Type>(array, |x| x as f64),
+        // time zones are ignored, as arrow stores timestamps in UTC
+        DataType::Timestamp(TimeUnit::Second, _) => date::<TimestampSecondType>(array),
+        DataType::Timestamp(TimeUnit::Millisecond, _) => date::<TimestampMillisecondType>(array),
+        DataType::Timestamp(TimeUnit::Microsecond, _) => date::<TimestampMicrosecondType>(array),
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => date::<TimestampNanosecondType>(array),
+        DataType::Date32 => date::<Date32Type>(array),
+        DataType::Date64 => date::<Date64Type>(array),
+        DataType::Boolean => ColumnValues::Nominal {
+            levels: vec!["False".to_owned(), "True".to_owned()],
+            codes: array
+                .as_boolean()
+                .iter()
+                .map(|v| v.map(usize::from))
+                .collect(),
+        },
+        DataType::Utf8 => ColumnValues::String(
+            array
+                .as_string::<i32>()
+                .iter()
+                .map(|v| v.map(str::to_owned))
+                .collect(),
+        ),
+        DataType::LargeUtf8 => ColumnValues::String(
+            array
+                .as_string::<i64>()
+                .iter()
+                .map(|v| v.map(str::to_owned))
+                .collect(),
+        ),
+        DataType::Dictionary(_, value_type) if **value_type == DataType::Utf8 => {
+            let dictionary = array.as_any_dictionary();
+            let levels: Vec<String> = dictionary
+                .values()
+                .as_string::<i32>()
+                .iter()
+                .map(|v| v.unwrap_or_default().to_owned())
+                .collect();
+            let keys = if levels.is_empty() {
+                vec![0; array.len()]
+            } else {
+                dictionary.normalized_keys()
+            };
+            ColumnValues::Nominal {
+                levels,
+                codes: keys
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, key)| if array.is_null(i) { None } else { Some(key) })
+                    .collect(),
+            }
+        }
+        _ => return None,
+    };
+    Some(values)
+}
+
+/// append the values of another record batch; returns `false` if the types do not match
+fn append(values: &mut ColumnValues, more: ColumnValues) -> bool {
+    match (values, more) {
+        (ColumnValues::Numeric(v), ColumnValues::Numeric(more)) => v.extend(more),
+        (ColumnValues::String(v), ColumnValues::String(more)) => v.extend(more),
+        (ColumnValues::Date(v), ColumnValues::Date(more)) => v.extend(more),
+        (
+            ColumnValues::Nominal { levels, codes },
+            ColumnValues::Nominal {
+                levels: more_levels,
+                codes: more_codes,
+            },
+        ) => {
+            // the dictionary may differ between batches, so codes are translated via the labels
+            for code in more_codes {
+                codes.push(code.map(|c| {
+                    let label = &more_levels[c];
+                    match levels.iter().position(|l| l == label) {
+                        Some(pos) => pos,
+                        None => {
+                            levels.push(label.clone());
+                            levels.len() - 1
+                        }
+                    }
+                }));
+            }
+        }
+        _ => return false,
+    }
+    true
+}
+
+fn column_error(column: &str, data_type: &arrow_schema::DataType) -> Error {
+    Error::ColumnError {
+        column: column.to_owned(),
+        message: format!("unsupported Parquet column type {}", data_type),
+    }
+}
+
+#[test]
+fn read_parquet() {
+    use std::sync::Arc;
+
+    use arrow_array::{
+        BooleanArray, DictionaryArray, Float64Array, RecordBatch, StringArray, UInt8Array,
+    };
+    use parquet::arrow::ArrowWriter;
+
+    let class: DictionaryArray<Int8Type> = vec![Some("b"), None, Some("a")].into_iter().collect();
+    let batch = RecordBatch::try_from_iter(vec![
+        (
+            "x",
+            Arc::new(Float64Array::from(vec![Some(1.5), None, Some(3.0)])) as _,
+        ),
+        ("n", Arc::new(UInt8Array::from(vec![1, 2, 3])) as _),
+        (
+            "flag",
+            Arc::new(BooleanArray::from(vec![true, false, true])) as _,
+        ),
+        (
+            "text",
+            Arc::new(StringArray::from(vec![Some("a"), Some("b"), None])) as _,
+        ),
+        ("class", Arc::new(class) as _),
+    ])
+    .unwrap();
+
+    let path = std::env::temp_dir().join(format!("openml-test-{}.parquet", std::process::id()));
+    let mut writer =
+        ArrowWriter::try_new(File::create(&path).unwrap(), batch.schema(), None).unwrap();
+    writer.write(&batch).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+
+    let frame = DataFrame::read_parquet("test", File::open(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(frame.n_rows(), 6);
+    assert_eq!(
+        frame.column_names().collect::<Vec<_>>(),
+        vec!["x", "n", "flag", "text", "class"]
+    );
+    assert!(frame.column("x").unwrap().is_missing(1));
+    assert_eq!(frame.column("n").unwrap().to_f64().unwrap()[2], 3.0);
+    assert_eq!(
+        frame.column("flag").unwrap().to_f64().unwrap()[..3],
+        [1.0, 0.0, 1.0]
+    );
+    assert!(frame.column("text").unwrap().is_missing(5));
+    assert_eq!(
+        frame.column("class").unwrap().values(),
+        &ColumnValues::Nominal {
+            levels: vec!["b".to_owned(), "a".to_owned()],
+            codes: vec![Some(0), None, Some(1), Some(0), None, Some(1)],
+        }
+    );
+}
@@ -0,0 +1,85 @@
+//! Conversion of data frames into Polars data frames, enabled by the `polars` feature
+
+use polars::prelude::{
+    CategoricalOrdering, DataFrame as PolarsDataFrame, DataType, IntoColumn, NamedFrom,
+    PolarsResult, Series, TimeUnit,
+};
+
+use super::{ColumnValues, DataFrame};
+
+impl DataFrame {
+    /// convert into a Polars data frame with the same column names. Numeric columns become
+    /// `Float64` columns, nominal columns `Categorical` columns, string columns `String` columns
+    /// and date columns `Datetime` columns; missing values become nulls.
+    ///
+    /// Polars only keeps the levels of a categorical column that occur in the data, so levels
+    /// that are declared but never used are lost.
+    pub fn to_polars(&self) -> PolarsResult<PolarsDataFrame> {
+        let columns = self
+            .columns
+            .iter()
+            .map(|c| {
+                let name = c.name.as_str().into();
+                let series = match c.values {
+                    ColumnValues::Numeric(ref v) => Series::new(name, v),
+                    ColumnValues::Nominal {
+                        ref levels,
+                        ref codes,
+                    } => {
+                        let labels: Vec<Option<&str>> = codes
+                            .iter()
+                            .map(|c| c.map(|c| levels[c].as_str()))
+                            .collect();
+                        Series::new(name, labels)
+                            .cast(&DataType::Categorical(None, CategoricalOrdering::Physical))?
+                    }
+                    ColumnValues::String(ref v) => Series::new(name, v),
+                    ColumnValues::Date(ref v) => {
+                        let millis: Vec<Option<i64>> = v
+                            .iter()
+                            .map(|d| d.map(|d| d.and_utc().timestamp_millis()))
+                            .collect();
+                        Series::new(name, millis)
+                            .cast(&DataType::Datetime(TimeUnit::Milliseconds, None))?
+                    }
+                };
+                Ok(series.into_column())
+            })
+            .collect::<PolarsResult<Vec<_>>>()?;
+
+        PolarsDataFrame::new(columns)
+    }
+}
+
+#[test]
+fn to_polars() {
+    use super::Column;
+
+    let frame = DataFrame::new(
+        "test",
+        vec![
+            Column::new("x", ColumnValues::Numeric(vec![Some(1.5), None])),
+            Column::new(
+                "class",
+                ColumnValues::Nominal {
+                    levels: vec!["a".to_owned(), "b".to_owned()],
+                    codes: vec![Some(1), Some(0)],
+                },
+            ),
+            Column::new(
+                "text",
+                ColumnValues::String(vec![Some("hello".to_owned()), None]),
+            ),
+        ],
+    );
+
+    let df = frame.to_polars().unwrap();
+    assert_eq!(df.shape(), (2, 3));
+    assert_eq!(df.column("x").unwrap().dtype(), &DataType::Float64);
+    assert_eq!(df.column("x").unwrap().null_count(), 1);
+    assert!(matches!(
+        df.column("class").unwrap().dtype(),
+        DataType::Categorical(..)
+    ));
+    assert_eq!(df.column("text").unwrap().dtype(), &DataType::String);
+}
@@ -0,0 +1,156 @@
+//! Deserialization of data frame rows into user defined records
+
+use std::fmt;
+
+use serde::de::{self, DeserializeOwned, Visitor};
+
+use crate::error::{Error, Result};
+
+use super::{from_frame, DataFrame};
+
+impl DataFrame {
+    /// deserialize every row into a record of type `T`, typically a struct that derives
+    /// `Deserialize`. Columns are matched to struct fields by name, so the field order does not
+    /// matter and columns without a field are skipped. Nominal values deserialize into strings
+    /// (their labels) or numbers (their codes), dates into epoch seconds, and missing values into
+    /// `None` if the field is an `Option`.
+    ///
+    /// ```no_run
+    /// # extern crate openml;
+    /// # #[macro_use]
+    /// # extern crate serde_derive;
+    /// use openml::SupervisedClassification;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct IrisRow {
+    ///     sepallength: f64,
+    ///     petalwidth: f64,
+    ///     class: String,
+    /// }
+    ///
+    /// # fn main() {
+    /// let task = SupervisedClassification::from_openml(59).unwrap();
+    /// let rows: Vec<IrisRow> = task.data_frame().deserialize_rows().unwrap();
+    /// # }
+    /// ```
+    ///
+    /// Types other than structs, such as tuples or `Vec<f64>`, are filled from all columns in
+    /// order.
+    pub fn deserialize_rows<T: DeserializeOwned>(&self) -> Result<Vec<T>> {
+        let frame = match struct_fields::<T>() {
+            Some(fields) => {
+                let columns = fields
+                    .iter()
+                    .map(|&field| {
+                        self.column(field)
+                            .cloned()
+                            .ok_or_else(|| Error::ColumnError {
+                                column: field.to_owned(),
+                                message: "no column for this field".to_owned(),
+                            })
+                    })
+                    .collect::<Result<_>>()?;
+                DataFrame::new(&self.name, columns)
+            }
+            None => self.clone(),
+        };
+        Ok(from_frame(&frame)?)
+    }
+}
+
+/// get the field names of `T` if it deserializes from a struct
+fn struct_fields<T: DeserializeOwned>() -> Option<&'static [&'static str]> {
+    let mut fields = None;
+    let _ = T::deserialize(FieldNames(&mut fields));
+    fields
+}
+
+/// A deserializer that only records the field names of a struct and then gives up
+struct FieldNames<'a>(&'a mut Option<&'static [&'static str]>);
+
+#[derive(Debug)]
+struct Stop;
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "not a struct")
+    }
+}
+
+impl std::error::Error for Stop {}
+
+impl de::Error for Stop {
+    fn custom<M: fmt::Display>(_msg: M) -> Self {
+        Stop
+    }
+}
+
+impl<'de, 'a> de::Deserializer<'de> for FieldNames<'a> {
+    type Error = Stop;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> std::result::Result<V::Value, Stop> {
+        Err(Stop)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> std::result::Result<V::Value, Stop> {
+        *self.0 = Some(fields);
+        Err(Stop)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier
+        ignored_any
+    }
+}
+
+#[test]
+fn deserialize_rows() {
+    use arff::dynamic::DataSet as ArffDataSet;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Record {
+        class: String,
+        size: Option<f64>,
+    }
+
+    let data = ArffDataSet::from_str(
+        "@RELATION test\n\
+         @ATTRIBUTE size NUMERIC\n\
+         @ATTRIBUTE note STRING\n\
+         @ATTRIBUTE class {red,green}\n\
+         @DATA\n\
+         1,'a',green\n\
+         ?,'b',red\n",
+    )
+    .unwrap();
+
+    let rows: Vec<Record> = DataFrame::from_arff(&data).deserialize_rows().unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            Record {
+                class: "green".to_owned(),
+                size: Some(1.0)
+            },
+            Record {
+                class: "red".to_owned(),
+                size: None
+            },
+        ]
+    );
+
+    #[derive(Debug, Deserialize)]
+    struct Other {
+        #[allow(dead_code)]
+        weight: f64,
+    }
+    assert!(DataFrame::from_arff(&data)
+        .deserialize_rows::<Other>()
+        .is_err());
+}
@@ -0,0 +1,152 @@
+//! Row-wise access to data frames
+
+use chrono::NaiveDateTime;
+
+use super::{dates, ColumnValues, DataFrame};
+
+/// A single value of a row
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CellValue<'a> {
+    Missing,
+    Numeric(f64),
+    Nominal { code: usize, label: &'a str },
+    String(&'a str),
+    Date(NaiveDateTime),
+}
+
+impl<'a> CellValue<'a> {
+    /// get the value as a number; nominal values are represented by their codes and dates by
+    /// epoch seconds. Returns `None` for missing values and strings.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            CellValue::Numeric(x) => Some(x),
+            CellValue::Nominal { code, .. } => Some(code as f64),
+            CellValue::Date(ref d) => Some(dates::epoch_seconds(d)),
+            CellValue::Missing | CellValue::String(_) => None,
+        }
+    }
+}
+
+/// A row of a data frame. Rows borrow the values from the data frame instead of copying them.
+#[derive(Debug, Clone, Copy)]
+pub struct Row<'a> {
+    frame: &'a DataFrame,
+    index: usize,
+}
+
+impl<'a> Row<'a> {
+    /// get the index of the row in the data frame
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// get the number of values
+    pub fn len(&self) -> usize {
+        self.frame.n_cols()
+    }
+
+    /// return `true` if the data frame has no columns
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// get the value in a column
+    pub fn get(&self, col: usize) -> CellValue<'a> {
+        let i = self.index;
+        match self.frame.columns[col].values {
+            ColumnValues::Numeric(ref v) => v[i].map(CellValue::Numeric),
+            ColumnValues::Nominal {
+                ref levels,
+                ref codes,
+            } => codes[i].map(|code| CellValue::Nominal {
+                code,
+                label: &levels[code],
+            }),
+            ColumnValues::String(ref v) => v[i].as_deref().map(CellValue::String),
+            ColumnValues::Date(ref v) => v[i].map(CellValue::Date),
+        }
+        .unwrap_or(CellValue::Missing)
+    }
+
+    /// get the value in the column with the given name
+    pub fn get_by_name(&self, name: &str) -> Option<CellValue<'a>> {
+        let col = self.frame.columns.iter().position(|c| c.name == name)?;
+        Some(self.get(col))
+    }
+
+    /// iterate over the values in column order
+    pub fn values(&self) -> impl Iterator<Item = CellValue<'a>> {
+        let row = *self;
+        (0..self.len()).map(move |col| row.get(col))
+    }
+
+    /// convert the row to numbers; see `CellValue::as_f64`. Missing values and strings become
+    /// NaN.
+    pub fn to_f64(&self) -> Vec<f64> {
+        self.values()
+            .map(|v| v.as_f64().unwrap_or(f64::NAN))
+            .collect()
+    }
+}
+
+impl DataFrame {
+    /// get a row
+    pub fn row(&self, index: usize) -> Row<'_> {
+        assert!(index < self.n_rows(), "row {} out of range", index);
+        Row { frame: self, index }
+    }
+
+    /// iterate over all rows without copying the data, e.g. to train online learners
+    pub fn rows(&self) -> impl Iterator<Item = Row<'_>> {
+        (0..self.n_rows()).map(move |index| Row { frame: self, index })
+    }
+
+    /// iterate over the rows with the given indices, e.g. the training set of a fold
+    pub fn rows_at<'a>(&'a self, indices: &'a [usize]) -> impl Iterator<Item = Row<'a>> {
+        indices.iter().map(move |&index| self.row(index))
+    }
+}
+
+#[test]
+fn rows() {
+    use super::Column;
+
+    let frame = DataFrame::new(
+        "test",
+        vec![
+            Column::new("x", ColumnValues::Numeric(vec![Some(1.5), None])),
+            Column::new(
+                "class",
+                ColumnValues::Nominal {
+                    levels: vec!["a".to_owned(), "b".to_owned()],
+                    codes: vec![Some(1), Some(0)],
+                },
+            ),
+            Column::new(
+                "text",
+                ColumnValues::String(vec![Some("hi".to_owned()), None]),
+            ),
+        ],
+    );
+
+    let rows: Vec<_> = frame.rows().collect();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].get(0), CellValue::Numeric(1.5));
+    assert_eq!(
+        rows[0].get_by_name("class"),
+        Some(CellValue::Nominal {
+            code: 1,
+            label: "b"
+        })
+    );
+    assert_eq!(rows[0].get(2), CellValue::String("hi"));
+    assert_eq!(rows[1].get(0), CellValue::Missing);
+    assert_eq!(rows[1].values().count(), 3);
+
+    let numeric = rows[0].to_f64();
+    assert_eq!(numeric[..2], [1.5, 1.0]);
+    assert!(numeric[2].is_nan());
+
+    let selected: Vec<_> = frame.rows_at(&[1]).map(|r| r.index()).collect();
+    assert_eq!(selected, vec![1]);
+}
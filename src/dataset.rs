@@ -1,22 +1,274 @@
-use arff::dynamic::DataSet as ArffDataSet;
+use crate::data_frame::{ColumnValues, DataFrame, Feature};
+use crate::error::{Error, Result};
+use crate::sparse::SparseDataSet;
+use crate::tasks::ClassLabels;
 
 /// An arbitrary data set
 #[derive(Debug)]
 pub(crate) struct DataSet {
-    pub(crate) arff: ArffDataSet,
+    pub(crate) id: String,
+    pub(crate) version: Option<u32>,
+    pub(crate) frame: DataFrame,
     pub(crate) target: Option<String>,
+    /// column that identifies rows; it is not a feature
+    pub(crate) row_id: Option<String>,
+    /// columns that must not be used as features
+    pub(crate) ignored: Vec<String>,
+    /// features that are not passed to flows, as chosen by the user
+    pub(crate) dropped: Vec<String>,
+    /// the features of a sparse data set; `frame` then holds only the target column and the
+    /// excluded columns
+    pub(crate) sparse: Option<SparseDataSet>,
 }
 
 impl DataSet {
-    /// return two `ArffDataSet`s; one containing the features and the other containing the target
-    /// variable.
-    pub(crate) fn clone_split(&self) -> Option<(ArffDataSet, ArffDataSet)> {
-        match self.target {
-            None => None,
-            Some(ref col) => {
-                let data = self.arff.clone();
-                Some(data.split_one(col))
+    /// return two data frames; one containing the features and the other containing the target
+    /// variable. The row ID, ignored and dropped columns are in neither.
+    pub(crate) fn clone_split(&self) -> Option<(DataFrame, DataFrame)> {
+        let target = self.target.as_ref()?;
+        let y = self.clone_target()?;
+        let excluded = self.excluded();
+        let x = match self.sparse {
+            Some(ref sparse) => self.select(&sparse.to_data_frame(), |name| {
+                !self.dropped.iter().any(|d| d == name)
+            }),
+            None => self.select(&self.frame, |name| {
+                name != target && !excluded.iter().chain(&self.dropped).any(|e| e == name)
+            }),
+        };
+        Some((x, y))
+    }
+
+    /// return a data frame containing only the target variable
+    pub(crate) fn clone_target(&self) -> Option<DataFrame> {
+        let target = self.target.as_ref()?;
+        Some(self.select(&self.frame, |name| name == target))
+    }
+
+    /// like `clone_split`, but fail with `Error::JsonFieldError` if the data set has no target
+    pub(crate) fn split_target(&self) -> Result<(DataFrame, DataFrame)> {
+        self.clone_split().ok_or_else(no_target)
+    }
+
+    /// like `clone_target`, but fail with `Error::JsonFieldError` if the data set has no target
+    pub(crate) fn target_frame(&self) -> Result<DataFrame> {
+        self.clone_target().ok_or_else(no_target)
+    }
+
+    /// copy the columns of a data frame whose names pass a filter
+    fn select(&self, frame: &DataFrame, keep: impl Fn(&str) -> bool) -> DataFrame {
+        let columns = frame
+            .columns()
+            .iter()
+            .filter(|c| keep(c.name()))
+            .cloned()
+            .collect();
+        DataFrame::new(frame.name(), columns)
+    }
+
+    /// pass only the given features to flows
+    pub(crate) fn select_features(&mut self, names: &[&str]) -> Result<()> {
+        let features = self.features();
+        self.check_features(&features, names)?;
+        self.dropped.extend(
+            features
+                .iter()
+                .map(Feature::name)
+                .filter(|f| !names.contains(f))
+                .map(str::to_owned),
+        );
+        Ok(())
+    }
+
+    /// do not pass the given features to flows
+    pub(crate) fn drop_features(&mut self, names: &[&str]) -> Result<()> {
+        let features = self.features();
+        self.check_features(&features, names)?;
+        self.dropped
+            .extend(names.iter().map(|&name| name.to_owned()));
+        Ok(())
+    }
+
+    fn check_features(&self, features: &[Feature], names: &[&str]) -> Result<()> {
+        match names
+            .iter()
+            .find(|&&name| !features.iter().any(|f| f.name() == name))
+        {
+            Some(name) => Err(Error::ColumnError {
+                column: (*name).to_owned(),
+                message: "not a feature of the task".to_owned(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// return the names of the columns that are neither features nor the target: the row ID and
+    /// the ignored columns
+    pub(crate) fn excluded(&self) -> Vec<String> {
+        self.row_id
+            .iter()
+            .chain(&self.ignored)
+            .filter(|&name| Some(name) != self.target.as_ref())
+            .cloned()
+            .collect()
+    }
+
+    /// return the row ID and ignored columns with typed values
+    pub(crate) fn excluded_columns(&self) -> DataFrame {
+        let excluded = self.excluded();
+        self.select(&self.frame, |name| excluded.iter().any(|e| e == name))
+    }
+
+    /// return the whole data set with typed columns; sparse features are expanded
+    pub(crate) fn data_frame(&self) -> DataFrame {
+        match self.sparse {
+            Some(ref sparse) => {
+                let mut columns = sparse.to_data_frame().columns().to_vec();
+                columns.extend(self.frame.columns().iter().cloned());
+                DataFrame::new(self.frame.name(), columns)
             }
+            None => self.frame.clone(),
         }
     }
+
+    /// return the names and types of the features that are passed to flows
+    pub(crate) fn features(&self) -> Vec<Feature> {
+        let features = match self.sparse {
+            Some(ref sparse) => sparse.features(),
+            None => self.frame.features(),
+        };
+        let excluded = self.excluded();
+        features
+            .into_iter()
+            .filter(|f| Some(f.name()) != self.target.as_deref())
+            .filter(|f| {
+                !excluded
+                    .iter()
+                    .chain(&self.dropped)
+                    .any(|name| name == f.name())
+            })
+            .collect()
+    }
+
+    /// return the class codes of the target column, if it is nominal
+    pub(crate) fn nominal_target(&self) -> Option<Vec<Option<usize>>> {
+        let col = self.frame.column(self.target.as_ref()?)?;
+        col.as_nominal().map(|(_, codes)| codes.to_vec())
+    }
+
+    /// return the target column as numbers; nominal values are represented by their class codes.
+    /// Fails if the data set has no target, or if the target is neither numeric nor nominal.
+    pub(crate) fn numeric_target(&self) -> Result<Vec<Option<f64>>> {
+        let target = self.target.as_ref().ok_or_else(no_target)?;
+        let not_numeric = |message: &str| Error::ColumnError {
+            column: target.clone(),
+            message: message.to_owned(),
+        };
+        let col = self
+            .frame
+            .column(target)
+            .ok_or_else(|| not_numeric("the target column is not in the data set"))?;
+        let values = col
+            .to_f64()
+            .ok_or_else(|| not_numeric("the target column is neither numeric nor nominal"))?;
+        Ok(values
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| if col.is_missing(i) { None } else { Some(x) })
+            .collect())
+    }
+
+    /// return the class labels declared for the target column, if it is nominal
+    pub(crate) fn class_labels(&self) -> Option<ClassLabels> {
+        match *self.frame.column(self.target.as_ref()?)?.values() {
+            ColumnValues::Nominal { ref levels, .. } => Some(ClassLabels::new(levels.clone())),
+            _ => None,
+        }
+    }
+}
+
+/// the error of tasks whose data set has no target
+fn no_target() -> Error {
+    Error::JsonFieldError {
+        path: "/task/input/source_data/target_feature".to_owned(),
+        message: "the task has no target feature".to_owned(),
+    }
+}
+
+#[cfg(test)]
+use arff::dynamic::DataSet as ArffDataSet;
+
+#[test]
+fn excluded_columns() {
+    let data = DataSet {
+        id: "1".to_owned(),
+        version: None,
+        frame: DataFrame::from_arff(
+            &ArffDataSet::from_str(
+                "@RELATION test\n\
+             @ATTRIBUTE id NUMERIC\n\
+             @ATTRIBUTE x NUMERIC\n\
+             @ATTRIBUTE note STRING\n\
+             @ATTRIBUTE y {a,b}\n\
+             @DATA\n\
+             7,1.5,'n',a\n",
+            )
+            .unwrap(),
+        ),
+        target: Some("y".to_owned()),
+        row_id: Some("id".to_owned()),
+        ignored: vec!["note".to_owned()],
+        dropped: vec![],
+        sparse: None,
+    };
+
+    let (x, y) = data.clone_split().unwrap();
+    assert_eq!(x.column_names().collect::<Vec<_>>(), vec!["x"]);
+    assert_eq!(y.column_names().collect::<Vec<_>>(), vec!["y"]);
+    assert_eq!(data.features().len(), 1);
+    assert_eq!(
+        data.excluded_columns().column_names().collect::<Vec<_>>(),
+        vec!["id", "note"]
+    );
+}
+
+#[test]
+fn feature_subsets() {
+    let data = || DataSet {
+        id: "1".to_owned(),
+        version: None,
+        frame: DataFrame::from_arff(
+            &ArffDataSet::from_str(
+                "@RELATION test\n\
+             @ATTRIBUTE age NUMERIC\n\
+             @ATTRIBUTE bmi NUMERIC\n\
+             @ATTRIBUTE smoker {no,yes}\n\
+             @ATTRIBUTE y NUMERIC\n\
+             @DATA\n\
+             40,22.5,no,1\n",
+            )
+            .unwrap(),
+        ),
+        target: Some("y".to_owned()),
+        row_id: None,
+        ignored: vec![],
+        dropped: vec![],
+        sparse: None,
+    };
+    let names = |data: &DataSet| {
+        let (x, _) = data.clone_split().unwrap();
+        x.column_names().map(str::to_owned).collect::<Vec<_>>()
+    };
+
+    let mut selected = data();
+    selected.select_features(&["bmi", "age"]).unwrap();
+    assert_eq!(names(&selected), vec!["age", "bmi"]);
+    assert_eq!(selected.features().len(), 2);
+
+    let mut dropped = data();
+    dropped.drop_features(&["bmi"]).unwrap();
+    assert_eq!(names(&dropped), vec!["age", "smoker"]);
+
+    assert!(data().drop_features(&["y"]).is_err());
+    assert!(data().select_features(&["height"]).is_err());
 }
@@ -1,14 +1,19 @@
 use std::io::Error as IoError;
+use std::path::PathBuf;
 use std::result::Result as StdResult;
 use std::string::FromUtf8Error;
 
 use app_dirs::AppDirsError;
 use arff::Error as ArffError;
+use bincode::Error as BincodeError;
+#[cfg(feature = "parquet")]
+use parquet::errors::ParquetError;
 use reqwest::Error as ReqwestError;
 use serde_json::Error as JsonError;
 
 pub type Result<T> = StdResult<T, Error>;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug)]
 pub enum Error {
     IoError(IoError),
@@ -17,6 +22,151 @@ pub enum Error {
     JsonError(JsonError),
     ArffError(ArffError),
     AppDirsError(AppDirsError),
+    BincodeError(BincodeError),
+    #[cfg(feature = "parquet")]
+    ParquetError(ParquetError),
+    HttpStatus {
+        url: String,
+        status: u16,
+    },
+    NotFound {
+        url: String,
+        body: String,
+    },
+    AuthRequired {
+        url: String,
+        status: u16,
+        body: String,
+    },
+    RateLimited {
+        url: String,
+        body: String,
+    },
+    ServerError {
+        url: String,
+        status: u16,
+        body: String,
+    },
+    Offline {
+        url: String,
+    },
+    MissingApiKey,
+    InvalidTag {
+        tag: String,
+    },
+    InvalidFlow {
+        name: String,
+        message: String,
+    },
+    InvalidId {
+        id: String,
+    },
+    DatasetUnavailable {
+        id: String,
+        status: String,
+    },
+    NotCached {
+        url: String,
+    },
+    Context {
+        operation: String,
+        url: String,
+        source: Box<Error>,
+    },
+    Api {
+        code: u32,
+        message: String,
+    },
+    JsonFieldError {
+        path: String,
+        message: String,
+    },
+    InvalidHeader {
+        name: String,
+        value: String,
+    },
+    ChecksumMismatch {
+        url: String,
+        expected: String,
+        actual: String,
+    },
+    SplitsFormatError {
+        line: usize,
+        repeat: Option<usize>,
+        fold: Option<usize>,
+        message: String,
+    },
+    UnsupportedMeasure(String),
+    SparseArffError {
+        line: usize,
+        message: String,
+    },
+    SingleClassFold {
+        repeat: usize,
+        fold: usize,
+    },
+    ColumnError {
+        column: String,
+        message: String,
+    },
+    InvalidPrediction {
+        row: Option<usize>,
+        message: String,
+    },
+    InvalidTrace {
+        repeat: usize,
+        fold: usize,
+        message: String,
+    },
+    InvalidPredictions {
+        repeat: usize,
+        fold: usize,
+        row: Option<usize>,
+        message: String,
+    },
+    PredictionFileError {
+        path: PathBuf,
+        line: Option<usize>,
+        message: String,
+    },
+}
+
+impl Error {
+    /// get the error that caused this one, without the operations it happened in
+    pub fn root_cause(&self) -> &Error {
+        match self {
+            Error::Context { source, .. } => source.root_cause(),
+            e => e,
+        }
+    }
+
+    /// get the HTTP status of the response this error was caused by, if any
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Error::NotFound { .. } => Some(404),
+            Error::RateLimited { .. } => Some(429),
+            Error::HttpStatus { status, .. }
+            | Error::AuthRequired { status, .. }
+            | Error::ServerError { status, .. } => Some(*status),
+            Error::Context { source, .. } => source.status(),
+            _ => None,
+        }
+    }
+}
+
+/// Attach the operation that failed, and the URL it accessed, to errors
+pub(crate) trait Context<T> {
+    fn context<F: FnOnce() -> String>(self, url: &str, operation: F) -> Result<T>;
+}
+
+impl<T> Context<T> for Result<T> {
+    fn context<F: FnOnce() -> String>(self, url: &str, operation: F) -> Result<T> {
+        self.map_err(|e| Error::Context {
+            operation: operation(),
+            url: url.to_owned(),
+            source: Box::new(e),
+        })
+    }
 }
 
 impl From<IoError> for Error {
@@ -49,6 +199,19 @@ impl From<ArffError> for Error {
     }
 }
 
+impl From<BincodeError> for Error {
+    fn from(e: BincodeError) -> Self {
+        Error::BincodeError(e)
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl From<ParquetError> for Error {
+    fn from(e: ParquetError) -> Self {
+        Error::ParquetError(e)
+    }
+}
+
 impl From<AppDirsError> for Error {
     fn from(e: AppDirsError) -> Self {
         match e {
@@ -57,3 +220,20 @@ impl From<AppDirsError> for Error {
         }
     }
 }
+
+#[test]
+fn error_context() {
+    let result: Result<()> = Err(Error::Offline {
+        url: "https://www.openml.org/api/v1/json/task/59".to_owned(),
+    });
+    let err = result
+        .context("https://www.openml.org/api/v1/json/task/59", || {
+            "loading task 59".to_owned()
+        })
+        .unwrap_err();
+    match &err {
+        Error::Context { operation, .. } => assert_eq!(operation, "loading task 59"),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(matches!(err.root_cause(), Error::Offline { .. }));
+}
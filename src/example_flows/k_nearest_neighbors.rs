@@ -0,0 +1,94 @@
+//! Implementation of a k-nearest-neighbors classifier
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use ndarray::{Array1, Array2, ArrayView1, Axis};
+
+/// A k-nearest-neighbors classifier with Euclidean distance
+///
+/// ```
+/// # use openml::example_flows::KNearestNeighbors;
+/// # let data: Vec<(&[f64], &u8)> = vec![(&[0.0], &1)];
+/// let knn = KNearestNeighbors::fit(3, data.into_iter());
+/// ```
+#[derive(Debug)]
+pub struct KNearestNeighbors<C> {
+    k: usize,
+    features: Array2<f64>,
+    labels: Vec<C>,
+}
+
+impl<C> KNearestNeighbors<C>
+where
+    C: Eq + Hash + Copy,
+{
+    /// train the classifier by storing the training data
+    pub fn fit<'a, I, J>(k: usize, data: I) -> Self
+    where
+        I: IntoIterator<Item = (J, &'a C)>,
+        J: IntoIterator<Item = &'a f64>,
+        C: 'a,
+    {
+        let mut values = Vec::new();
+        let mut labels = Vec::new();
+
+        for (x, &y) in data {
+            values.extend(x);
+            labels.push(y);
+        }
+
+        let n_cols = if labels.is_empty() {
+            0
+        } else {
+            values.len() / labels.len()
+        };
+        let features = Array2::from_shape_vec((labels.len(), n_cols), values)
+            .expect("all feature vectors must have the same length");
+
+        KNearestNeighbors {
+            k,
+            features,
+            labels,
+        }
+    }
+
+    /// predict the majority class of the `k` nearest training rows. Ties are broken in favor of
+    /// the class whose neighbors are closer.
+    pub fn predict(&self, x: &[f64]) -> C {
+        let x = ArrayView1::from(x);
+        let distances: Array1<f64> = (&self.features - &x).mapv(|d| d * d).sum_axis(Axis(1));
+
+        let mut neighbors: Vec<_> = distances.iter().zip(&self.labels).collect();
+        neighbors.sort_by(|a, b| a.0.partial_cmp(b.0).unwrap());
+
+        let mut votes = HashMap::new();
+        for (rank, (_, &label)) in neighbors.iter().take(self.k).enumerate() {
+            let vote = votes.entry(label).or_insert((0, 0));
+            vote.0 += 1;
+            vote.1 += rank;
+        }
+
+        votes
+            .into_iter()
+            .max_by(|(_, (n1, r1)), (_, (n2, r2))| n1.cmp(n2).then(r2.cmp(r1)))
+            .map(|(label, _)| label)
+            .expect("cannot predict without training data")
+    }
+}
+
+#[test]
+fn knn() {
+    let data = [
+        (vec![1.0, 2.0], 'A'),
+        (vec![2.0, 1.0], 'A'),
+        (vec![1.0, 5.0], 'B'),
+        (vec![2.0, 6.0], 'B'),
+        (vec![2.0, 5.0], 'B'),
+    ];
+
+    let knn = KNearestNeighbors::fit(3, data.iter().map(|(x, y)| (x, y)));
+
+    assert_eq!(knn.predict(&[1.5, 1.5]), 'A');
+    assert_eq!(knn.predict(&[1.5, 5.5]), 'B');
+}
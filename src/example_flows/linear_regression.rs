@@ -0,0 +1,111 @@
+//! Implementation of a least-squares linear regression
+
+use std::iter::FromIterator;
+
+use ndarray::{Array1, Array2, ArrayView1};
+
+/// A multivariate linear regression, fit by solving the normal equations
+///
+/// The model is trained by consuming an iterator over the training data:
+/// ```
+/// # use openml::example_flows::LinearRegression;
+/// # let data: Vec<(&[f64], &f64)> = vec![(&[0.0], &1.0), (&[1.0], &2.0)];
+/// let model: LinearRegression = data
+///     .into_iter()
+///     .collect();
+/// ```
+#[derive(Debug)]
+pub struct LinearRegression {
+    intercept: f64,
+    coefficients: Array1<f64>,
+}
+
+impl<'a, J> FromIterator<(J, &'a f64)> for LinearRegression
+where
+    J: IntoIterator<Item = &'a f64>,
+{
+    fn from_iter<I: IntoIterator<Item = (J, &'a f64)>>(iter: I) -> Self {
+        let mut values = Vec::new();
+        let mut targets = Vec::new();
+
+        for (x, &y) in iter {
+            values.push(1.0);
+            values.extend(x);
+            targets.push(y);
+        }
+
+        let n_rows = targets.len();
+        let n_cols = values.len() / n_rows.max(1);
+        let x = Array2::from_shape_vec((n_rows, n_cols), values)
+            .expect("all feature vectors must have the same length");
+        let y = Array1::from(targets);
+
+        // a tiny ridge keeps the normal equations solvable for constant or collinear features
+        let mut a = x.t().dot(&x);
+        for i in 0..n_cols {
+            a[[i, i]] += 1e-9;
+        }
+        let b = x.t().dot(&y);
+
+        let w = solve(a, b);
+
+        LinearRegression {
+            intercept: w[0],
+            coefficients: w.slice(ndarray::s![1..]).to_owned(),
+        }
+    }
+}
+
+impl LinearRegression {
+    /// predict target value for a single feature vector
+    pub fn predict(&self, x: &[f64]) -> f64 {
+        self.intercept + self.coefficients.dot(&ArrayView1::from(x))
+    }
+}
+
+/// solve the linear system `a * w = b` by Gaussian elimination with partial pivoting
+fn solve(mut a: Array2<f64>, mut b: Array1<f64>) -> Array1<f64> {
+    let n = b.len();
+
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&i, &j| a[[i, col]].abs().partial_cmp(&a[[j, col]].abs()).unwrap())
+            .unwrap();
+        for k in 0..n {
+            a.swap([col, k], [pivot, k]);
+        }
+        b.swap(col, pivot);
+
+        for row in col + 1..n {
+            let factor = a[[row, col]] / a[[col, col]];
+            for k in col..n {
+                a[[row, k]] -= factor * a[[col, k]];
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+
+    let mut w = Array1::zeros(n);
+    for row in (0..n).rev() {
+        let mut sum = b[row];
+        for k in row + 1..n {
+            sum -= a[[row, k]] * w[k];
+        }
+        w[row] = sum / a[[row, row]];
+    }
+    w
+}
+
+#[test]
+fn linear_regression() {
+    let data = [
+        (vec![1.0, 2.0], 1.0 + 2.0 * 1.0 - 3.0 * 2.0),
+        (vec![2.0, 1.0], 1.0 + 2.0 * 2.0 - 3.0 * 1.0),
+        (vec![1.0, 5.0], 1.0 + 2.0 * 1.0 - 3.0 * 5.0),
+        (vec![3.0, 6.0], 1.0 + 2.0 * 3.0 - 3.0 * 6.0),
+    ];
+
+    let model: LinearRegression = data.iter().map(|(x, y)| (x, y)).collect();
+
+    assert!((model.predict(&[4.0, 4.0]) - (1.0 + 8.0 - 12.0)).abs() < 1e-6);
+}
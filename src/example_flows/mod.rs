@@ -0,0 +1,55 @@
+//! Complete example flows, enabled by the `examples` feature.
+//!
+//! The flows in this module are meant as scaffolding to copy from: each one trains a model on the
+//! training set of every fold and predicts the testing set, so they can be passed to `run`
+//! directly:
+//!
+//! ```no_run
+//! # extern crate openml;
+//! use openml::example_flows::knn_flow;
+//! use openml::prelude::*;
+//! use openml::{PredictiveAccuracy, SupervisedClassification};
+//!
+//! let task = SupervisedClassification::from_openml(59).unwrap();
+//! let result: PredictiveAccuracy<u8> = task.run(knn_flow(5));
+//! println!("Classification Accuracy: {}", result.result());
+//! ```
+
+mod k_nearest_neighbors;
+mod linear_regression;
+
+use std::hash::Hash;
+
+pub use self::k_nearest_neighbors::KNearestNeighbors;
+pub use self::linear_regression::LinearRegression;
+
+/// Flow for classification tasks: fit a k-nearest-neighbors classifier in every fold.
+#[allow(clippy::type_complexity)]
+pub fn knn_flow<C>(
+    k: usize,
+) -> impl Fn(
+    &mut dyn Iterator<Item = (&[f64], &C)>,
+    &mut dyn Iterator<Item = &[f64]>,
+) -> Box<dyn Iterator<Item = C>>
+where
+    C: Eq + Hash + Copy + 'static,
+{
+    move |train, test| {
+        let model = KNearestNeighbors::fit(k, train.map(|(x, y)| (x.iter(), y)));
+        let y_out: Vec<_> = test.map(|x| model.predict(x)).collect();
+        Box::new(y_out.into_iter())
+    }
+}
+
+/// Flow for regression tasks: fit a least-squares linear regression in every fold.
+#[allow(clippy::type_complexity)]
+pub fn linear_regression_flow() -> impl Fn(
+    &mut dyn Iterator<Item = (&[f64], &f64)>,
+    &mut dyn Iterator<Item = &[f64]>,
+) -> Box<dyn Iterator<Item = f64>> {
+    |train, test| {
+        let model: LinearRegression = train.map(|(x, y)| (x.iter(), y)).collect();
+        let y_out: Vec<_> = test.map(|x| model.predict(x)).collect();
+        Box::new(y_out.into_iter())
+    }
+}
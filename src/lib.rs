@@ -39,11 +39,30 @@
 
 extern crate app_dirs;
 extern crate arff;
+#[cfg(feature = "arrow")]
+extern crate arrow_array;
+#[cfg(feature = "parquet")]
+extern crate arrow_schema;
+extern crate bincode;
+extern crate chrono;
+extern crate crc32fast;
+extern crate flate2;
 extern crate fs2;
 extern crate reqwest;
 #[macro_use]
 extern crate log;
+extern crate md5;
+#[cfg(feature = "mmap")]
+extern crate memmap2;
+#[cfg(feature = "examples")]
+extern crate ndarray;
 extern crate num_traits;
+#[cfg(feature = "parquet")]
+extern crate parquet;
+#[cfg(feature = "polars")]
+extern crate polars;
+extern crate rand;
+extern crate rand_chacha;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
@@ -53,20 +72,65 @@ extern crate simple_logger;
 #[cfg(test)]
 extern crate time;
 
+pub mod anonymize;
 pub mod baseline;
+mod data_frame;
 mod dataset;
 mod error;
-mod measure_accumulator;
+#[cfg(feature = "examples")]
+pub mod example_flows;
+pub mod measures;
 mod openml_api;
 pub mod prelude;
 mod procedures;
+mod runs;
+mod sparse;
+pub mod splits;
 mod tasks;
 
-pub use crate::measure_accumulator::{
-    MeasureAccumulator, PredictiveAccuracy, RootMeanSquaredError,
+pub use crate::data_frame::{
+    AttributeType, CellValue, Column, ColumnValues, DataFrame, Feature, Row,
 };
 
-pub use crate::tasks::{SupervisedClassification, SupervisedRegression, Task};
+pub use crate::error::{Error, Result};
+
+pub use crate::measures::{
+    AdjustedRandIndex, AreaUnderRocCurve, Kappa, LogLoss, MatthewsCorrelation, MeanAbsoluteError,
+    MeasureAccumulator, Precision, PredictiveAccuracy, RSquared, Recall, RelativeAbsoluteError,
+    RootMeanSquaredError, RootRelativeSquaredError, F1,
+};
+
+pub use crate::openml_api::{
+    cache_dir, cache_entries, cache_stats, clear_cache, download_all, prefetch_suite,
+    prefetch_tasks, remove_cached, remove_cached_data_set, reset_cache_stats, set_binary_cache,
+    set_cache_compression, set_cache_ttl, set_extra_headers, set_lenient_splits_parsing,
+    set_memory_cache_limits, set_random_seed, set_shared_cache, set_splits_streaming_threshold,
+    set_transfer_compression, set_upload_options, set_user_agent, verify_run, CacheEntry,
+    CacheStats, DataSetId, DataSetIdentifier, FlowId, FlowIdentifier, Id, OpenML, OpenMLBuilder,
+    ResourceKind, RetryPolicy, RunId, RunIdentifier, TaskId, TaskIdentifier,
+};
+
+#[cfg(feature = "parquet")]
+pub use crate::openml_api::set_prefer_parquet;
+
+pub use crate::procedures::{EstimationProcedure, ProcedureType};
+
+pub use crate::runs::{
+    FlowBuilder, FlowMetadata, FlowParameter, MeasureComparison, ParameterSettings, PredictionRow,
+    RunPrediction, RunPredictions, RunResult, RunVerification, TraceIteration, TraceRecorder,
+    UploadOptions, UploadedRun, Visibility,
+};
+
+pub use crate::sparse::{SparseDataSet, SparseMatrix, SparseRow};
+
+#[cfg(feature = "mmap")]
+pub use crate::tasks::MappedFeatures;
+
+pub use crate::tasks::{
+    CheckPrediction, ClassLabels, CostMatrix, FeatureView, Flow, FoldContext, FoldData,
+    FoldProgress, Folds, MissingValues, PredictionFileEvaluation, SingleClassPolicy,
+    SupervisedClassification, SupervisedRegression, Task, TaskKind, TrainingView,
+};
 
 #[cfg(test)]
 mod tests {
@@ -74,7 +138,7 @@ mod tests {
     use time::PreciseTime;
 
     use crate::baseline::NaiveBayesClassifier;
-    use crate::measure_accumulator::PredictiveAccuracy;
+    use crate::measures::PredictiveAccuracy;
 
     use super::*;
 
@@ -110,7 +174,7 @@ mod tests {
 
         let result: PredictiveAccuracy<_> = task.run(|train, test| {
             // train classifier
-            let nbc: NaiveBayesClassifier<u8> = train.map(|(x, y)| (x, y)).collect();
+            let nbc: NaiveBayesClassifier<u8> = train.map(|(x, y)| (x.iter(), y)).collect();
 
             // test classifier
             let y_out: Vec<_> = test.map(|x| nbc.predict(x)).collect();
@@ -123,7 +187,6 @@ mod tests {
 
     #[test]
     fn apidev2() {
-        use simple_logger;
         simple_logger::init_with_level(Level::Info).unwrap();
 
         let start = PreciseTime::now();
@@ -0,0 +1,706 @@
+//! Measures for classification tasks
+
+use std::any::Any;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use num_traits::AsPrimitive;
+
+use super::MeasureAccumulator;
+use crate::tasks::ClassLabels;
+
+/// Classification Accuracy: relative amount of correctly classified labels
+///
+/// Labels that are floating point numbers, e.g. class codes predicted by a regression model, are
+/// rounded to the nearest class code, so that `0.9999999` counts as class `1`. Other labels are
+/// compared with `==`. `with_comparison` sets another comparison, e.g. `f64::eq` to compare
+/// floating point labels exactly:
+///
+/// ```
+/// use openml::measures::PredictiveAccuracy;
+/// use openml::MeasureAccumulator;
+///
+/// let mut accuracy = PredictiveAccuracy::new();
+/// accuracy.update_one(&1.0, &0.9999999);
+/// assert_eq!(accuracy.result(), 1.0);
+///
+/// let mut exact = PredictiveAccuracy::with_comparison(f64::eq);
+/// exact.update_one(&1.0, &0.9999999);
+/// assert_eq!(exact.result(), 0.0);
+/// ```
+#[derive(Debug)]
+pub struct PredictiveAccuracy<T> {
+    n_correct: usize,
+    n_wrong: usize,
+    matches: fn(&T, &T) -> bool,
+}
+
+impl<T> PredictiveAccuracy<T> {
+    /// initialize new measure that decides with `matches(known, predicted)` whether a prediction
+    /// is correct
+    pub fn with_comparison(matches: fn(&T, &T) -> bool) -> Self {
+        PredictiveAccuracy {
+            n_correct: 0,
+            n_wrong: 0,
+            matches,
+        }
+    }
+}
+
+/// Label comparison that rounds both labels to the nearest integer class code
+pub fn nearest_class_code<T>(known: &T, pred: &T) -> bool
+where
+    T: AsPrimitive<f64>,
+{
+    known.as_().round() == pred.as_().round()
+}
+
+/// Label comparison of `PredictiveAccuracy::new`: like `nearest_class_code` for floating point
+/// labels, and `==` for all others
+fn same_class<T>(known: &T, pred: &T) -> bool
+where
+    T: PartialEq + 'static,
+{
+    match (as_float(known), as_float(pred)) {
+        (Some(known), Some(pred)) => known.round() == pred.round(),
+        _ => known == pred,
+    }
+}
+
+/// get a label as `f64` if it is a floating point number
+fn as_float<T: 'static>(label: &T) -> Option<f64> {
+    let label: &dyn Any = label;
+    label
+        .downcast_ref::<f64>()
+        .copied()
+        .or_else(|| label.downcast_ref::<f32>().map(|&x| f64::from(x)))
+}
+
+impl<T> MeasureAccumulator<T> for PredictiveAccuracy<T>
+where
+    T: PartialEq + 'static,
+{
+    /// initialize new measure that rounds floating point labels to the nearest class code and
+    /// compares other labels with `==`
+    fn new() -> Self {
+        Self::with_comparison(same_class)
+    }
+
+    fn update_one(&mut self, known: &T, pred: &T) {
+        if (self.matches)(known, pred) {
+            self.n_correct += 1;
+        } else {
+            self.n_wrong += 1;
+        }
+    }
+
+    fn result(&self) -> f64 {
+        self.n_correct as f64 / (self.n_correct + self.n_wrong) as f64
+    }
+}
+
+#[test]
+fn accuracy() {
+    fn score<T: PartialEq + Clone>(
+        mut accuracy: PredictiveAccuracy<T>,
+        known: &[T],
+        pred: &[T],
+    ) -> f64
+    where
+        PredictiveAccuracy<T>: MeasureAccumulator<T>,
+    {
+        accuracy.update(known.iter().cloned(), pred.iter().cloned());
+        accuracy.result()
+    }
+
+    let (known, pred) = ([1.0, 2.0, 0.0, 1.0], [0.9999999, 2.4, 0.51, 1.0]);
+    assert_eq!(score(PredictiveAccuracy::new(), &known, &pred), 0.75);
+    assert_eq!(
+        score(PredictiveAccuracy::with_comparison(f64::eq), &known, &pred),
+        0.25
+    );
+    assert_eq!(
+        score(PredictiveAccuracy::new(), &[1.0f32, 2.0], &[1.0000001, 1.0]),
+        0.5
+    );
+    assert_eq!(
+        score(PredictiveAccuracy::new(), &[0u8, 1, 2, 2], &[0, 1, 1, 2]),
+        0.75
+    );
+    let names = ["setosa".to_owned(), "virginica".to_owned()];
+    assert_eq!(score(PredictiveAccuracy::new(), &names, &names), 1.0);
+}
+
+/// Area under the ROC curve, computed one-vs-rest for every class and averaged with weights
+/// proportional to the class frequencies
+///
+/// Known values are class codes; predictions are per-class probabilities (or any scores that rank
+/// rows by their confidence), indexed by class code.
+#[derive(Debug)]
+pub struct AreaUnderRocCurve<T> {
+    known: Vec<usize>,
+    scores: Vec<Vec<f64>>,
+    _t: PhantomData<T>,
+}
+
+impl<T> MeasureAccumulator<T, Vec<f64>> for AreaUnderRocCurve<T>
+where
+    T: AsPrimitive<usize>,
+{
+    fn new() -> Self {
+        AreaUnderRocCurve {
+            known: Vec::new(),
+            scores: Vec::new(),
+            _t: PhantomData,
+        }
+    }
+
+    fn update_one(&mut self, known: &T, pred: &Vec<f64>) {
+        self.known.push(known.as_());
+        self.scores.push(pred.clone());
+    }
+
+    fn result(&self) -> f64 {
+        let n_classes = self.scores.iter().map(Vec::len).max().unwrap_or(0);
+
+        let mut weighted_sum = 0.0;
+        let mut total_weight = 0;
+
+        for class in 0..n_classes {
+            let scores: Vec<_> = self
+                .scores
+                .iter()
+                .zip(&self.known)
+                .map(|(s, &k)| (s.get(class).cloned().unwrap_or(0.0), k == class))
+                .collect();
+
+            let n_positive = scores.iter().filter(|(_, pos)| *pos).count();
+
+            if let Some(auc) = binary_auc(scores) {
+                weighted_sum += auc * n_positive as f64;
+                total_weight += n_positive;
+            }
+        }
+
+        weighted_sum / total_weight as f64
+    }
+}
+
+/// Area under the ROC curve of a binary problem, given `(score, is_positive)` pairs.
+///
+/// This is the Mann-Whitney U statistic, normalized to [0, 1]; tied scores receive half credit.
+/// Returns `None` if there are no positive or no negative rows.
+fn binary_auc(mut scores: Vec<(f64, bool)>) -> Option<f64> {
+    scores.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+    let mut rank_sum = 0.0;
+    let mut i = 0;
+    while i < scores.len() {
+        let mut j = i;
+        while j < scores.len() && scores[j].0 == scores[i].0 {
+            j += 1;
+        }
+
+        // tied rows share the average of the (1-based) ranks i + 1 ..= j
+        let rank = (i + 1 + j) as f64 / 2.0;
+        let n_positive = scores[i..j].iter().filter(|(_, pos)| *pos).count();
+        rank_sum += rank * n_positive as f64;
+
+        i = j;
+    }
+
+    let n_positive = scores.iter().filter(|(_, pos)| *pos).count() as f64;
+    let n_negative = scores.len() as f64 - n_positive;
+
+    if n_positive == 0.0 || n_negative == 0.0 {
+        return None;
+    }
+
+    Some((rank_sum - n_positive * (n_positive + 1.0) / 2.0) / (n_positive * n_negative))
+}
+
+#[test]
+fn auc() {
+    let known = [0u8, 0, 1, 1];
+    let probs = vec![
+        vec![0.9, 0.1],
+        vec![0.6, 0.4],
+        vec![0.65, 0.35],
+        vec![0.2, 0.8],
+    ];
+
+    let mut auc = AreaUnderRocCurve::new();
+    auc.update(known.iter().cloned(), probs.into_iter());
+    assert_eq!(auc.result(), 0.75);
+
+    let known = [0u8, 1, 2, 2];
+    let probs = vec![
+        vec![0.8, 0.1, 0.1],
+        vec![0.1, 0.8, 0.1],
+        vec![0.1, 0.1, 0.8],
+        vec![0.1, 0.1, 0.8],
+    ];
+
+    let mut auc = AreaUnderRocCurve::new();
+    auc.update(known.iter().cloned(), probs.into_iter());
+    assert_eq!(auc.result(), 1.0);
+}
+
+/// Log loss (cross-entropy): mean negative natural logarithm of the probability predicted for the
+/// known class
+///
+/// Known values are class codes; predictions are per-class probabilities, indexed by class code.
+/// Probabilities are clipped to `[epsilon, 1 - epsilon]` so that a single confident mistake does
+/// not make the loss infinite.
+#[derive(Debug)]
+pub struct LogLoss<T> {
+    sum: f64,
+    n: usize,
+    epsilon: f64,
+    _t: PhantomData<T>,
+}
+
+impl<T> LogLoss<T> {
+    /// initialize new measure that clips probabilities to `[epsilon, 1 - epsilon]`
+    pub fn with_epsilon(epsilon: f64) -> Self {
+        LogLoss {
+            sum: 0.0,
+            n: 0,
+            epsilon,
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<T> MeasureAccumulator<T, Vec<f64>> for LogLoss<T>
+where
+    T: AsPrimitive<usize>,
+{
+    /// initialize new measure with `epsilon = 1e-15`
+    fn new() -> Self {
+        Self::with_epsilon(1e-15)
+    }
+
+    fn update_one(&mut self, known: &T, pred: &Vec<f64>) {
+        let p = pred.get(known.as_()).cloned().unwrap_or(0.0);
+        let p = p.max(self.epsilon).min(1.0 - self.epsilon);
+        self.sum -= p.ln();
+        self.n += 1;
+    }
+
+    fn result(&self) -> f64 {
+        self.sum / self.n as f64
+    }
+}
+
+#[test]
+fn log_loss() {
+    let known = [0u8, 1, 1];
+    let probs = vec![vec![0.5, 0.5], vec![0.2, 0.8], vec![1.0, 0.0]];
+
+    let mut loss = LogLoss::with_epsilon(1e-3);
+    loss.update(known.iter().cloned(), probs.into_iter());
+
+    let expected = -(0.5f64.ln() + 0.8f64.ln() + 1e-3f64.ln()) / 3.0;
+    assert!((loss.result() - expected).abs() < 1e-12);
+}
+
+/// How per-class scores are combined into a single value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Averaging {
+    /// compute the score from the counts summed over all classes
+    Micro,
+    /// unweighted mean of the per-class scores
+    Macro,
+    /// mean of the per-class scores, weighted by the number of rows of each class (as OpenML does)
+    Weighted,
+}
+
+/// Precision, recall, F1 score and support (number of rows with the known label) of one class
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassMetrics<L> {
+    pub label: L,
+    pub precision: f64,
+    pub recall: f64,
+    pub f1: f64,
+    pub support: usize,
+}
+
+/// Counts of (known, predicted) label pairs
+#[derive(Debug)]
+pub(crate) struct ConfusionMatrix<T>
+where
+    T: Eq + Hash,
+{
+    counts: HashMap<(T, T), usize>,
+}
+
+impl<T> ConfusionMatrix<T>
+where
+    T: Eq + Hash + Clone,
+{
+    pub(crate) fn new() -> Self {
+        ConfusionMatrix {
+            counts: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn add(&mut self, known: &T, pred: &T) {
+        *self
+            .counts
+            .entry((known.clone(), pred.clone()))
+            .or_insert(0) += 1;
+    }
+
+    /// per-class counts of (true positives, predicted, actual) for every label that occurs
+    pub(crate) fn class_counts(&self) -> HashMap<&T, (usize, usize, usize)> {
+        let mut classes = HashMap::new();
+        for ((known, pred), &n) in &self.counts {
+            if known == pred {
+                classes.entry(known).or_insert((0, 0, 0)).0 += n;
+            }
+            classes.entry(pred).or_insert((0, 0, 0)).1 += n;
+            classes.entry(known).or_insert((0, 0, 0)).2 += n;
+        }
+        classes
+    }
+
+    /// per-class metrics, in order of the labels
+    pub(crate) fn per_class(&self) -> Vec<ClassMetrics<T>>
+    where
+        T: Ord,
+    {
+        let mut metrics: Vec<_> = self
+            .class_counts()
+            .into_iter()
+            .map(|(label, (tp, predicted, actual))| ClassMetrics {
+                label: label.clone(),
+                precision: precision(tp, predicted, actual),
+                recall: recall(tp, predicted, actual),
+                f1: f1(tp, predicted, actual),
+                support: actual,
+            })
+            .collect();
+        metrics.sort_by(|a, b| a.label.cmp(&b.label));
+        metrics
+    }
+
+    /// combine a score computed from (true positives, predicted, actual) over all classes
+    fn score<S>(&self, averaging: Averaging, score: S) -> f64
+    where
+        S: Fn(usize, usize, usize) -> f64,
+    {
+        let classes = self.class_counts();
+
+        match averaging {
+            Averaging::Micro => {
+                let (tp, predicted, actual) = classes
+                    .values()
+                    .fold((0, 0, 0), |acc, c| (acc.0 + c.0, acc.1 + c.1, acc.2 + c.2));
+                score(tp, predicted, actual)
+            }
+            Averaging::Macro => {
+                let sum: f64 = classes.values().map(|&(t, p, a)| score(t, p, a)).sum();
+                sum / classes.len() as f64
+            }
+            Averaging::Weighted => {
+                let total: usize = classes.values().map(|c| c.2).sum();
+                let sum: f64 = classes
+                    .values()
+                    .map(|&(t, p, a)| score(t, p, a) * a as f64)
+                    .sum();
+                sum / total as f64
+            }
+        }
+    }
+}
+
+fn ratio(a: usize, b: usize) -> f64 {
+    if b == 0 {
+        0.0
+    } else {
+        a as f64 / b as f64
+    }
+}
+
+fn precision(tp: usize, predicted: usize, _actual: usize) -> f64 {
+    ratio(tp, predicted)
+}
+
+fn recall(tp: usize, _predicted: usize, actual: usize) -> f64 {
+    ratio(tp, actual)
+}
+
+fn f1(tp: usize, predicted: usize, actual: usize) -> f64 {
+    // 2PR / (P + R) simplifies to 2TP / (predicted + actual)
+    ratio(2 * tp, predicted + actual)
+}
+
+macro_rules! confusion_measure {
+    ($(#[$doc:meta])* $name:ident, $score:ident) => {
+        $(#[$doc])*
+        #[derive(Debug)]
+        pub struct $name<T>
+        where
+            T: Eq + Hash,
+        {
+            confusion: ConfusionMatrix<T>,
+            averaging: Averaging,
+        }
+
+        impl<T> $name<T>
+        where
+            T: Eq + Hash + Clone,
+        {
+            /// initialize new measure with the given averaging of per-class scores
+            pub fn with_averaging(averaging: Averaging) -> Self {
+                $name {
+                    confusion: ConfusionMatrix::new(),
+                    averaging,
+                }
+            }
+
+            /// get precision, recall, F1 score and support of every class that occurred, in
+            /// order of the labels
+            pub fn per_class(&self) -> Vec<ClassMetrics<T>>
+            where
+                T: Ord,
+            {
+                self.confusion.per_class()
+            }
+
+            /// get the per-class metrics like `per_class`, with class codes decoded into the
+            /// task's class labels. Codes without a label are reported as their number.
+            pub fn per_class_labels(&self, labels: &ClassLabels) -> Vec<ClassMetrics<String>>
+            where
+                T: Ord + AsPrimitive<usize>,
+            {
+                self.per_class()
+                    .into_iter()
+                    .map(|m| {
+                        let code = m.label.as_();
+                        ClassMetrics {
+                            label: labels
+                                .label(code)
+                                .map_or_else(|| code.to_string(), str::to_owned),
+                            precision: m.precision,
+                            recall: m.recall,
+                            f1: m.f1,
+                            support: m.support,
+                        }
+                    })
+                    .collect()
+            }
+        }
+
+        impl<T> MeasureAccumulator<T> for $name<T>
+        where
+            T: Eq + Hash + Clone,
+        {
+            /// initialize new measure with weighted averaging, as used by OpenML
+            fn new() -> Self {
+                Self::with_averaging(Averaging::Weighted)
+            }
+
+            fn update_one(&mut self, known: &T, pred: &T) {
+                self.confusion.add(known, pred);
+            }
+
+            fn result(&self) -> f64 {
+                self.confusion.score(self.averaging, $score)
+            }
+        }
+    };
+}
+
+confusion_measure!(
+    /// Precision: relative amount of correct predictions among the rows predicted as a class
+    Precision,
+    precision
+);
+
+confusion_measure!(
+    /// Recall: relative amount of the rows of a class that are predicted correctly
+    Recall,
+    recall
+);
+
+confusion_measure!(
+    /// F1 score: harmonic mean of precision and recall
+    F1,
+    f1
+);
+
+/// Cohen's kappa: agreement between known and predicted labels, corrected for the agreement
+/// expected by chance
+#[derive(Debug)]
+pub struct Kappa<T>
+where
+    T: Eq + Hash,
+{
+    confusion: ConfusionMatrix<T>,
+}
+
+impl<T> MeasureAccumulator<T> for Kappa<T>
+where
+    T: Eq + Hash + Clone,
+{
+    fn new() -> Self {
+        Kappa {
+            confusion: ConfusionMatrix::new(),
+        }
+    }
+
+    fn update_one(&mut self, known: &T, pred: &T) {
+        self.confusion.add(known, pred);
+    }
+
+    fn result(&self) -> f64 {
+        let classes = self.confusion.class_counts();
+        let n: usize = classes.values().map(|c| c.2).sum();
+        let n = n as f64;
+
+        let observed = classes.values().map(|c| c.0).sum::<usize>() as f64 / n;
+        let expected = classes
+            .values()
+            .map(|&(_, predicted, actual)| predicted as f64 * actual as f64)
+            .sum::<f64>()
+            / (n * n);
+
+        if expected == 1.0 {
+            // known and predicted labels are one and the same class
+            return 1.0;
+        }
+
+        (observed - expected) / (1.0 - expected)
+    }
+}
+
+/// Matthews correlation coefficient: correlation between known and predicted labels, in the
+/// multi-class generalization by Gorodkin (for two classes it equals the usual binary MCC)
+///
+/// This is OpenML's `matthews_correlation_coefficient` (`Measure::MatthewsCorrelation`).
+#[derive(Debug)]
+pub struct MatthewsCorrelation<T>
+where
+    T: Eq + Hash,
+{
+    confusion: ConfusionMatrix<T>,
+}
+
+impl<T> MeasureAccumulator<T> for MatthewsCorrelation<T>
+where
+    T: Eq + Hash + Clone,
+{
+    fn new() -> Self {
+        MatthewsCorrelation {
+            confusion: ConfusionMatrix::new(),
+        }
+    }
+
+    fn update_one(&mut self, known: &T, pred: &T) {
+        self.confusion.add(known, pred);
+    }
+
+    fn result(&self) -> f64 {
+        let classes = self.confusion.class_counts();
+
+        let mut correct = 0.0;
+        let mut n = 0.0;
+        let mut sum_pt = 0.0;
+        let mut sum_pp = 0.0;
+        let mut sum_tt = 0.0;
+        for &(tp, predicted, actual) in classes.values() {
+            let (p, t) = (predicted as f64, actual as f64);
+            correct += tp as f64;
+            n += t;
+            sum_pt += p * t;
+            sum_pp += p * p;
+            sum_tt += t * t;
+        }
+
+        let denominator = ((n * n - sum_pp) * (n * n - sum_tt)).sqrt();
+        if denominator == 0.0 {
+            // all known or all predicted labels are the same class
+            return 0.0;
+        }
+
+        (correct * n - sum_pt) / denominator
+    }
+}
+
+#[test]
+fn precision_recall_f1() {
+    fn measure<M: MeasureAccumulator<i32>>(mut m: M) -> f64 {
+        let known = [0, 0, 0, 1, 1, 2];
+        let preds = [0, 0, 1, 1, 1, 1];
+        for (k, p) in known.iter().zip(&preds) {
+            m.update_one(k, p);
+        }
+        m.result()
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-12, "{} != {}", a, b);
+    }
+
+    // per class precision: 1, 0.5, 0; recall: 2/3, 1, 0; F1: 0.8, 2/3, 0; support: 3, 2, 1
+    assert_close(measure(Precision::with_averaging(Averaging::Macro)), 0.5);
+    assert_close(measure(Precision::new()), 4.0 / 6.0);
+    assert_close(measure(Recall::with_averaging(Averaging::Macro)), 5.0 / 9.0);
+    assert_close(measure(Recall::new()), 4.0 / 6.0);
+    assert_close(measure(F1::with_averaging(Averaging::Micro)), 4.0 / 6.0);
+    assert_close(
+        measure(F1::with_averaging(Averaging::Macro)),
+        (0.8 + 2.0 / 3.0) / 3.0,
+    );
+
+    let mut f1 = F1::new();
+    f1.update(
+        [0u8, 0, 0, 1, 1, 2].iter().cloned(),
+        [0, 0, 1, 1, 1, 1].iter().cloned(),
+    );
+    let labels = ClassLabels::new(vec!["a".to_owned(), "b".to_owned(), "c".to_owned()]);
+    let report = f1.per_class_labels(&labels);
+    assert_eq!(
+        report.iter().map(|m| m.label.as_str()).collect::<Vec<_>>(),
+        vec!["a", "b", "c"]
+    );
+    assert_close(report[1].precision, 0.5);
+    assert_close(report[0].recall, 2.0 / 3.0);
+    assert_eq!(report[2].support, 1);
+
+    // observed agreement 4/6; chance agreement (2 * 3 + 4 * 2 + 0 * 1) / 36
+    assert_close(
+        measure(Kappa::new()),
+        (4.0 / 6.0 - 14.0 / 36.0) / (1.0 - 14.0 / 36.0),
+    );
+}
+
+#[test]
+fn matthews_correlation() {
+    fn mcc(known: &[i32], preds: &[i32]) -> f64 {
+        let mut m = MatthewsCorrelation::new();
+        m.update(known.iter().cloned(), preds.iter().cloned());
+        m.result()
+    }
+
+    // binary: TP 3, TN 3, FP 1, FN 1
+    let known = [1, 1, 1, 0, 0, 0, 0, 1];
+    let preds = [1, 1, 0, 0, 0, 1, 0, 1];
+    assert!((mcc(&known, &preds) - 0.5).abs() < 1e-12);
+    assert_eq!(mcc(&known, &known), 1.0);
+    let inverted: Vec<_> = known.iter().map(|k| 1 - k).collect();
+    assert_eq!(mcc(&known, &inverted), -1.0);
+
+    // multi-class: 4 of 6 correct; predicted counts 2, 4, 0; known counts 3, 2, 1
+    let known = [0, 0, 0, 1, 1, 2];
+    let preds = [0, 0, 1, 1, 1, 1];
+    let expected = (4.0 * 6.0 - 14.0) / ((36.0f64 - 20.0) * (36.0 - 14.0)).sqrt();
+    assert!((mcc(&known, &preds) - expected).abs() < 1e-12);
+
+    // constant predictions carry no information
+    assert_eq!(mcc(&known, &[0; 6]), 0.0);
+}
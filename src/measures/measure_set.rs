@@ -0,0 +1,80 @@
+//! Evaluation of several measures in a single run
+
+use super::MeasureAccumulator;
+
+/// Several measures that are updated together, so that a flow only needs to be run once
+///
+/// The measures are given as a tuple and must all compare the same types of known values and
+/// predictions. `result()` returns the result of the first measure; the others are reached
+/// through the tuple:
+///
+/// ```
+/// use openml::measures::MeasureSet;
+/// use openml::{MeasureAccumulator, PredictiveAccuracy, F1};
+///
+/// let mut set: MeasureSet<(PredictiveAccuracy<u8>, F1<u8>)> = MeasureSet::new();
+/// set.update([0, 1, 1].iter().cloned(), [0, 1, 0].iter().cloned());
+///
+/// let MeasureSet((accuracy, f1)) = set;
+/// println!("accuracy: {}, F1: {}", accuracy.result(), f1.result());
+/// ```
+#[derive(Debug)]
+pub struct MeasureSet<M>(pub M);
+
+macro_rules! measure_set {
+    ($($m:ident . $i:tt),+) => {
+        impl<T, P, $($m),+> MeasureAccumulator<T, P> for MeasureSet<($($m,)+)>
+        where
+            $($m: MeasureAccumulator<T, P>),+
+        {
+            fn new() -> Self {
+                MeasureSet(($($m::new(),)+))
+            }
+
+            fn start_fold(&mut self, repeat: usize, fold: usize) {
+                $((self.0).$i.start_fold(repeat, fold);)+
+            }
+
+            fn training_targets(&mut self, known: &mut dyn Iterator<Item = &T>) {
+                let known: Vec<&T> = known.collect();
+                $((self.0).$i.training_targets(&mut known.iter().cloned());)+
+            }
+
+            fn update_one(&mut self, known: &T, pred: &P) {
+                $((self.0).$i.update_one(known, pred);)+
+            }
+
+            /// get the result of the first measure
+            fn result(&self) -> f64 {
+                (self.0).0.result()
+            }
+        }
+    };
+}
+
+measure_set!(A.0);
+measure_set!(A.0, B.1);
+measure_set!(A.0, B.1, C.2);
+measure_set!(A.0, B.1, C.2, D.3);
+measure_set!(A.0, B.1, C.2, D.3, E.4);
+measure_set!(A.0, B.1, C.2, D.3, E.4, F.5);
+measure_set!(A.0, B.1, C.2, D.3, E.4, F.5, G.6);
+measure_set!(A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7);
+
+#[test]
+fn measure_set() {
+    use super::{Kappa, PredictiveAccuracy, Recall};
+
+    let known = [0, 0, 1, 1];
+    let preds = [0, 1, 1, 1];
+
+    let mut set: MeasureSet<(PredictiveAccuracy<i32>, Recall<i32>, Kappa<i32>)> = MeasureSet::new();
+    set.update(known.iter().cloned(), preds.iter().cloned());
+
+    assert_eq!(set.result(), 0.75);
+
+    let MeasureSet((accuracy, recall, kappa)) = set;
+    assert_eq!(accuracy.result(), 0.75);
+    assert_eq!(recall.result(), 0.75);
+    assert_eq!(kappa.result(), 0.5);
+}
@@ -0,0 +1,314 @@
+//! Measure accumulators are summaries of model performance, such as classification accuracy or
+//! regression error.
+
+use std::str::FromStr;
+
+use crate::error::{Error, Result};
+
+mod classification;
+mod clustering;
+mod measure_set;
+mod per_fold;
+mod regression;
+
+pub use self::classification::{
+    nearest_class_code, AreaUnderRocCurve, Averaging, ClassMetrics, Kappa, LogLoss,
+    MatthewsCorrelation, Precision, PredictiveAccuracy, Recall, F1,
+};
+pub use self::clustering::AdjustedRandIndex;
+pub use self::measure_set::MeasureSet;
+pub use self::per_fold::PerFold;
+pub use self::regression::{
+    MeanAbsoluteError, RSquared, RelativeAbsoluteError, RootMeanSquaredError,
+    RootRelativeSquaredError,
+};
+
+/// Trait implemented by performance measures
+///
+/// `T` is the type of the known target values and `P` the type of the predictions. Most measures
+/// compare predictions of the same type as the targets, but e.g. `AreaUnderRocCurve` compares
+/// class labels against per-class probabilities.
+pub trait MeasureAccumulator<T, P = T> {
+    /// initialize new measure
+    fn new() -> Self
+    where
+        Self: Sized;
+
+    /// start a new fold; called before the predictions of each fold are added. Measures that keep
+    /// results per fold, such as `PerFold`, override this; the default does nothing.
+    fn start_fold(&mut self, _repeat: usize, _fold: usize) {}
+
+    /// pass the known values of the training set of the fold that was just started. Measures
+    /// relative to a baseline fitted on the training set, such as `RelativeAbsoluteError`,
+    /// override this; the default ignores them.
+    fn training_targets(&mut self, _known: &mut dyn Iterator<Item = &T>) {}
+
+    /// update with one prediction
+    fn update_one(&mut self, known: &T, pred: &P);
+
+    /// get resulting performance
+    fn result(&self) -> f64;
+
+    /// update with multiple predictions
+    fn update<I: Iterator<Item = T>, J: Iterator<Item = P>>(&mut self, known: I, predicted: J)
+    where
+        Self: Sized,
+    {
+        for (k, p) in known.zip(predicted) {
+            self.update_one(&k, &p)
+        }
+    }
+}
+
+/// The performance measures implemented by this crate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Measure {
+    /// Classification accuracy, see `PredictiveAccuracy`
+    Accuracy,
+    /// Root mean squared error, see `RootMeanSquaredError`
+    Rmse,
+    /// Adjusted Rand index, see `AdjustedRandIndex`
+    AdjustedRandIndex,
+    /// Area under the ROC curve, see `AreaUnderRocCurve`
+    AreaUnderRocCurve,
+    /// Weighted precision, see `Precision`
+    Precision,
+    /// Weighted recall, see `Recall`
+    Recall,
+    /// Weighted F1 score, see `F1`
+    F1,
+    /// Mean absolute error, see `MeanAbsoluteError`
+    Mae,
+    /// Relative absolute error, see `RelativeAbsoluteError`
+    RelativeAbsoluteError,
+    /// Root relative squared error, see `RootRelativeSquaredError`
+    RootRelativeSquaredError,
+    /// Cohen's kappa, see `Kappa`
+    Kappa,
+    /// Log loss in bits (OpenML's mean class complexity), see `LogLoss`
+    MeanClassComplexity,
+    /// Matthews correlation coefficient, see `MatthewsCorrelation`
+    MatthewsCorrelation,
+    /// Coefficient of determination, see `RSquared`
+    RSquared,
+}
+
+/// Names of the measures on the OpenML server; the single source for both directions of mapping
+const OPENML_NAMES: &[(Measure, &str)] = &[
+    (Measure::Accuracy, "predictive_accuracy"),
+    (Measure::Rmse, "root_mean_squared_error"),
+    (Measure::AdjustedRandIndex, "adjusted_rand_index"),
+    (Measure::AreaUnderRocCurve, "area_under_roc_curve"),
+    (Measure::Precision, "precision"),
+    (Measure::Recall, "recall"),
+    (Measure::F1, "f_measure"),
+    (Measure::Mae, "mean_absolute_error"),
+    (Measure::RelativeAbsoluteError, "relative_absolute_error"),
+    (
+        Measure::RootRelativeSquaredError,
+        "root_relative_squared_error",
+    ),
+    (Measure::Kappa, "kappa"),
+    (Measure::MeanClassComplexity, "mean_class_complexity"),
+    (
+        Measure::MatthewsCorrelation,
+        "matthews_correlation_coefficient",
+    ),
+    (Measure::RSquared, "r_squared"),
+];
+
+impl Measure {
+    /// look up a measure by its OpenML name (e.g. `"predictive_accuracy"`)
+    pub fn from_openml_name(name: &str) -> Option<Measure> {
+        OPENML_NAMES
+            .iter()
+            .find(|(_, n)| *n == name)
+            .map(|&(m, _)| m)
+    }
+
+    /// get the name OpenML uses for this measure
+    pub fn to_openml_name(self) -> &'static str {
+        OPENML_NAMES
+            .iter()
+            .find(|(m, _)| *m == self)
+            .map(|&(_, n)| n)
+            .expect("every measure has an OpenML name")
+    }
+}
+
+/// Parse an OpenML measure name; measures this crate cannot compute (e.g. run times or
+/// information scores) yield `Error::UnsupportedMeasure`.
+///
+/// ```
+/// use openml::measures::Measure;
+///
+/// assert_eq!("f_measure".parse::<Measure>().unwrap(), Measure::F1);
+/// assert!("usercpu_time_millis".parse::<Measure>().is_err());
+/// ```
+impl FromStr for Measure {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        Measure::from_openml_name(name).ok_or_else(|| Error::UnsupportedMeasure(name.to_owned()))
+    }
+}
+
+/// Compute a measure from a vector of known values and a vector of predictions.
+///
+/// This makes the measure definitions usable on data that does not come from OpenML. Class labels
+/// and cluster assignments are expected as numeric codes; all measures that compare them round
+/// them to the nearest integer first. For `Measure::AreaUnderRocCurve` the
+/// problem must be binary (classes `0.0` and `1.0`) and the predictions are the probabilities of
+/// class `1.0`; the same holds for `Measure::MeanClassComplexity`.
+///
+/// ```
+/// use openml::measures::{evaluate, Measure};
+///
+/// let rmse = evaluate(Measure::Rmse, &[1.0, 2.0, 3.0], &[1.0, 2.0, 5.0]);
+/// assert_eq!(rmse, (4.0f64 / 3.0).sqrt());
+/// ```
+///
+/// Measures of empty vectors are undefined and yield NaN.
+///
+/// # Panics
+/// if `truth` and `predictions` differ in length
+pub fn evaluate(measure: Measure, truth: &[f64], predictions: &[f64]) -> f64 {
+    assert_eq!(
+        truth.len(),
+        predictions.len(),
+        "truth and predictions differ in length"
+    );
+    if truth.is_empty() {
+        return f64::NAN;
+    }
+
+    match measure {
+        Measure::Accuracy => accumulate::<PredictiveAccuracy<_>, _, _>(truth, predictions),
+        Measure::Rmse => accumulate::<RootMeanSquaredError<_>, _, _>(truth, predictions),
+        Measure::Mae => accumulate::<MeanAbsoluteError<_>, _, _>(truth, predictions),
+        Measure::RelativeAbsoluteError => {
+            accumulate::<RelativeAbsoluteError<_>, _, _>(truth, predictions)
+        }
+        Measure::RootRelativeSquaredError => {
+            accumulate::<RootRelativeSquaredError<_>, _, _>(truth, predictions)
+        }
+        Measure::RSquared => accumulate::<RSquared<_>, _, _>(truth, predictions),
+        Measure::AdjustedRandIndex => {
+            accumulate::<AdjustedRandIndex<_>, _, _>(&labels(truth), &labels(predictions))
+        }
+        Measure::Precision => {
+            accumulate::<Precision<_>, _, _>(&labels(truth), &labels(predictions))
+        }
+        Measure::Recall => accumulate::<Recall<_>, _, _>(&labels(truth), &labels(predictions)),
+        Measure::F1 => accumulate::<F1<_>, _, _>(&labels(truth), &labels(predictions)),
+        Measure::Kappa => accumulate::<Kappa<_>, _, _>(&labels(truth), &labels(predictions)),
+        Measure::MatthewsCorrelation => {
+            accumulate::<MatthewsCorrelation<_>, _, _>(&labels(truth), &labels(predictions))
+        }
+        Measure::AreaUnderRocCurve => {
+            let probabilities: Vec<_> = predictions.iter().map(|&p| vec![1.0 - p, p]).collect();
+            accumulate::<AreaUnderRocCurve<_>, _, _>(truth, &probabilities)
+        }
+        Measure::MeanClassComplexity => {
+            let probabilities: Vec<_> = predictions.iter().map(|&p| vec![1.0 - p, p]).collect();
+            accumulate::<LogLoss<_>, _, _>(truth, &probabilities) / std::f64::consts::LN_2
+        }
+    }
+}
+
+/// round numeric labels to the nearest class code, in a hashable representation
+fn labels(x: &[f64]) -> Vec<u64> {
+    // adding 0.0 maps -0.0 to 0.0, so both have the same bit pattern
+    x.iter().map(|x| (x.round() + 0.0).to_bits()).collect()
+}
+
+fn accumulate<M, T, P>(truth: &[T], predictions: &[P]) -> f64
+where
+    M: MeasureAccumulator<T, P>,
+{
+    let mut measure = M::new();
+    for (known, pred) in truth.iter().zip(predictions) {
+        measure.update_one(known, pred);
+    }
+    measure.result()
+}
+
+#[test]
+fn openml_names() {
+    for &(measure, name) in OPENML_NAMES {
+        assert_eq!(Measure::from_openml_name(name), Some(measure));
+        assert_eq!(measure.to_openml_name(), name);
+    }
+    assert_eq!(Measure::from_openml_name("no_such_measure"), None);
+}
+
+#[test]
+fn evaluate_vectors() {
+    let truth = [0.0, 0.0, 0.0, 1.0, 1.0, 1.0];
+    let preds = [0.0, 0.0, 1.0, 1.0, 2.0, 2.0];
+
+    assert_eq!(evaluate(Measure::Accuracy, &truth, &preds), 0.5);
+    assert_eq!(evaluate(Measure::Accuracy, &[1.0], &[0.9999999]), 1.0);
+    assert_eq!(evaluate(Measure::Rmse, &truth, &preds), 0.5f64.sqrt());
+    assert_eq!(evaluate(Measure::RSquared, &truth, &truth), 1.0);
+    assert_eq!(
+        evaluate(Measure::AdjustedRandIndex, &truth, &preds),
+        0.24242424242424246
+    );
+
+    assert_eq!(evaluate(Measure::F1, &truth, &truth), 1.0);
+    // labels are rounded to class codes by every measure
+    let noisy = [0.0, -0.0, 0.0000001, 1.0, 0.9999999, 1.4];
+    for &measure in &[
+        Measure::Accuracy,
+        Measure::Precision,
+        Measure::Recall,
+        Measure::F1,
+        Measure::Kappa,
+        Measure::MatthewsCorrelation,
+        Measure::AdjustedRandIndex,
+    ] {
+        assert_eq!(evaluate(measure, &truth, &noisy), 1.0, "{:?}", measure);
+    }
+    assert_eq!(evaluate(Measure::MatthewsCorrelation, &truth, &truth), 1.0);
+    assert_eq!(
+        "matthews_correlation_coefficient"
+            .parse::<Measure>()
+            .unwrap(),
+        Measure::MatthewsCorrelation
+    );
+
+    let scores = [0.1, 0.4, 0.35, 0.8];
+    assert_eq!(
+        evaluate(Measure::AreaUnderRocCurve, &[0.0, 0.0, 1.0, 1.0], &scores),
+        0.75
+    );
+    assert_eq!(
+        evaluate(Measure::MeanClassComplexity, &[0.0, 1.0], &[0.5, 0.5]),
+        1.0
+    );
+}
+
+#[test]
+fn evaluate_empty() {
+    for &(measure, _) in OPENML_NAMES {
+        assert!(evaluate(measure, &[], &[]).is_nan(), "{:?}", measure);
+    }
+    assert!(evaluate(Measure::AdjustedRandIndex, &[1.0], &[0.0]).is_nan());
+}
+
+#[test]
+fn trait_object() {
+    let mut measures: Vec<Box<dyn MeasureAccumulator<f64>>> = vec![
+        Box::new(RootMeanSquaredError::new()),
+        Box::new(RSquared::new()),
+    ];
+
+    for measure in &mut measures {
+        measure.update_one(&1.0, &1.0);
+        measure.update_one(&3.0, &3.0);
+    }
+
+    assert_eq!(measures[0].result(), 0.0);
+    assert_eq!(measures[1].result(), 1.0);
+}
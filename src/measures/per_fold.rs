@@ -0,0 +1,107 @@
+//! Recording of results per fold
+
+use super::MeasureAccumulator;
+
+/// A measure that is additionally recorded separately for every fold
+///
+/// `result()` returns the measure over the predictions of all folds. The individual fold results,
+/// e.g. to estimate the variance of a measure or for statistical tests, are reached with
+/// `folds()`:
+///
+/// ```no_run
+/// # extern crate openml;
+/// use openml::measures::PerFold;
+/// use openml::prelude::*;
+/// use openml::{PredictiveAccuracy, SupervisedClassification};
+/// # use openml::baseline::NaiveBayesClassifier;
+///
+/// let task = SupervisedClassification::from_openml(59).unwrap();
+/// let result: PerFold<PredictiveAccuracy<u8>> = task.run(|train, test| {
+///     // ...
+/// #     let nbc: NaiveBayesClassifier<u8> = train.map(|(x, y)| (x.iter(), y)).collect();
+/// #     let y_out: Vec<_> = test.map(|x| nbc.predict(x)).collect();
+/// #     Box::new(y_out.into_iter())
+/// });
+///
+/// println!("overall accuracy: {}", result.result());
+/// for (repeat, fold, accuracy) in result.folds() {
+///     println!("repeat {}, fold {}: {}", repeat, fold, accuracy.result());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct PerFold<M> {
+    total: M,
+    folds: Vec<(usize, usize, M)>,
+}
+
+impl<M> PerFold<M> {
+    /// get the measure over the predictions of all folds
+    pub fn total(&self) -> &M {
+        &self.total
+    }
+
+    /// get the measure of every fold, together with the repeat and fold index
+    pub fn folds(&self) -> &[(usize, usize, M)] {
+        &self.folds
+    }
+}
+
+impl<T, P, M> MeasureAccumulator<T, P> for PerFold<M>
+where
+    M: MeasureAccumulator<T, P>,
+{
+    fn new() -> Self {
+        PerFold {
+            total: M::new(),
+            folds: vec![],
+        }
+    }
+
+    fn start_fold(&mut self, repeat: usize, fold: usize) {
+        self.total.start_fold(repeat, fold);
+
+        let mut measure = M::new();
+        measure.start_fold(repeat, fold);
+        self.folds.push((repeat, fold, measure));
+    }
+
+    fn training_targets(&mut self, known: &mut dyn Iterator<Item = &T>) {
+        let known: Vec<&T> = known.collect();
+        self.total.training_targets(&mut known.iter().cloned());
+        if let Some((_, _, measure)) = self.folds.last_mut() {
+            measure.training_targets(&mut known.iter().cloned());
+        }
+    }
+
+    fn update_one(&mut self, known: &T, pred: &P) {
+        self.total.update_one(known, pred);
+        if let Some((_, _, measure)) = self.folds.last_mut() {
+            measure.update_one(known, pred);
+        }
+    }
+
+    fn result(&self) -> f64 {
+        self.total.result()
+    }
+}
+
+#[test]
+fn per_fold() {
+    use super::PredictiveAccuracy;
+
+    let mut measure: PerFold<PredictiveAccuracy<i32>> = PerFold::new();
+
+    measure.start_fold(0, 0);
+    measure.update([0, 1].iter().cloned(), [0, 1].iter().cloned());
+    measure.start_fold(0, 1);
+    measure.update([0, 1].iter().cloned(), [1, 1].iter().cloned());
+
+    assert_eq!(measure.result(), 0.75);
+
+    let folds: Vec<_> = measure
+        .folds()
+        .iter()
+        .map(|(r, f, m)| (*r, *f, m.result()))
+        .collect();
+    assert_eq!(folds, vec![(0, 0, 1.0), (0, 1, 0.5)]);
+}
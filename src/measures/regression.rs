@@ -0,0 +1,299 @@
+//! Measures for regression tasks
+
+use std::marker::PhantomData;
+
+use num_traits::AsPrimitive;
+
+use super::MeasureAccumulator;
+
+/// Root Mean Squared Error
+#[derive(Debug)]
+pub struct RootMeanSquaredError<T> {
+    sum_of_squares: f64,
+    n: usize,
+    _t: PhantomData<T>,
+}
+
+impl<T> MeasureAccumulator<T> for RootMeanSquaredError<T>
+where
+    T: AsPrimitive<f64>,
+{
+    fn new() -> Self {
+        RootMeanSquaredError {
+            sum_of_squares: 0.0,
+            n: 0,
+            _t: PhantomData,
+        }
+    }
+
+    fn update_one(&mut self, known: &T, pred: &T) {
+        let diff = known.as_() - pred.as_();
+        self.sum_of_squares += diff * diff;
+        self.n += 1;
+    }
+
+    fn result(&self) -> f64 {
+        (self.sum_of_squares / self.n as f64).sqrt()
+    }
+}
+
+/// Mean Absolute Error
+#[derive(Debug)]
+pub struct MeanAbsoluteError<T> {
+    sum: f64,
+    n: usize,
+    _t: PhantomData<T>,
+}
+
+impl<T> MeasureAccumulator<T> for MeanAbsoluteError<T>
+where
+    T: AsPrimitive<f64>,
+{
+    fn new() -> Self {
+        MeanAbsoluteError {
+            sum: 0.0,
+            n: 0,
+            _t: PhantomData,
+        }
+    }
+
+    fn update_one(&mut self, known: &T, pred: &T) {
+        self.sum += (known.as_() - pred.as_()).abs();
+        self.n += 1;
+    }
+
+    fn result(&self) -> f64 {
+        self.sum / self.n as f64
+    }
+}
+
+/// Coefficient of determination (R²): `1 - SS_res / SS_tot`, the fraction of the variance of the
+/// known values that is explained by the predictions
+///
+/// `SS_tot` is summed over the folds, each around the mean of its own known values, so that
+/// differences between the folds do not count as explained variance.
+#[derive(Debug)]
+pub struct RSquared<T> {
+    residual_sum_of_squares: f64,
+    total_sum_of_squares: f64,
+    fold_n: usize,
+    fold_mean: f64,
+    _t: PhantomData<T>,
+}
+
+impl<T> MeasureAccumulator<T> for RSquared<T>
+where
+    T: AsPrimitive<f64>,
+{
+    fn new() -> Self {
+        RSquared {
+            residual_sum_of_squares: 0.0,
+            total_sum_of_squares: 0.0,
+            fold_n: 0,
+            fold_mean: 0.0,
+            _t: PhantomData,
+        }
+    }
+
+    fn start_fold(&mut self, _repeat: usize, _fold: usize) {
+        self.fold_n = 0;
+        self.fold_mean = 0.0;
+    }
+
+    fn update_one(&mut self, known: &T, pred: &T) {
+        let known = known.as_();
+        let diff = known - pred.as_();
+        self.residual_sum_of_squares += diff * diff;
+
+        // Welford's update of the running mean of the fold and the sum of squared deviations
+        self.fold_n += 1;
+        let delta = known - self.fold_mean;
+        self.fold_mean += delta / self.fold_n as f64;
+        self.total_sum_of_squares += delta * (known - self.fold_mean);
+    }
+
+    fn result(&self) -> f64 {
+        1.0 - self.residual_sum_of_squares / self.total_sum_of_squares
+    }
+}
+
+/// Relative Absolute Error: total absolute error, relative to the total absolute error of always
+/// predicting the mean of the training set
+///
+/// Like OpenML (and Weka), the reference of every fold is the mean of its training set, which the
+/// `run` methods of tasks pass through `training_targets`. Predictions that are evaluated without
+/// a training set, e.g. by `evaluate`, are compared against the mean of their known values instead.
+#[derive(Debug)]
+pub struct RelativeAbsoluteError<T> {
+    absolute_error: f64,
+    reference_error: f64,
+    training_mean: Option<f64>,
+    untrained: Vec<f64>,
+    _t: PhantomData<T>,
+}
+
+impl<T> MeasureAccumulator<T> for RelativeAbsoluteError<T>
+where
+    T: AsPrimitive<f64>,
+{
+    fn new() -> Self {
+        RelativeAbsoluteError {
+            absolute_error: 0.0,
+            reference_error: 0.0,
+            training_mean: None,
+            untrained: vec![],
+            _t: PhantomData,
+        }
+    }
+
+    fn start_fold(&mut self, _repeat: usize, _fold: usize) {
+        self.training_mean = None;
+    }
+
+    fn training_targets(&mut self, known: &mut dyn Iterator<Item = &T>) {
+        self.training_mean = mean(known);
+    }
+
+    fn update_one(&mut self, known: &T, pred: &T) {
+        let known = known.as_();
+        self.absolute_error += (known - pred.as_()).abs();
+        match self.training_mean {
+            Some(mean) => self.reference_error += (known - mean).abs(),
+            None => self.untrained.push(known),
+        }
+    }
+
+    fn result(&self) -> f64 {
+        let mean = mean(&mut self.untrained.iter()).unwrap_or(0.0);
+        let reference =
+            self.reference_error + self.untrained.iter().map(|k| (k - mean).abs()).sum::<f64>();
+        self.absolute_error / reference
+    }
+}
+
+/// Root Relative Squared Error: root of the total squared error, relative to the total squared
+/// error of always predicting the mean of the training set
+///
+/// As for `RelativeAbsoluteError`, the reference of every fold is the mean of its training set,
+/// and the mean of the known values if there is no training set.
+#[derive(Debug)]
+pub struct RootRelativeSquaredError<T> {
+    squared_error: f64,
+    reference_error: f64,
+    training_mean: Option<f64>,
+    untrained: Vec<f64>,
+    _t: PhantomData<T>,
+}
+
+impl<T> MeasureAccumulator<T> for RootRelativeSquaredError<T>
+where
+    T: AsPrimitive<f64>,
+{
+    fn new() -> Self {
+        RootRelativeSquaredError {
+            squared_error: 0.0,
+            reference_error: 0.0,
+            training_mean: None,
+            untrained: vec![],
+            _t: PhantomData,
+        }
+    }
+
+    fn start_fold(&mut self, _repeat: usize, _fold: usize) {
+        self.training_mean = None;
+    }
+
+    fn training_targets(&mut self, known: &mut dyn Iterator<Item = &T>) {
+        self.training_mean = mean(known);
+    }
+
+    fn update_one(&mut self, known: &T, pred: &T) {
+        let known = known.as_();
+        let diff = known - pred.as_();
+        self.squared_error += diff * diff;
+        match self.training_mean {
+            Some(mean) => self.reference_error += (known - mean) * (known - mean),
+            None => self.untrained.push(known),
+        }
+    }
+
+    fn result(&self) -> f64 {
+        let mean = mean(&mut self.untrained.iter()).unwrap_or(0.0);
+        let reference = self.reference_error
+            + self
+                .untrained
+                .iter()
+                .map(|k| (k - mean) * (k - mean))
+                .sum::<f64>();
+        (self.squared_error / reference).sqrt()
+    }
+}
+
+/// mean of some values, or `None` if there are none
+fn mean<T: AsPrimitive<f64>>(values: &mut dyn Iterator<Item = &T>) -> Option<f64> {
+    let (sum, n) = values.fold((0.0, 0), |(sum, n), v| (sum + v.as_(), n + 1));
+    if n == 0 {
+        None
+    } else {
+        Some(sum / n as f64)
+    }
+}
+
+#[test]
+fn r_squared() {
+    let known = [1.0, 2.0, 3.0, 4.0];
+    let preds = [1.5, 2.0, 2.5, 4.0];
+
+    let mut r2 = RSquared::new();
+    r2.update(known.iter().cloned(), preds.iter().cloned());
+
+    // SS_res = 0.5, SS_tot = 5
+    assert!((r2.result() - 0.9).abs() < 1e-12);
+
+    // per fold: means 1.5 and 3.5, SS_tot = 0.5 + 0.5
+    let mut r2 = RSquared::new();
+    r2.start_fold(0, 0);
+    r2.update(known[..2].iter().cloned(), preds[..2].iter().cloned());
+    r2.start_fold(0, 1);
+    r2.update(known[2..].iter().cloned(), preds[2..].iter().cloned());
+    assert!((r2.result() - 0.5).abs() < 1e-12);
+}
+
+#[test]
+fn relative_errors() {
+    let known = [1.0, 2.0, 3.0, 4.0];
+    let preds = [1.5, 2.0, 2.5, 4.0];
+
+    let mut rae = RelativeAbsoluteError::new();
+    rae.update(known.iter().cloned(), preds.iter().cloned());
+    // absolute error 1, reference 1.5 + 0.5 + 0.5 + 1.5
+    assert!((rae.result() - 0.25).abs() < 1e-12);
+
+    let mut rrse = RootRelativeSquaredError::new();
+    rrse.update(known.iter().cloned(), preds.iter().cloned());
+    assert!((rrse.result() - 0.1f64.sqrt()).abs() < 1e-12);
+}
+
+#[test]
+fn relative_errors_per_fold() {
+    let mut rae = RelativeAbsoluteError::new();
+    let mut rrse = RootRelativeSquaredError::new();
+    let folds = [
+        // training targets, known values and predictions of two folds
+        (vec![1.0, 3.0], vec![2.0, 4.0], vec![2.5, 3.0]),
+        (vec![2.0, 4.0, 6.0], vec![1.0, 5.0], vec![1.0, 4.0]),
+    ];
+    for (fold, (training, known, preds)) in folds.iter().enumerate() {
+        rae.start_fold(0, fold);
+        rae.training_targets(&mut training.iter());
+        rae.update(known.iter().cloned(), preds.iter().cloned());
+        rrse.start_fold(0, fold);
+        rrse.training_targets(&mut training.iter());
+        rrse.update(known.iter().cloned(), preds.iter().cloned());
+    }
+
+    // training means 2 and 4: absolute error 0.5 + 1 + 0 + 1, reference 0 + 2 + 3 + 1
+    assert!((rae.result() - 2.5 / 6.0).abs() < 1e-12);
+    // squared error 0.25 + 1 + 0 + 1, reference 0 + 4 + 9 + 1
+    assert!((rrse.result() - (2.25f64 / 14.0).sqrt()).abs() < 1e-12);
+}
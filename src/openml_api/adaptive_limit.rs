@@ -0,0 +1,107 @@
+//! Adaptive limit on the number of concurrent downloads
+
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+/// Responses slower than this count as a sign of an overloaded server
+const SLOW_RESPONSE: Duration = Duration::from_secs(10);
+
+/// How a download went, as far as the load of the server is concerned
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Outcome {
+    /// the server answered successfully, after the given time
+    Success(Duration),
+    /// the server asked to slow down (429) or failed (5xx)
+    Overloaded,
+    /// the download says nothing about server load, e.g. it failed for another reason or the
+    /// file was cached already
+    Unrelated,
+}
+
+/// Concurrency limit with additive increase and multiplicative decrease (AIMD)
+///
+/// Every fast, successful download raises the limit by `1 / limit`, so the limit grows by about
+/// one per round of downloads. Every overloaded response or slow download halves it.
+#[derive(Debug)]
+pub(crate) struct AdaptiveLimit {
+    state: Mutex<State>,
+    released: Condvar,
+}
+
+#[derive(Debug)]
+struct State {
+    limit: f64,
+    min: usize,
+    max: usize,
+    in_flight: usize,
+}
+
+impl AdaptiveLimit {
+    pub(crate) fn new(initial: usize, min: usize, max: usize) -> Self {
+        assert!(1 <= min && min <= initial && initial <= max);
+        AdaptiveLimit {
+            state: Mutex::new(State {
+                limit: initial as f64,
+                min,
+                max,
+                in_flight: 0,
+            }),
+            released: Condvar::new(),
+        }
+    }
+
+    /// block until another download is allowed, then register it
+    pub(crate) fn acquire(&self) {
+        let mut state = self.state.lock().unwrap();
+        while state.in_flight >= state.limit as usize {
+            state = self.released.wait(state).unwrap();
+        }
+        state.in_flight += 1;
+    }
+
+    /// unregister a download and adapt the limit to its outcome
+    pub(crate) fn release(&self, outcome: Outcome) {
+        let mut state = self.state.lock().unwrap();
+        state.in_flight -= 1;
+
+        match outcome {
+            Outcome::Success(time) if time < SLOW_RESPONSE => {
+                state.limit = (state.limit + 1.0 / state.limit).min(state.max as f64);
+            }
+            Outcome::Success(_) | Outcome::Overloaded => {
+                state.limit = (state.limit / 2.0).max(state.min as f64);
+            }
+            Outcome::Unrelated => {}
+        }
+
+        debug!("Download concurrency limit: {:.2}", state.limit);
+        self.released.notify_all();
+    }
+}
+
+#[test]
+fn aimd() {
+    let limit = AdaptiveLimit::new(2, 1, 4);
+    let current = || limit.state.lock().unwrap().limit as usize;
+    let fast = Outcome::Success(Duration::from_millis(100));
+
+    for _ in 0..10 {
+        limit.acquire();
+        limit.release(fast);
+    }
+    assert_eq!(current(), 4);
+
+    limit.acquire();
+    limit.release(Outcome::Overloaded);
+    assert_eq!(current(), 2);
+
+    limit.acquire();
+    limit.release(Outcome::Success(SLOW_RESPONSE));
+    limit.acquire();
+    limit.release(Outcome::Overloaded);
+    assert_eq!(current(), 1);
+
+    limit.acquire();
+    limit.release(Outcome::Unrelated);
+    assert_eq!(current(), 1);
+}
@@ -1,51 +1,424 @@
-use serde_json;
+//! Typed models of the JSON responses of the OpenML API
+//!
+//! The models only hold the fields this crate uses. Responses that do not fit them fail to
+//! deserialize with an error that names the field, e.g. ``missing field `task_name` ``.
 
-/// Generic JSON response as returned by the OpenML API
-#[derive(Debug, Serialize, Deserialize)]
-pub struct GenericResponse(serde_json::Value);
+use std::fmt;
+use std::result::Result as StdResult;
+use std::str::FromStr;
 
-impl GenericResponse {
-    #[inline(always)]
-    pub fn look_up<'a>(&'a self, p: &str) -> Option<&'a serde_json::Value> {
-        self.0.pointer(p)
-    }
+use serde::de::{self, Deserialize, DeserializeOwned, Deserializer, Visitor};
+
+use crate::error::{Error, Result};
+
+/// parse a response body into a model. OpenML error responses fail with `Error::Api`.
+pub(crate) fn parse_response<T: DeserializeOwned>(body: &str) -> Result<T> {
+    serde_json::from_str(body).map_err(|e| api_error(body).unwrap_or_else(|| e.into()))
+}
+
+/// get the error in a response body, if it is an OpenML error response, e.g.
+/// `{"error": {"code": "482", "message": "Unknown task"}}`
+pub(crate) fn api_error(body: &str) -> Option<Error> {
+    let response: ErrorResponse = serde_json::from_str(body).ok()?;
+    let error = response.error;
+    Some(Error::Api {
+        code: error.code.and_then(|code| code.0.parse().ok()).unwrap_or(0),
+        message: error.message,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorResponse {
+    error: ApiError,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiError {
+    #[serde(default)]
+    code: Option<StringOrNumber>,
+    #[serde(default)]
+    message: String,
+}
+
+/// Response to `task/{id}`
+#[derive(Debug, Deserialize)]
+pub(crate) struct TaskResponse {
+    pub(crate) task: TaskDescription,
 }
 
-/// A row in a split file
+/// Description of a task
 #[derive(Debug, Deserialize)]
-pub(crate) struct CrossValItem {
+pub(crate) struct TaskDescription {
+    pub(crate) task_id: StringOrNumber,
+    pub(crate) task_name: String,
+    pub(crate) task_type_id: StringOrNumber,
+    #[serde(deserialize_with = "one_or_many")]
+    pub(crate) input: Vec<TaskInput>,
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub(crate) tag: Vec<String>,
+}
+
+/// An input of a task, identified by its `name`. Inputs this crate does not use are `Other`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "name", rename_all = "snake_case")]
+pub(crate) enum TaskInput {
+    SourceData {
+        data_set: DataSetInput,
+    },
+    EstimationProcedure {
+        estimation_procedure: EstimationProcedureDescription,
+    },
+    EvaluationMeasures {
+        #[serde(default)]
+        evaluation_measures: Option<EvaluationMeasures>,
+    },
+    CostMatrix {
+        #[serde(default)]
+        cost_matrix: Option<Vec<Vec<StringOrNumber>>>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// The data set of a task
+#[derive(Debug, Deserialize)]
+pub(crate) struct DataSetInput {
+    pub(crate) data_set_id: StringOrNumber,
+    #[serde(default)]
+    pub(crate) target_feature: Option<String>,
+}
+
+/// The estimation procedure of a task
+#[derive(Debug, Deserialize)]
+pub(crate) struct EstimationProcedureDescription {
+    #[serde(default)]
+    pub(crate) id: Option<StringOrNumber>,
     #[serde(rename = "type")]
-    pub purpose: TrainTest,
+    pub(crate) kind: String,
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub(crate) parameter: Vec<Parameter>,
+    #[serde(default)]
+    pub(crate) data_splits_url: Option<String>,
+}
 
-    pub rowid: usize,
+/// A parameter of an estimation procedure; parameters without a value are not set
+#[derive(Debug, Deserialize)]
+pub(crate) struct Parameter {
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) value: Option<StringOrNumber>,
+}
 
-    pub repeat: usize,
+/// The names of the measures a task is evaluated with
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct EvaluationMeasures {
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub(crate) evaluation_measure: Vec<String>,
+}
 
-    pub fold: usize,
+/// Response to `data/{id}`
+#[derive(Debug, Deserialize)]
+pub(crate) struct DataSetResponse {
+    pub(crate) data_set_description: DataSetDescription,
 }
 
+/// Description of a data set
 #[derive(Debug, Deserialize)]
-pub(crate) enum TrainTest {
-    #[serde(rename = "TRAIN")]
-    Train,
+pub(crate) struct DataSetDescription {
+    // only Parquet files are named after the data set
+    #[cfg_attr(not(feature = "parquet"), allow(dead_code))]
+    #[serde(default)]
+    pub(crate) name: Option<String>,
+    #[serde(default)]
+    pub(crate) version: Option<StringOrNumber>,
+    #[serde(default)]
+    pub(crate) status: Option<String>,
+    pub(crate) url: String,
+    #[serde(default)]
+    pub(crate) parquet_url: Option<String>,
+    #[serde(default)]
+    pub(crate) md5_checksum: Option<String>,
+    #[serde(default)]
+    pub(crate) default_target_attribute: Option<String>,
+    #[serde(default)]
+    pub(crate) row_id_attribute: Option<String>,
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub(crate) ignore_attribute: Vec<String>,
+}
+
+/// Response to `study/{id}`
+#[derive(Debug, Deserialize)]
+pub(crate) struct StudyResponse {
+    pub(crate) study: Study,
+}
+
+/// A study or benchmark suite
+#[derive(Debug, Deserialize)]
+pub(crate) struct Study {
+    #[serde(default)]
+    pub(crate) tasks: Option<StudyTasks>,
+}
 
-    #[serde(rename = "TEST")]
-    Test,
+/// The tasks of a study
+#[derive(Debug, Deserialize)]
+pub(crate) struct StudyTasks {
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub(crate) task_id: Vec<StringOrNumber>,
+}
+
+/// Response to `flow/exists/{name}/{external_version}`
+#[derive(Debug, Deserialize)]
+pub(crate) struct FlowExistsResponse {
+    pub(crate) flow_exists: FlowExists,
+}
+
+/// Whether a flow exists, and its id if it does
+#[derive(Debug, Deserialize)]
+pub(crate) struct FlowExists {
+    pub(crate) exists: String,
+    pub(crate) id: StringOrNumber,
 }
 
-/// Cost matrix, used by some classification tasks - currently UNIMPLEMENTED
-#[derive(Debug)]
-pub(crate) enum CostMatrix {
-    None,
+/// Response to uploading a flow
+#[derive(Debug, Deserialize)]
+pub(crate) struct UploadFlowResponse {
+    pub(crate) upload_flow: UploadedFlowId,
+}
+
+/// Response to uploading a run
+#[derive(Debug, Deserialize)]
+pub(crate) struct UploadRunResponse {
+    pub(crate) upload_run: UploadedRunId,
+}
+
+/// The id of an uploaded flow
+#[derive(Debug, Deserialize)]
+pub(crate) struct UploadedFlowId {
+    pub(crate) id: StringOrNumber,
+}
+
+/// The id of an uploaded run
+#[derive(Debug, Deserialize)]
+pub(crate) struct UploadedRunId {
+    pub(crate) run_id: StringOrNumber,
+}
+
+/// Response to `run/{id}`
+#[derive(Debug, Deserialize)]
+pub(crate) struct RunResponse {
+    pub(crate) run: RunDescription,
 }
 
-impl<'a> From<&'a serde_json::Value> for CostMatrix {
-    fn from(item: &serde_json::Value) -> Self {
-        let v = &item["cost_matrix"];
-        match v.as_array() {
-            None => panic!("invalid cots matrix"),
-            Some(c) if c.is_empty() => CostMatrix::None,
-            Some(_) => unimplemented!("cost matrix"),
+/// Description of a run
+#[derive(Debug, Deserialize)]
+pub(crate) struct RunDescription {
+    pub(crate) task_id: StringOrNumber,
+    #[serde(default)]
+    pub(crate) output_data: Option<OutputData>,
+}
+
+/// The files and evaluations of a run
+#[derive(Debug, Deserialize)]
+pub(crate) struct OutputData {
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub(crate) file: Vec<OutputFile>,
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub(crate) evaluation: Vec<RunEvaluation>,
+}
+
+/// A file of a run, e.g. its `predictions`
+#[derive(Debug, Deserialize)]
+pub(crate) struct OutputFile {
+    pub(crate) name: String,
+    pub(crate) url: String,
+}
+
+/// A measure computed by the server, over the whole run or, with `repeat` and `fold`, over one
+/// fold. Measures such as per-class scores have no single value.
+#[derive(Debug, Deserialize)]
+pub(crate) struct RunEvaluation {
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) value: Option<StringOrNumber>,
+    #[serde(default)]
+    pub(crate) repeat: Option<StringOrNumber>,
+    #[serde(default)]
+    pub(crate) fold: Option<StringOrNumber>,
+}
+
+/// A value that OpenML encodes as a string, such as an id or a number, but that may also come as
+/// a number
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct StringOrNumber(pub(crate) String);
+
+impl StringOrNumber {
+    /// parse the value; `field` names it in errors
+    pub(crate) fn parse<T: FromStr>(&self, field: &str) -> Result<T> {
+        self.0.parse().map_err(|_| Error::JsonFieldError {
+            path: field.to_owned(),
+            message: format!("cannot parse {:?}", self.0),
+        })
+    }
+}
+
+impl fmt::Display for StringOrNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for StringOrNumber {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> StdResult<Self, D::Error> {
+        deserializer.deserialize_any(StringOrNumberVisitor)
+    }
+}
+
+struct StringOrNumberVisitor;
+
+impl<'de> Visitor<'de> for StringOrNumberVisitor {
+    type Value = StringOrNumber;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a string or a number")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> StdResult<Self::Value, E> {
+        Ok(StringOrNumber(v.to_owned()))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> StdResult<Self::Value, E> {
+        Ok(StringOrNumber(v.to_string()))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> StdResult<Self::Value, E> {
+        Ok(StringOrNumber(v.to_string()))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> StdResult<Self::Value, E> {
+        Ok(StringOrNumber(v.to_string()))
+    }
+}
+
+/// OpenML encodes lists with a single element as that element, and empty lists as null; accept
+/// all of these forms
+fn one_or_many<'de, D, T>(deserializer: D) -> StdResult<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany<T> {
+        Many(Vec<T>),
+        One(T),
+    }
+
+    Ok(match Option::<OneOrMany<T>>::deserialize(deserializer)? {
+        Some(OneOrMany::Many(items)) => items,
+        Some(OneOrMany::One(item)) => vec![item],
+        None => vec![],
+    })
+}
+
+#[test]
+fn api_errors() {
+    match api_error(r#"{"error": {"code": "482", "message": "Unknown task"}}"#) {
+        Some(Error::Api { code, message }) => {
+            assert_eq!(code, 482);
+            assert_eq!(message, "Unknown task");
         }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(api_error(r#"{"task": {"task_id": "59"}}"#).is_none());
+    assert!(api_error("<html>Bad Gateway</html>").is_none());
+
+    match parse_response::<TaskResponse>(r#"{"error": {"code": 151, "message": "Unknown"}}"#) {
+        Err(Error::Api { code, .. }) => assert_eq!(code, 151),
+        other => panic!("unexpected {:?}", other),
     }
 }
+
+#[test]
+fn evaluation_measures() {
+    let measures = |json: &str| {
+        serde_json::from_str::<EvaluationMeasures>(json)
+            .unwrap()
+            .evaluation_measure
+    };
+    assert_eq!(
+        measures(r#"{"evaluation_measure": "predictive_accuracy"}"#),
+        vec!["predictive_accuracy"]
+    );
+    assert_eq!(
+        measures(r#"{"evaluation_measure": ["f_measure", "precision"]}"#),
+        vec!["f_measure", "precision"]
+    );
+    assert!(measures("[]").is_empty());
+    assert!(measures(r#"{"evaluation_measure": null}"#).is_empty());
+
+    let input: TaskInput =
+        serde_json::from_str(r#"{"name": "evaluation_measures", "evaluation_measures": null}"#)
+            .unwrap();
+    assert!(matches!(
+        input,
+        TaskInput::EvaluationMeasures {
+            evaluation_measures: None
+        }
+    ));
+}
+
+#[test]
+fn task_responses() {
+    let response: TaskResponse = parse_response(
+        r#"{"task": {"task_id": "59", "task_name": "Task 59: iris", "task_type_id": 1,
+            "input": [
+                {"name": "source_data", "data_set": {"data_set_id": "61", "target_feature": "class"}},
+                {"name": "estimation_procedure", "estimation_procedure": {"type": "crossvalidation",
+                    "parameter": {"name": "number_folds", "value": "10"}}},
+                {"name": "evaluation_measures", "evaluation_measures": []},
+                {"name": "cost_matrix", "cost_matrix": null},
+                {"name": "target_feature", "target_feature": "class"}
+            ],
+            "tag": "OpenML100"}}"#,
+    )
+    .unwrap();
+    let task = response.task;
+    assert_eq!(task.task_id.0, "59");
+    assert_eq!(task.task_type_id.0, "1");
+    assert_eq!(task.tag, vec!["OpenML100"]);
+    match &task.input[..] {
+        [TaskInput::SourceData { data_set }, TaskInput::EstimationProcedure {
+            estimation_procedure,
+        }, TaskInput::EvaluationMeasures {
+            evaluation_measures: Some(measures),
+        }, TaskInput::CostMatrix { cost_matrix: None }, TaskInput::Other] => {
+            assert_eq!(data_set.target_feature.as_deref(), Some("class"));
+            assert_eq!(estimation_procedure.parameter[0].name, "number_folds");
+            assert!(measures.evaluation_measure.is_empty());
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+
+    let drifted = parse_response::<TaskResponse>(r#"{"task": {"task_id": "59", "input": []}}"#);
+    match drifted {
+        Err(Error::JsonError(e)) => assert!(e.to_string().contains("task_name")),
+        other => panic!("unexpected {:?}", other),
+    }
+    match StringOrNumber("ten".to_owned()).parse::<u32>("number_folds") {
+        Err(Error::JsonFieldError { path, .. }) => assert_eq!(path, "number_folds"),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn upload_responses() {
+    let response: FlowExistsResponse =
+        parse_response(r#"{"flow_exists": {"exists": "false", "id": "-1"}}"#).unwrap();
+    assert_eq!(response.flow_exists.exists, "false");
+    let response: UploadFlowResponse =
+        parse_response(r#"{"upload_flow": {"id": "8353"}}"#).unwrap();
+    assert_eq!(response.upload_flow.id.0, "8353");
+    let response: UploadRunResponse =
+        parse_response(r#"{"upload_run": {"run_id": 10437}}"#).unwrap();
+    assert_eq!(response.upload_run.run_id.0, "10437");
+}
@@ -0,0 +1,97 @@
+//! Removing files from the cache, so that they are downloaded again
+
+use std::fs::{self, File};
+use std::path::Path;
+
+use crate::error::Result;
+
+use super::api_types::{parse_response, DataSetResponse};
+use super::client::default_client;
+use super::web_access::{cache_files, cache_path, read_text};
+use super::{DataSetIdentifier, OpenML};
+
+/// Prefixes of the names of all files in the cache; other files in the cache directory are left
+/// alone, in case it is shared with other programs
+const CACHE_FILE_PREFIXES: [&str; 2] = ["http", "features_"];
+
+impl OpenML {
+    /// Remove all cached downloads and parsed data sets, including those in the in-memory cache
+    /// of this client and its clones
+    pub fn clear_cache(&self) -> Result<()> {
+        self.memory_cache().clear();
+        remove_files(&self.cache_dir()?, |name| {
+            CACHE_FILE_PREFIXES.iter().any(|p| name.starts_with(p))
+        })
+    }
+
+    /// Remove the description and the files of a data set from the cache, so that they are
+    /// downloaded again the next time the data set is used
+    pub fn remove_cached_data_set<T: DataSetIdentifier>(&self, id: T) -> Result<()> {
+        let id = id.as_u32()?;
+        let info_url = self.api_url(&format!("data/{}", id));
+
+        if let Ok(file) = File::open(cache_path(self, &info_url)?) {
+            let data = read_text(file)?;
+            // a corrupt description is removed anyway; the files it names are unknown then
+            if let Ok(info) = parse_response::<DataSetResponse>(&data) {
+                let description = info.data_set_description;
+                self.remove_cached(&description.url)?;
+                if let Some(url) = description.parquet_url {
+                    self.remove_cached(&url)?;
+                }
+            }
+        }
+        self.remove_cached(&info_url)?;
+
+        let features = format!("features_{}_", id);
+        remove_files(&self.cache_dir()?, |name| name.starts_with(&features))
+    }
+}
+
+/// Remove all cached downloads and parsed data sets of the default client, like
+/// `OpenML::clear_cache`
+pub fn clear_cache() -> Result<()> {
+    default_client().clear_cache()
+}
+
+/// Remove a data set from the cache of the default client, like `OpenML::remove_cached_data_set`
+pub fn remove_cached_data_set<T: DataSetIdentifier>(id: T) -> Result<()> {
+    default_client().remove_cached_data_set(id)
+}
+
+/// remove the files in a directory whose names pass a filter
+fn remove_files(dir: &Path, remove: impl Fn(&str) -> bool) -> Result<()> {
+    for path in cache_files(dir)? {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if remove(&name) {
+            fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn remove_matching_files() {
+    let dir = std::env::temp_dir().join(format!("openml-test-{}-clear", std::process::id()));
+    fs::create_dir_all(dir.join("www.openml.org")).unwrap();
+    for name in &[
+        "www.openml.org/https_www.openml.org_a",
+        "features_61_0_0.f64",
+        "notes.txt",
+    ] {
+        fs::write(dir.join(name), "").unwrap();
+    }
+
+    remove_files(&dir, |name| {
+        CACHE_FILE_PREFIXES.iter().any(|p| name.starts_with(p))
+    })
+    .unwrap();
+    let mut left: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect();
+    left.sort();
+    assert_eq!(left, vec!["notes.txt", "www.openml.org"]);
+    assert_eq!(fs::read_dir(dir.join("www.openml.org")).unwrap().count(), 0);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,674 @@
+//! Clients that hold the settings of the access to OpenML
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT};
+
+use crate::error::{Error, Result};
+use crate::runs::UploadOptions;
+
+use super::memory_cache::MemoryCache;
+use super::stats::Counters;
+use super::{ResourceKind, RetryPolicy};
+
+/// The official OpenML server
+const DEFAULT_SERVER: &str = "https://www.openml.org";
+
+/// How long connections to the server are kept open without requests
+const IDLE_CONNECTION_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Default limits of the in-memory cache: entries and bytes
+const MEMORY_CACHE_LIMITS: (usize, usize) = (64, 256 * 1024 * 1024);
+
+/// Access to OpenML: the server, cache directory, API key, timeouts, retry policy, headers and
+/// offline mode, together with an HTTP client that keeps connections to the server open and an
+/// in-memory cache.
+///
+/// Every client has its own settings, so that e.g. the test server and the production server can
+/// be used side by side. Clones share the HTTP client, the in-memory cache and the cache
+/// statistics. The functions
+/// outside of `OpenML`, such as `SupervisedClassification::from_openml` or `prefetch_tasks`, use
+/// the default client, which is configured with `set_user_agent` and the other `set_*`
+/// functions.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use openml::OpenML;
+///
+/// let openml = OpenML::builder()
+///     .server("https://test.openml.org")
+///     .cache_dir("/tmp/openml")
+///     .timeouts(Some(Duration::from_secs(5)), Some(Duration::from_secs(60)))
+///     .build();
+/// let task = openml.classification_task(59).unwrap();
+/// println!("{}", task);
+/// ```
+#[derive(Clone)]
+pub struct OpenML {
+    settings: Arc<Settings>,
+}
+
+#[derive(Clone)]
+struct Settings {
+    cache_dir: Option<PathBuf>,
+    server: String,
+    api_key: Option<String>,
+    timeouts: (Option<Duration>, Option<Duration>),
+    retry_policy: RetryPolicy,
+    offline: bool,
+    /// name and version of the tool that uses this crate
+    user_agent: Option<String>,
+    extra_headers: Vec<(HeaderName, HeaderValue)>,
+    compress_transfers: bool,
+    /// read-only cache directory, and whether missing files fall through to the writable cache
+    shared_cache: Option<(PathBuf, bool)>,
+    /// time to live of cached resources, by kind
+    cache_ttl: [Option<Duration>; 4],
+    memory_cache: Arc<MemoryCache>,
+    stats: Arc<Counters>,
+    /// tags and visibility of uploaded flows and runs
+    upload_options: UploadOptions,
+    /// built on first use, from the timeouts, the headers and the transfer compression
+    http: OnceLock<reqwest::blocking::Client>,
+}
+
+/// The settings, without the API key
+impl fmt::Debug for OpenML {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let settings = &*self.settings;
+        f.debug_struct("OpenML")
+            .field("server", &settings.server)
+            .field("cache_dir", &settings.cache_dir)
+            .field("api_key", &settings.api_key.as_ref().map(|_| "..."))
+            .field("timeouts", &settings.timeouts)
+            .field("retry_policy", &settings.retry_policy)
+            .field("offline", &settings.offline)
+            .field("user_agent", &settings.user_agent)
+            .field("shared_cache", &settings.shared_cache)
+            .finish()
+    }
+}
+
+impl Default for OpenML {
+    fn default() -> Self {
+        OpenML::new()
+    }
+}
+
+impl OpenML {
+    /// create a client with the default settings
+    pub fn new() -> Self {
+        OpenML::builder().build()
+    }
+
+    /// start configuring a client
+    pub fn builder() -> OpenMLBuilder {
+        OpenMLBuilder::default()
+    }
+
+    /// get a client with the settings of this one, but that caches downloads in `path`; see
+    /// `OpenMLBuilder::cache_dir`. Both clients share their HTTP connections.
+    pub fn with_cache_dir<P: Into<PathBuf>>(&self, path: P) -> OpenML {
+        let path = path.into();
+        self.with_settings(|settings| settings.cache_dir = Some(path))
+    }
+
+    /// get a client with the settings of this one, but for the OpenML server at `url`, e.g. the
+    /// test server `https://test.openml.org`; see `OpenMLBuilder::server`. Both clients can be
+    /// used side by side.
+    ///
+    /// ```
+    /// use openml::OpenML;
+    ///
+    /// let production = OpenML::new();
+    /// let test = production.with_server("https://test.openml.org");
+    /// assert_eq!(production.server(), "https://www.openml.org");
+    /// assert_eq!(test.server(), "https://test.openml.org");
+    /// ```
+    pub fn with_server<S: Into<String>>(&self, url: S) -> OpenML {
+        let url = url.into().trim_end_matches('/').to_owned();
+        self.with_settings(|settings| settings.server = url)
+    }
+
+    /// get a client with the settings of this one, but that works offline or online; see
+    /// `OpenMLBuilder::offline`. Offline, everything is served from the cache, and queries for
+    /// anything that is not cached fail with `Error::Offline`.
+    pub fn offline(&self, offline: bool) -> OpenML {
+        self.with_settings(|settings| settings.offline = offline)
+    }
+
+    /// get a client with changed settings, which shares the HTTP client, the in-memory cache and
+    /// the cache statistics of this one
+    fn with_settings<F: FnOnce(&mut Settings)>(&self, change: F) -> OpenML {
+        let mut settings = Settings::clone(&self.settings);
+        change(&mut settings);
+        OpenML {
+            settings: Arc::new(settings),
+        }
+    }
+
+    /// get the base URL of the server, e.g. `https://www.openml.org`
+    pub fn server(&self) -> &str {
+        &self.settings.server
+    }
+
+    /// check whether the client works from the cache only
+    pub fn is_offline(&self) -> bool {
+        self.settings.offline
+    }
+
+    /// get the URL of a resource of the JSON API, e.g. `task/59`
+    pub(crate) fn api_url(&self, resource: &str) -> String {
+        format!("{}/api/v1/json/{}", self.settings.server, resource)
+    }
+
+    /// get the cache directory that was set, if any; see `cache_dir`
+    pub(crate) fn configured_cache_dir(&self) -> Option<PathBuf> {
+        self.settings.cache_dir.clone()
+    }
+
+    /// get the API key, failing with `Error::MissingApiKey` if none is set
+    pub(crate) fn require_api_key(&self) -> Result<&str> {
+        self.settings.api_key.as_deref().ok_or(Error::MissingApiKey)
+    }
+
+    pub(crate) fn retry_policy(&self) -> &RetryPolicy {
+        &self.settings.retry_policy
+    }
+
+    /// get the read-only cache directory and whether files missing from it are downloaded
+    pub(crate) fn shared_cache(&self) -> Option<(&Path, bool)> {
+        let (dir, fall_through) = self.settings.shared_cache.as_ref()?;
+        Some((dir, *fall_through))
+    }
+
+    /// get the time to live of the cached response of a URL
+    pub(crate) fn cache_ttl(&self, url: &str) -> Option<Duration> {
+        ResourceKind::of_url(url).and_then(|kind| self.settings.cache_ttl[kind.index()])
+    }
+
+    pub(crate) fn memory_cache(&self) -> &MemoryCache {
+        &self.settings.memory_cache
+    }
+
+    pub(crate) fn stats(&self) -> &Counters {
+        &self.settings.stats
+    }
+
+    pub(crate) fn upload_options(&self) -> &UploadOptions {
+        &self.settings.upload_options
+    }
+
+    /// get the headers that are sent with every request
+    fn headers(&self) -> HeaderMap {
+        let crate_agent = concat!(env!("CARGO_PKG_NAME"), "-rust/", env!("CARGO_PKG_VERSION"));
+        let agent = match self.settings.user_agent.as_ref() {
+            Some(tool) => format!("{} {}", tool, crate_agent),
+            None => crate_agent.to_owned(),
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert(USER_AGENT, HeaderValue::from_str(&agent).unwrap());
+        for (name, value) in &self.settings.extra_headers {
+            headers.insert(name.clone(), value.clone());
+        }
+        headers
+    }
+
+    /// get the HTTP client, building it on first use
+    pub(crate) fn http(&self) -> Result<reqwest::blocking::Client> {
+        if let Some(client) = self.settings.http.get() {
+            return Ok(client.clone());
+        }
+        let (connect, read) = self.settings.timeouts;
+        // with gzip enabled, requests without a `Range` header ask for a compressed response, which
+        // is decompressed while it is read. Idle connections stay open for the next request; TCP
+        // keep-alive stops firewalls from dropping them while a flow runs.
+        let client = reqwest::blocking::Client::builder()
+            .connect_timeout(connect)
+            .timeout(read)
+            .pool_idle_timeout(IDLE_CONNECTION_TIMEOUT)
+            .tcp_keepalive(IDLE_CONNECTION_TIMEOUT)
+            .gzip(self.settings.compress_transfers)
+            .default_headers(self.headers())
+            .build()?;
+        Ok(self.settings.http.get_or_init(|| client).clone())
+    }
+}
+
+/// Collects the settings of an `OpenML` client. Settings that are not given keep their defaults.
+#[derive(Debug, Clone, Default)]
+pub struct OpenMLBuilder {
+    cache_dir: Option<PathBuf>,
+    server: Option<String>,
+    api_key: Option<String>,
+    timeouts: Option<(Option<Duration>, Option<Duration>)>,
+    retry_policy: Option<RetryPolicy>,
+    offline: bool,
+    user_agent: Option<String>,
+    extra_headers: Vec<(HeaderName, HeaderValue)>,
+    transfer_compression: Option<bool>,
+    shared_cache: Option<(PathBuf, bool)>,
+    cache_ttl: [Option<Duration>; 4],
+    memory_cache_limits: Option<(usize, usize)>,
+    upload_options: UploadOptions,
+}
+
+impl OpenMLBuilder {
+    /// set the directory where downloaded files and parsed data sets are cached. It is created if
+    /// it does not exist.
+    ///
+    /// Without this setting the directory is taken from the `OPENML_CACHE_DIR` environment
+    /// variable, so that users can move the cache of a program that embeds this crate, and
+    /// otherwise it is the platform's cache directory; see `OpenML::cache_dir`.
+    pub fn cache_dir<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.cache_dir = Some(path.into());
+        self
+    }
+
+    /// set the OpenML server that tasks and data sets are loaded from and runs are uploaded to,
+    /// e.g. the test server `https://test.openml.org`. The default is `https://www.openml.org`.
+    /// Files from different servers are cached separately.
+    pub fn server<S: Into<String>>(mut self, url: S) -> Self {
+        self.server = Some(url.into().trim_end_matches('/').to_owned());
+        self
+    }
+
+    /// set the API key that uploads of flows and runs are authenticated with. It is shown in the
+    /// account settings on the OpenML website; the official server and the test server have
+    /// different keys. Loading tasks and data sets does not need a key.
+    ///
+    /// Without a key, uploads fail with `Error::MissingApiKey`.
+    pub fn api_key<S: Into<String>>(mut self, key: S) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+
+    /// set how long HTTP requests may wait for a connection to the server (`connect`), and for
+    /// each read of the response (`read`), e.g. while the server stalls in the middle of a large
+    /// data set. A request that times out fails, and is retried according to the retry policy.
+    /// `None` waits forever. Both timeouts default to 30 seconds.
+    pub fn timeouts(mut self, connect: Option<Duration>, read: Option<Duration>) -> Self {
+        self.timeouts = Some((connect, read));
+        self
+    }
+
+    /// set how requests that fail with a connection error or a retryable HTTP status, e.g. 503,
+    /// are repeated. By default they are attempted three times with exponential backoff; once all
+    /// attempts fail, the error of the last one is returned.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// choose whether to work offline. In offline mode all queries are answered from the cache,
+    /// and queries for anything that is not cached fail with `Error::Offline` instead of
+    /// accessing the network. The default is `false`.
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// set the name and version of the tool that uses this crate, e.g. `"mybench/1.2"`. It is sent
+    /// as the `User-Agent` of all requests, followed by the name and version of this crate, so
+    /// that the OpenML operators can tell where requests come from. Fails with
+    /// `Error::InvalidHeader` if it cannot be sent in a header.
+    ///
+    /// ```
+    /// let openml = openml::OpenML::builder().user_agent("mybench/1.2")?.build();
+    /// # Ok::<(), openml::Error>(())
+    /// ```
+    pub fn user_agent<S: Into<String>>(mut self, agent: S) -> Result<Self> {
+        let agent = agent.into();
+        header_value(USER_AGENT.as_str(), &agent)?;
+        self.user_agent = Some(agent);
+        Ok(self)
+    }
+
+    /// set headers that are sent with every request, e.g. the credentials an institutional proxy
+    /// requires. They replace the headers set before; a `User-Agent` among them replaces the one
+    /// set with `user_agent`. Fails with `Error::InvalidHeader` if a header is malformed.
+    ///
+    /// ```
+    /// use openml::OpenML;
+    ///
+    /// assert!(OpenML::builder().extra_headers(&[("X-Lab", "ml-group")]).is_ok());
+    /// assert!(OpenML::builder().extra_headers(&[("no spaces", "x")]).is_err());
+    /// ```
+    pub fn extra_headers(mut self, headers: &[(&str, &str)]) -> Result<Self> {
+        self.extra_headers = headers
+            .iter()
+            .map(|&(name, value)| {
+                let header =
+                    HeaderName::from_bytes(name.as_bytes()).map_err(|_| Error::InvalidHeader {
+                        name: name.to_owned(),
+                        value: value.to_owned(),
+                    })?;
+                Ok((header, header_value(name, value)?))
+            })
+            .collect::<Result<_>>()?;
+        Ok(self)
+    }
+
+    /// choose whether responses are requested gzip-compressed and decompressed while they are
+    /// read (the default). ARFF files and JSON descriptions compress well, so this makes downloads
+    /// of large data sets much faster. Resumed downloads are not compressed, because ranges refer
+    /// to the bytes of the uncompressed file.
+    pub fn transfer_compression(mut self, compress: bool) -> Self {
+        self.transfer_compression = Some(compress);
+        self
+    }
+
+    /// use a pre-populated cache directory that is never written to, e.g. a network mount that an
+    /// administrator filled for a whole lab. Files are looked up there before the writable cache
+    /// directory, and used as they are: they do not expire and are never removed.
+    ///
+    /// Files that are missing from the shared directory are downloaded into the writable cache
+    /// (see `cache_dir`) if `fall_through` is `true`; otherwise queries for them fail with
+    /// `Error::NotCached`.
+    pub fn shared_cache<P: Into<PathBuf>>(mut self, dir: P, fall_through: bool) -> Self {
+        self.shared_cache = Some((dir.into(), fall_through));
+        self
+    }
+
+    /// stop using a shared cache directory
+    pub(crate) fn without_shared_cache(mut self) -> Self {
+        self.shared_cache = None;
+        self
+    }
+
+    /// set how long cached resources of a kind are used before they are downloaded again, or
+    /// `None` to keep them forever. By default all resources are kept forever.
+    ///
+    /// Descriptions of tasks and data sets may be edited on the server, e.g. when a data set is
+    /// deactivated, while data set files never change. A typical setup therefore only sets a TTL
+    /// for descriptions:
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use openml::{OpenML, ResourceKind};
+    ///
+    /// let day = Duration::from_secs(24 * 60 * 60);
+    /// let openml = OpenML::builder()
+    ///     .cache_ttl(ResourceKind::Task, Some(day))
+    ///     .cache_ttl(ResourceKind::DataSetDescription, Some(day))
+    ///     .build();
+    /// ```
+    ///
+    /// Expired files are still used in offline mode.
+    pub fn cache_ttl(mut self, kind: ResourceKind, ttl: Option<Duration>) -> Self {
+        self.cache_ttl[kind.index()] = ttl;
+        self
+    }
+
+    /// set the size of the in-memory cache, in front of the file cache, as the maximum number of
+    /// entries and the maximum number of bytes. The defaults are 64 entries and 256 MiB; setting
+    /// either to 0 disables the cache.
+    ///
+    /// The cache keeps task descriptions, data set descriptions and parsed data sets, so that
+    /// loading a task a second time with the same client neither reads nor parses files. When it
+    /// is full, the least recently used entries are evicted.
+    pub fn memory_cache_limits(mut self, max_entries: usize, max_bytes: usize) -> Self {
+        self.memory_cache_limits = Some((max_entries, max_bytes));
+        self
+    }
+
+    /// set the tags and the visibility of all flows and runs the client uploads, so that
+    /// automated pipelines label their runs consistently, e.g. with the study they belong to.
+    /// Fails with `Error::InvalidTag` if the server would reject a tag.
+    pub fn upload_options(mut self, options: UploadOptions) -> Result<Self> {
+        options.check()?;
+        self.upload_options = options;
+        Ok(self)
+    }
+
+    /// create a client with the settings
+    pub fn build(self) -> OpenML {
+        let default_timeout = Some(Duration::from_secs(30));
+        let (max_entries, max_bytes) = self.memory_cache_limits.unwrap_or(MEMORY_CACHE_LIMITS);
+        OpenML {
+            settings: Arc::new(Settings {
+                cache_dir: self.cache_dir,
+                server: self.server.unwrap_or_else(|| DEFAULT_SERVER.to_owned()),
+                api_key: self.api_key,
+                timeouts: self.timeouts.unwrap_or((default_timeout, default_timeout)),
+                retry_policy: self.retry_policy.unwrap_or_default(),
+                offline: self.offline,
+                user_agent: self.user_agent,
+                extra_headers: self.extra_headers,
+                compress_transfers: self.transfer_compression.unwrap_or(true),
+                shared_cache: self.shared_cache,
+                cache_ttl: self.cache_ttl,
+                memory_cache: Arc::new(MemoryCache::new(max_entries, max_bytes)),
+                stats: Arc::default(),
+                upload_options: self.upload_options,
+                http: OnceLock::new(),
+            }),
+        }
+    }
+}
+
+fn header_value(name: &str, value: &str) -> Result<HeaderValue> {
+    HeaderValue::from_str(value).map_err(|_| Error::InvalidHeader {
+        name: name.to_owned(),
+        value: value.to_owned(),
+    })
+}
+
+/// Settings of the client used by the functions outside of `OpenML`
+static DEFAULT_BUILDER: RwLock<Option<OpenMLBuilder>> = RwLock::new(None);
+
+/// Client used by the functions outside of `OpenML`; built on first use
+static DEFAULT_CLIENT: RwLock<Option<OpenML>> = RwLock::new(None);
+
+/// get the client with the default settings
+pub(crate) fn default_client() -> OpenML {
+    if let Some(openml) = DEFAULT_CLIENT.read().unwrap().as_ref() {
+        return openml.clone();
+    }
+    // the settings stay locked, so that they cannot change while the client is built
+    let builder = DEFAULT_BUILDER.read().unwrap();
+    DEFAULT_CLIENT
+        .write()
+        .unwrap()
+        .get_or_insert_with(|| builder.clone().unwrap_or_default().build())
+        .clone()
+}
+
+/// Change the settings of the default client. The next request builds a new default client with
+/// them, which starts with an empty in-memory cache and zero `cache_stats`.
+pub(crate) fn configure_default_client<F>(change: F)
+where
+    F: FnOnce(OpenMLBuilder) -> OpenMLBuilder,
+{
+    let mut builder = DEFAULT_BUILDER.write().unwrap();
+    *builder = Some(change(builder.clone().unwrap_or_default()));
+    *DEFAULT_CLIENT.write().unwrap() = None;
+}
+
+/// Change the settings of the default client like `configure_default_client`, unless `change`
+/// fails
+pub(crate) fn try_configure_default_client<F>(change: F) -> Result<()>
+where
+    F: FnOnce(OpenMLBuilder) -> Result<OpenMLBuilder>,
+{
+    let mut builder = DEFAULT_BUILDER.write().unwrap();
+    *builder = Some(change(builder.clone().unwrap_or_default())?);
+    *DEFAULT_CLIENT.write().unwrap() = None;
+    Ok(())
+}
+
+#[test]
+fn builder() {
+    let openml = OpenML::new();
+    assert_eq!(openml.server(), DEFAULT_SERVER);
+    assert!(!openml.is_offline());
+    assert_eq!(openml.configured_cache_dir(), None);
+    assert!(matches!(
+        openml.require_api_key(),
+        Err(Error::MissingApiKey)
+    ));
+    assert_eq!(*openml.retry_policy(), RetryPolicy::default());
+
+    let test = OpenML::builder()
+        .server("https://test.openml.org/")
+        .cache_dir("/tmp/openml-test")
+        .api_key("abc")
+        .timeouts(None, Some(Duration::from_secs(5)))
+        .retry_policy(RetryPolicy::none())
+        .offline(true)
+        .build();
+    assert_eq!(test.server(), "https://test.openml.org");
+    assert_eq!(
+        test.api_url("task/59"),
+        "https://test.openml.org/api/v1/json/task/59"
+    );
+    assert_eq!(
+        test.configured_cache_dir(),
+        Some(PathBuf::from("/tmp/openml-test"))
+    );
+    assert_eq!(test.require_api_key().unwrap(), "abc");
+    assert_eq!(test.retry_policy().max_attempts, 1);
+    assert!(test.is_offline());
+    assert!(!format!("{:?}", test).contains("abc"));
+
+    // building another client leaves the first one alone
+    assert_eq!(openml.server(), DEFAULT_SERVER);
+    assert!(!openml.is_offline());
+}
+
+#[test]
+fn shared_http_client() {
+    let openml = OpenML::builder()
+        .timeouts(None, Some(Duration::from_secs(5)))
+        .build();
+    let clone = openml.clone();
+    openml.http().unwrap();
+    assert!(clone.settings.http.get().is_some());
+    assert!(OpenML::new().settings.http.get().is_none());
+}
+
+#[test]
+fn separate_cache_dirs() {
+    let base = std::env::temp_dir().join(format!("openml-test-{}-dirs", std::process::id()));
+    let openml = OpenML::builder().cache_dir(base.join("a")).build();
+    let other = openml.with_cache_dir(base.join("b"));
+
+    assert_eq!(openml.cache_dir().unwrap(), base.join("a"));
+    assert_eq!(other.cache_dir().unwrap(), base.join("b"));
+    assert!(base.join("b").is_dir());
+
+    let url = "https://www.openml.org/api/v1/json/task/59";
+    let path = super::web_access::cache_path(&other, url).unwrap();
+    assert!(path.starts_with(base.join("b")));
+    std::fs::remove_dir_all(&base).unwrap();
+}
+
+#[test]
+fn servers_side_by_side() {
+    let production = OpenML::new();
+    let test = production.with_server("https://test.openml.org/");
+    assert_eq!(
+        production.api_url("task/59"),
+        "https://www.openml.org/api/v1/json/task/59"
+    );
+    assert_eq!(
+        test.api_url("task/59"),
+        "https://test.openml.org/api/v1/json/task/59"
+    );
+
+    let dir = std::env::temp_dir().join(format!("openml-test-{}-servers", std::process::id()));
+    let production = production.with_cache_dir(&dir);
+    let test = test.with_cache_dir(&dir);
+    let cached = |openml: &OpenML| {
+        super::web_access::cache_path(openml, &openml.api_url("task/59")).unwrap()
+    };
+    assert_ne!(cached(&production), cached(&test));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn settings_per_client() {
+    let day = Duration::from_secs(24 * 60 * 60);
+    let tool = OpenML::builder()
+        .user_agent("mybench/1.2")
+        .unwrap()
+        .extra_headers(&[("X-Lab", "ml-group")])
+        .unwrap()
+        .shared_cache("/mnt/openml", false)
+        .cache_ttl(ResourceKind::Task, Some(day))
+        .build();
+    let plain = OpenML::new();
+
+    let headers = tool.headers();
+    assert!(headers[USER_AGENT]
+        .to_str()
+        .unwrap()
+        .starts_with("mybench/1.2 openml-rust/"));
+    assert_eq!(headers["x-lab"], "ml-group");
+    assert!(plain.headers()[USER_AGENT]
+        .to_str()
+        .unwrap()
+        .starts_with("openml-rust/"));
+    assert!(plain.headers().get("x-lab").is_none());
+
+    assert_eq!(tool.shared_cache(), Some((Path::new("/mnt/openml"), false)));
+    assert_eq!(plain.shared_cache(), None);
+
+    let task_url = tool.api_url("task/59");
+    assert_eq!(tool.cache_ttl(&task_url), Some(day));
+    assert_eq!(tool.cache_ttl(&tool.api_url("data/61")), None);
+    assert_eq!(plain.cache_ttl(&task_url), None);
+
+    assert!(matches!(
+        OpenML::builder().user_agent("line\nbreak"),
+        Err(Error::InvalidHeader { .. })
+    ));
+}
+
+#[test]
+fn memory_cache_per_client() {
+    let dir = std::env::temp_dir().join(format!("openml-test-{}-memory", std::process::id()));
+    let openml = OpenML::builder().cache_dir(dir.join("a")).build();
+    let clone = openml.clone();
+    let other = OpenML::builder().cache_dir(dir.join("b")).build();
+    let tiny = OpenML::builder().memory_cache_limits(0, 0).build();
+
+    let url = openml.api_url("task/59");
+    for client in &[&openml, &other, &tiny] {
+        client.memory_cache().insert_response(&url, "{}".to_owned());
+    }
+    assert!(clone.memory_cache().get_response(&url).is_some());
+    assert!(tiny.memory_cache().get_response(&url).is_none());
+
+    // clearing the cache of one client leaves the others alone
+    other.clear_cache().unwrap();
+    assert!(other.memory_cache().get_response(&url).is_none());
+    assert!(openml.memory_cache().get_response(&url).is_some());
+    clone.clear_cache().unwrap();
+    assert!(openml.memory_cache().get_response(&url).is_none());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn upload_options_per_client() {
+    use crate::runs::Visibility;
+
+    let options = UploadOptions::new()
+        .with_tag("study_42")
+        .with_visibility(Visibility::Private);
+    let private = OpenML::builder()
+        .upload_options(options.clone())
+        .unwrap()
+        .build();
+    assert_eq!(*private.upload_options(), options);
+    assert_eq!(*OpenML::new().upload_options(), UploadOptions::new());
+
+    let invalid = UploadOptions::new().with_tag("no spaces");
+    assert!(matches!(
+        OpenML::builder().upload_options(invalid),
+        Err(Error::InvalidTag { .. })
+    ));
+}
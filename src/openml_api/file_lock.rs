@@ -58,6 +58,12 @@ impl SharedLock {
         file.lock_shared()?;
         Ok(SharedLock { file })
     }
+
+    /// get the locked file, e.g. to clone it for readers that need to seek
+    #[cfg(feature = "parquet")]
+    pub fn file(&self) -> &File {
+        &self.file
+    }
 }
 
 impl Drop for SharedLock {
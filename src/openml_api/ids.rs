@@ -0,0 +1,147 @@
+//! Identifiers of OpenML objects
+
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::error::{Error, Result};
+
+/// Identifiers of OpenML objects: numbers, or strings of digits
+pub trait Id {
+    fn as_string(&self) -> Cow<'_, str>;
+
+    /// get the numeric id; fails with `Error::InvalidId` if it is not a number
+    fn as_u32(&self) -> Result<u32>;
+}
+
+/// Ids that identify tasks: `TaskId`, or numbers and strings
+pub trait TaskIdentifier: Id {}
+
+/// Ids that identify data sets: `DataSetId`, or numbers and strings
+pub trait DataSetIdentifier: Id {}
+
+/// Ids that identify flows: `FlowId`, or numbers and strings
+pub trait FlowIdentifier: Id {}
+
+/// Ids that identify runs: `RunId`, or numbers and strings
+pub trait RunIdentifier: Id {}
+
+fn parse_id(id: &str) -> Result<u32> {
+    id.trim()
+        .parse()
+        .map_err(|_| Error::InvalidId { id: id.to_owned() })
+}
+
+impl Id for String {
+    #[inline(always)]
+    fn as_string(&self) -> Cow<'_, str> {
+        Cow::from(self.as_str())
+    }
+
+    #[inline(always)]
+    fn as_u32(&self) -> Result<u32> {
+        parse_id(self)
+    }
+}
+
+impl Id for &str {
+    #[inline(always)]
+    fn as_string(&self) -> Cow<'_, str> {
+        Cow::from(*self)
+    }
+
+    #[inline(always)]
+    fn as_u32(&self) -> Result<u32> {
+        parse_id(self)
+    }
+}
+
+impl Id for u32 {
+    #[inline(always)]
+    fn as_string(&self) -> Cow<'_, str> {
+        Cow::from(format!("{}", self))
+    }
+
+    #[inline(always)]
+    fn as_u32(&self) -> Result<u32> {
+        Ok(*self)
+    }
+}
+
+macro_rules! untyped_ids {
+    ($($marker:ident),*) => {
+        $(
+            impl $marker for String {}
+            impl<'a> $marker for &'a str {}
+            impl $marker for u32 {}
+        )*
+    };
+}
+
+untyped_ids!(
+    TaskIdentifier,
+    DataSetIdentifier,
+    FlowIdentifier,
+    RunIdentifier
+);
+
+macro_rules! typed_id {
+    ($(#[$doc:meta])* $name:ident: $marker:ident) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $name(pub u32);
+
+        impl Id for $name {
+            #[inline(always)]
+            fn as_string(&self) -> Cow<'_, str> {
+                Cow::from(self.0.to_string())
+            }
+
+            #[inline(always)]
+            fn as_u32(&self) -> Result<u32> {
+                Ok(self.0)
+            }
+        }
+
+        impl $marker for $name {}
+
+        impl From<u32> for $name {
+            fn from(id: u32) -> Self {
+                $name(id)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+    };
+}
+
+typed_id!(
+    /// The id of a task; unlike a plain number it cannot be mistaken for the id of a data set
+    TaskId: TaskIdentifier
+);
+typed_id!(
+    /// The id of a data set
+    DataSetId: DataSetIdentifier
+);
+typed_id!(
+    /// The id of a flow
+    FlowId: FlowIdentifier
+);
+typed_id!(
+    /// The id of a run
+    RunId: RunIdentifier
+);
+
+#[test]
+fn ids() {
+    assert_eq!("59".as_u32().unwrap(), 59);
+    assert_eq!(TaskId(59).as_u32().unwrap(), 59);
+    assert_eq!(DataSetId::from(61).to_string(), "61");
+    match "not-a-number".as_u32() {
+        Err(Error::InvalidId { id }) => assert_eq!(id, "not-a-number"),
+        other => panic!("unexpected {:?}", other),
+    }
+}
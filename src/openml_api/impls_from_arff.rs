@@ -0,0 +1,317 @@
+//! implementations to parse ARFF files line by line, for files too large to be deserialized in one
+//! piece
+use std::io::BufRead;
+
+use crate::error::{Error, Result};
+use crate::procedures::{CompactFold, CompactSets, Fold, FrozenSets};
+
+impl CompactSets {
+    /// Parse a splits file in streaming fashion, keeping only one bit per row and fold in memory.
+    ///
+    /// Returns `None` if a row is listed twice in the training or testing set of a fold, which bit
+    /// sets cannot represent; such files must be loaded with `FrozenSets::from_reader`.
+    ///
+    /// If `lenient` is set, a malformed line ends parsing instead of failing it: the folds that
+    /// were completed before the line are kept and a warning is logged. The fold of the last line
+    /// that could be parsed, and all folds after it, are dropped because they may be incomplete.
+    pub(crate) fn from_reader<R: BufRead>(reader: R, lenient: bool) -> Result<Option<Self>> {
+        Ok(read_splits(reader, lenient)?.map(|folds| CompactSets { folds }))
+    }
+}
+
+impl FrozenSets {
+    /// Parse a splits file into index vectors, which keep the rows of every fold in file order,
+    /// including rows that are listed twice. See `CompactSets::from_reader` for `lenient`.
+    pub(crate) fn from_reader<R: BufRead>(reader: R, lenient: bool) -> Result<Self> {
+        match read_splits(reader, lenient)? {
+            Some(folds) => Ok(FrozenSets { folds }),
+            None => unreachable!("index vectors keep repeated rows"),
+        }
+    }
+}
+
+/// The rows of a fold, collected while a splits file is parsed
+trait SplitFold: Clone {
+    fn empty() -> Self;
+
+    /// add a row to the training or testing set. Returns `false` if the set cannot hold the row
+    /// because it is already in it.
+    fn insert(&mut self, test: bool, rowid: usize) -> bool;
+}
+
+impl SplitFold for Fold {
+    fn empty() -> Self {
+        Fold::new()
+    }
+
+    fn insert(&mut self, test: bool, rowid: usize) -> bool {
+        if test {
+            self.testset.push(rowid);
+        } else {
+            self.trainset.push(rowid);
+        }
+        true
+    }
+}
+
+impl SplitFold for CompactFold {
+    fn empty() -> Self {
+        CompactFold::new()
+    }
+
+    fn insert(&mut self, test: bool, rowid: usize) -> bool {
+        if test {
+            self.testset.insert(rowid)
+        } else {
+            self.trainset.insert(rowid)
+        }
+    }
+}
+
+/// Parse a splits file into folds, or `None` if a fold cannot hold one of its rows. See
+/// `CompactSets::from_reader` for `lenient`.
+fn read_splits<R: BufRead, F: SplitFold>(reader: R, lenient: bool) -> Result<Option<Vec<Vec<F>>>> {
+    let mut folds = vec![];
+    let mut current = None;
+
+    match parse_splits(reader, &mut folds, &mut current) {
+        Ok(true) => Ok(Some(folds)),
+        Ok(false) => Ok(None),
+        Err(e) => {
+            let (repeat, fold) = match (lenient, current) {
+                (true, Some(current)) => current,
+                _ => return Err(e),
+            };
+
+            folds.truncate(repeat + 1);
+            folds[repeat].truncate(fold);
+            if folds[repeat].is_empty() {
+                folds.pop();
+            }
+            if folds.is_empty() {
+                return Err(e);
+            }
+
+            warn!(
+                "Malformed splits file ({:?}); keeping the {} folds of the first {} repeats \
+                 before repeat {}, fold {}",
+                e,
+                folds.iter().map(Vec::len).sum::<usize>(),
+                folds.len(),
+                repeat,
+                fold
+            );
+            Ok(Some(folds))
+        }
+    }
+}
+
+/// Parse the lines of a splits file into `folds`. `current` tracks the (repeat, fold) of the last
+/// data line, so that callers can tell which folds were complete when an error occurred. Returns
+/// `false` if parsing stopped at a row that its fold could not hold.
+fn parse_splits<R: BufRead, F: SplitFold>(
+    reader: R,
+    folds: &mut Vec<Vec<F>>,
+    current: &mut Option<(usize, usize)>,
+) -> Result<bool> {
+    let mut attributes = Vec::new();
+    let mut columns = None;
+
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        let lineno = i + 1;
+
+        if line.is_empty() || line.starts_with('%') {
+            continue;
+        }
+
+        let (purpose_col, rowid_col, repeat_col, fold_col) = match columns {
+            Some(cols) => cols,
+            None => {
+                let lower = line.to_lowercase();
+                if lower.starts_with("@attribute") {
+                    let name = line["@attribute".len()..]
+                        .split_whitespace()
+                        .next()
+                        .map(|name| name.trim_matches(|c| c == '\'' || c == '"'))
+                        .map(str::to_lowercase)
+                        .ok_or_else(|| splits_error(lineno, None, "attribute without name"))?;
+                    attributes.push(name);
+                } else if lower.starts_with("@data") {
+                    let find = |name: &str| {
+                        attributes.iter().position(|a| a == name).ok_or_else(|| {
+                            splits_error(lineno, None, &format!("missing attribute '{}'", name))
+                        })
+                    };
+                    columns = Some((
+                        find("type")?,
+                        find("rowid")?,
+                        find("repeat")?,
+                        find("fold")?,
+                    ));
+                }
+                continue;
+            }
+        };
+
+        let values: Vec<_> = line
+            .split(',')
+            .map(|v| v.trim().trim_matches(|c| c == '\'' || c == '"'))
+            .collect();
+
+        if values.len() != attributes.len() {
+            return Err(splits_error(
+                lineno,
+                *current,
+                &format!(
+                    "expected {} values but found {}",
+                    attributes.len(),
+                    values.len()
+                ),
+            ));
+        }
+
+        let parse = |col: usize| {
+            values[col].parse::<usize>().map_err(|_| {
+                splits_error(
+                    lineno,
+                    *current,
+                    &format!("invalid integer '{}'", values[col]),
+                )
+            })
+        };
+        let rowid = parse(rowid_col)?;
+        let repeat = parse(repeat_col)?;
+        let fold = parse(fold_col)?;
+        *current = Some((repeat, fold));
+
+        if repeat >= folds.len() {
+            folds.resize(repeat + 1, vec![]);
+        }
+        let rep = &mut folds[repeat];
+
+        if fold >= rep.len() {
+            rep.resize(fold + 1, F::empty());
+        }
+
+        let test = match values[purpose_col] {
+            "TRAIN" => false,
+            "TEST" => true,
+            other => {
+                return Err(splits_error(
+                    lineno,
+                    *current,
+                    &format!(
+                        "expected TRAIN or TEST for row {} but found '{}'",
+                        rowid, other
+                    ),
+                ))
+            }
+        };
+        if !rep[fold].insert(test, rowid) {
+            debug!(
+                "Row {} is listed twice in repeat {}, fold {} of the splits",
+                rowid, repeat, fold
+            );
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+fn splits_error(line: usize, location: Option<(usize, usize)>, message: &str) -> Error {
+    Error::SplitsFormatError {
+        line,
+        repeat: location.map(|(repeat, _)| repeat),
+        fold: location.map(|(_, fold)| fold),
+        message: message.to_owned(),
+    }
+}
+
+#[test]
+fn compact_sets_from_reader() {
+    use crate::procedures::Procedure;
+
+    let arff = "@RELATION splits\n\
+                @ATTRIBUTE type {TRAIN,TEST}\n\
+                @ATTRIBUTE rowid NUMERIC\n\
+                @ATTRIBUTE repeat NUMERIC\n\
+                @ATTRIBUTE fold NUMERIC\n\
+                @DATA\n\
+                TRAIN,2,0,0\n\
+                TRAIN,0,0,0\n\
+                TEST,1,0,0\n\
+                % a comment\n\
+                TEST,0,0,1\n\
+                TRAIN,1,0,1\n";
+
+    let sets = CompactSets::from_reader(arff.as_bytes(), false)
+        .unwrap()
+        .unwrap();
+    let folds: Vec<_> = sets.iter().map(|(_, _, fold)| fold).collect();
+
+    assert_eq!(folds.len(), 2);
+    assert_eq!(folds[0].trainset, vec![0, 2]);
+    assert_eq!(folds[0].testset, vec![1]);
+    assert_eq!(folds[1].trainset, vec![1]);
+    assert_eq!(folds[1].testset, vec![0]);
+}
+
+#[test]
+fn lenient_splits() {
+    use crate::procedures::Procedure;
+
+    let arff = "@RELATION splits\n\
+                @ATTRIBUTE type {TRAIN,TEST}\n\
+                @ATTRIBUTE rowid NUMERIC\n\
+                @ATTRIBUTE repeat NUMERIC\n\
+                @ATTRIBUTE fold NUMERIC\n\
+                @DATA\n\
+                TRAIN,1,0,0\n\
+                TEST,0,0,0\n\
+                TRAIN,0,0,1\n\
+                TEST,1,0,1\n\
+                TRAIN,1,0,2\n\
+                TEST,x,0,2\n";
+
+    match CompactSets::from_reader(arff.as_bytes(), false) {
+        Err(Error::SplitsFormatError {
+            line, repeat, fold, ..
+        }) => assert_eq!((line, repeat, fold), (12, Some(0), Some(2))),
+        other => panic!("expected splits format error, got {:?}", other),
+    }
+
+    let sets = CompactSets::from_reader(arff.as_bytes(), true)
+        .unwrap()
+        .unwrap();
+    assert_eq!(sets.iter().count(), 2);
+}
+
+#[test]
+fn repeated_rows() {
+    use crate::procedures::Procedure;
+
+    let arff = "@RELATION splits\n\
+                @ATTRIBUTE type {TRAIN,TEST}\n\
+                @ATTRIBUTE rowid NUMERIC\n\
+                @ATTRIBUTE repeat NUMERIC\n\
+                @ATTRIBUTE fold NUMERIC\n\
+                @DATA\n\
+                TRAIN,2,0,0\n\
+                TRAIN,0,0,0\n\
+                TRAIN,2,0,0\n\
+                TEST,1,0,0\n";
+
+    // bit sets would silently drop the second row 2
+    assert!(CompactSets::from_reader(arff.as_bytes(), false)
+        .unwrap()
+        .is_none());
+
+    let sets = FrozenSets::from_reader(arff.as_bytes(), false).unwrap();
+    let folds: Vec<_> = sets.iter().map(|(_, _, fold)| fold).collect();
+    assert_eq!(folds.len(), 1);
+    assert_eq!(folds[0].trainset, vec![2, 0, 2]);
+    assert_eq!(folds[0].testset, vec![1]);
+}
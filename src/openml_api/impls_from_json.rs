@@ -1,133 +1,491 @@
 //! implementations to convert the API's JSON responses into corresponding Rust structures
-use arff;
-use arff::dynamic::DataSet as ArffDataSet;
-use serde_json;
+use std::io::BufReader;
 
+use serde::Deserialize;
+
+use crate::data_frame::DataFrame;
 use crate::dataset::DataSet;
-use crate::error::Result;
-use crate::procedures::{Fold, FrozenSets};
-use crate::tasks::{SupervisedClassification, SupervisedRegression};
+use crate::error::{Context, Error, Result};
+use crate::procedures::{CompactSets, EstimationProcedure, FrozenSets, Procedure, ProcedureType};
+use crate::sparse::{self, SparseDataSet};
+use crate::splits;
+use crate::tasks::{
+    CostMatrix, MissingValues, SingleClassPolicy, SupervisedClassification, SupervisedRegression,
+};
 
-use super::api_types::{CrossValItem, GenericResponse, TrainTest};
-use super::web_access::get_cached;
+use super::api_types::{
+    parse_response, DataSetDescription, DataSetInput, DataSetResponse,
+    EstimationProcedureDescription, StringOrNumber, TaskDescription, TaskInput,
+};
+use super::client::default_client;
+use super::parsed_cache::parse_cached;
+use super::web_access::{get_cached, get_cached_verified, open_cached};
+use super::{lenient_splits, random_seed, splits_streaming_threshold, OpenML};
 
 impl DataSet {
-    fn from_json(item: &serde_json::Value) -> Self {
-        let v = &item["data_set"];
-        let id = v["data_set_id"].as_str().unwrap();
-        let target = v["target_feature"].as_str();
+    fn from_json(input: &DataSetInput, openml: &OpenML) -> Result<Self> {
+        let id = input.data_set_id.0.as_str();
+
+        let info_url = openml.api_url(&format!("data/{}", id));
+        let description = data_set_description(openml, &info_url).context(&info_url, || {
+            format!("fetching the description of data set {}", id)
+        })?;
+        check_status(id, &description)?;
 
-        let info_url = format!("https://www.openml.org/api/v1/json/data/{}", id);
-        let info: GenericResponse = serde_json::from_str(&get_cached(&info_url).unwrap()).unwrap();
+        let target = input
+            .target_feature
+            .as_ref()
+            .or(description.default_target_attribute.as_ref())
+            .cloned();
 
-        let default_target = info
-            .look_up("/data_set_description/default_target_attribute")
-            .and_then(|v| v.as_str());
+        let dset_url = description.url.as_str();
+        let version = description.version.as_ref().and_then(|v| v.0.parse().ok());
 
-        let target = match (default_target, target) {
-            (Some(s), None) | (_, Some(s)) => Some(s.to_owned()),
-            (None, None) => None,
+        let mut dataset = DataSet {
+            id: id.to_owned(),
+            version,
+            frame: DataFrame::new("", vec![]),
+            target,
+            row_id: description.row_id_attribute.clone(),
+            ignored: description.ignore_attribute.clone(),
+            dropped: vec![],
+            sparse: None,
         };
 
-        let dset_url = info
-            .look_up("/data_set_description/url")
-            .unwrap()
-            .as_str()
-            .unwrap();
-        let dset_str = get_cached(&dset_url).unwrap();
-        let dset = ArffDataSet::from_str(&dset_str).unwrap();
+        #[cfg(feature = "parquet")]
+        {
+            let parquet_url = description.parquet_url.as_deref();
+            if let (true, Some(url)) = (super::prefer_parquet(), parquet_url) {
+                match load_parquet(openml, url, &description) {
+                    Ok(frame) => {
+                        dataset.frame = frame;
+                        return Ok(dataset);
+                    }
+                    Err(e) => warn!("Could not load {} ({:?}); using ARFF instead", url, e),
+                }
+            }
+        }
+
+        if let Some(frame) = openml.memory_cache().get_frame(dset_url) {
+            dataset.frame = frame.as_ref().clone();
+            return Ok(dataset);
+        }
+
+        let dset_str = match description.md5_checksum.as_deref() {
+            Some(md5) => get_cached_verified(openml, dset_url, md5),
+            None => get_cached(openml, dset_url),
+        }
+        .context(dset_url, || format!("fetching data set {}", id))?;
+        let parsing = || format!("parsing data set {}", id);
+
+        if sparse::is_sparse(&dset_str) {
+            let mut sparse = SparseDataSet::from_str(&dset_str).context(dset_url, parsing)?;
+            match dataset.target.as_ref().and_then(|t| sparse.column_index(t)) {
+                Some(target) => {
+                    // keep the features sparse, and only the target and the excluded columns in
+                    // dense form
+                    let mut dense: Vec<usize> = dataset
+                        .excluded()
+                        .iter()
+                        .filter_map(|name| sparse.column_index(name))
+                        .collect();
+                    dense.push(target);
+                    dense.sort_unstable();
+                    dense.dedup();
 
-        DataSet { arff: dset, target }
+                    let columns = dense.iter().map(|&col| sparse.column(col)).collect();
+                    dataset.frame = DataFrame::new(sparse.name(), columns);
+                    for &col in dense.iter().rev() {
+                        sparse = sparse.without_column(col);
+                    }
+                    dataset.sparse = Some(sparse);
+                }
+                None => dataset.frame = sparse.to_data_frame(),
+            }
+            return Ok(dataset);
+        }
+
+        dataset.frame = parse_cached(openml, dset_url, &dset_str).context(dset_url, parsing)?;
+        openml
+            .memory_cache()
+            .insert_frame(dset_url, dataset.frame.clone());
+        Ok(dataset)
     }
 }
 
+/// fail with `Error::DatasetUnavailable` unless a data set is active. Data sets that are
+/// deactivated or still in preparation have no usable data file.
+fn check_status(id: &str, description: &DataSetDescription) -> Result<()> {
+    match description.status.as_deref() {
+        Some(status) if status != "active" => Err(Error::DatasetUnavailable {
+            id: id.to_owned(),
+            status: status.to_owned(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// fetch the description of a data set
+fn data_set_description(openml: &OpenML, url: &str) -> Result<DataSetDescription> {
+    let response: DataSetResponse = parse_response(&get_cached(openml, url)?)?;
+    Ok(response.data_set_description)
+}
+
+/// download a data set in Parquet format
+#[cfg(feature = "parquet")]
+fn load_parquet(openml: &OpenML, url: &str, description: &DataSetDescription) -> Result<DataFrame> {
+    let name = description.name.as_deref().unwrap_or_default();
+    let (lock, _) = open_cached(openml, url)?;
+    DataFrame::read_parquet(name, lock.file().try_clone()?)
+}
+
 impl SupervisedClassification {
-    pub fn from_json(task_json: &serde_json::Value) -> Self {
+    /// build a task from the `task` object of the API's JSON response, loading its inputs with
+    /// the default client
+    pub fn from_json(task_json: &serde_json::Value) -> Result<Self> {
+        let task = TaskDescription::deserialize(task_json)?;
+        SupervisedClassification::from_description(task, &default_client())
+    }
+
+    /// build a task from its description
+    pub(crate) fn from_description(task: TaskDescription, openml: &OpenML) -> Result<Self> {
         let mut source_data = None;
         let mut estimation_procedure = None;
-        //let mut cost_matrix = None;
+        let mut cost_matrix = CostMatrix::None;
+        let mut evaluation_measures = vec![];
 
-        for input_item in task_json["input"].as_array().unwrap() {
-            match input_item["name"].as_str() {
-                Some("source_data") => source_data = Some(DataSet::from_json(input_item)),
-                Some("estimation_procedure") => {
-                    estimation_procedure = Some(Box::new(FrozenSets::from_json(input_item)))
+        for input in &task.input {
+            match input {
+                TaskInput::SourceData { data_set } => {
+                    source_data = Some(DataSet::from_json(data_set, openml)?)
+                }
+                TaskInput::EstimationProcedure {
+                    estimation_procedure: procedure,
+                } => estimation_procedure = Some(procedure),
+                TaskInput::EvaluationMeasures {
+                    evaluation_measures: Some(measures),
+                } => evaluation_measures = measures.evaluation_measure.clone(),
+                TaskInput::CostMatrix { cost_matrix: costs } => {
+                    cost_matrix = CostMatrix::from_json(costs.as_deref())?
                 }
-                //Some("cost_matrix") => cost_matrix = Some(input_item.into()),
-                Some(_) => {}
-                None => panic!("/task/input/name is not a string"),
+                _ => {}
             }
         }
 
-        SupervisedClassification {
-            id: task_json["task_id"].as_str().unwrap().to_owned(),
-            name: task_json["task_name"].as_str().unwrap().to_owned(),
-            source_data: source_data.unwrap(),
-            estimation_procedure: estimation_procedure.unwrap(),
-            //cost_matrix: cost_matrix.unwrap(),
-        }
+        let source_data = source_data.ok_or_else(|| missing_input("source data"))?;
+        let procedure_json =
+            estimation_procedure.ok_or_else(|| missing_input("estimation procedure"))?;
+        let estimation_procedure = EstimationProcedure::from_json(procedure_json)?;
+        let class_labels = source_data.class_labels();
+
+        Ok(SupervisedClassification {
+            splits: splits_from_json(openml, procedure_json, &estimation_procedure, &source_data)?,
+            id: task.task_id.0,
+            name: task.task_name,
+            tags: task.tag,
+            source_data,
+            estimation_procedure,
+            evaluation_measures,
+            cost_matrix,
+            class_labels,
+            single_class_policy: SingleClassPolicy::default(),
+            missing_values: MissingValues::default(),
+            progress: None,
+            openml: openml.clone(),
+        })
     }
 }
 
 impl SupervisedRegression {
-    pub fn from_json(task_json: &serde_json::Value) -> Self {
+    /// build a task from the `task` object of the API's JSON response, loading its inputs with
+    /// the default client
+    pub fn from_json(task_json: &serde_json::Value) -> Result<Self> {
+        let task = TaskDescription::deserialize(task_json)?;
+        SupervisedRegression::from_description(task, &default_client())
+    }
+
+    /// build a task from its description
+    pub(crate) fn from_description(task: TaskDescription, openml: &OpenML) -> Result<Self> {
         let mut source_data = None;
         let mut estimation_procedure = None;
+        let mut evaluation_measures = vec![];
 
-        for input_item in task_json["input"].as_array().unwrap() {
-            match input_item["name"].as_str() {
-                Some("source_data") => source_data = Some(DataSet::from_json(input_item)),
-                Some("estimation_procedure") => {
-                    estimation_procedure = Some(Box::new(FrozenSets::from_json(input_item)))
+        for input in &task.input {
+            match input {
+                TaskInput::SourceData { data_set } => {
+                    source_data = Some(DataSet::from_json(data_set, openml)?)
                 }
-                Some(_) => {}
-                None => panic!("/task/input/name is not a string"),
+                TaskInput::EstimationProcedure {
+                    estimation_procedure: procedure,
+                } => estimation_procedure = Some(procedure),
+                TaskInput::EvaluationMeasures {
+                    evaluation_measures: Some(measures),
+                } => evaluation_measures = measures.evaluation_measure.clone(),
+                _ => {}
             }
         }
 
-        SupervisedRegression {
-            id: task_json["task_id"].as_str().unwrap().to_owned(),
-            name: task_json["task_name"].as_str().unwrap().to_owned(),
-            source_data: source_data.unwrap(),
-            estimation_procedure: estimation_procedure.unwrap(),
+        let source_data = source_data.ok_or_else(|| missing_input("source data"))?;
+        let procedure_json =
+            estimation_procedure.ok_or_else(|| missing_input("estimation procedure"))?;
+        let estimation_procedure = EstimationProcedure::from_json(procedure_json)?;
+
+        Ok(SupervisedRegression {
+            splits: splits_from_json(openml, procedure_json, &estimation_procedure, &source_data)?,
+            id: task.task_id.0,
+            name: task.task_name,
+            tags: task.tag,
+            source_data,
+            estimation_procedure,
+            evaluation_measures,
+            missing_values: MissingValues::default(),
+            progress: None,
+            openml: openml.clone(),
+        })
+    }
+}
+
+/// error about a task without an input it needs
+fn missing_input(name: &str) -> Error {
+    Error::JsonFieldError {
+        path: "/task/input".to_owned(),
+        message: format!("no {}", name),
+    }
+}
+
+impl CostMatrix {
+    /// tasks without costs may omit the input, or give it an empty or null matrix
+    fn from_json(rows: Option<&[Vec<StringOrNumber>]>) -> Result<Self> {
+        let rows = match rows {
+            Some(rows) if !rows.is_empty() => rows,
+            _ => return Ok(CostMatrix::None),
+        };
+
+        let costs = rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(|(j, cost)| cost.parse(&format!("cost_matrix/{}/{}", i, j)))
+                    .collect()
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(CostMatrix::Costs(costs))
+    }
+}
+
+impl EstimationProcedure {
+    fn from_json(v: &EstimationProcedureDescription) -> Result<Self> {
+        let parameter = |name: &str| {
+            v.parameter
+                .iter()
+                .find(|p| p.name == name)
+                .and_then(|p| p.value.as_ref())
+        };
+        let parse = |name: &str, default: usize| {
+            parameter(name).map_or(Ok(default), |value| {
+                value.parse(&format!("estimation_procedure/{}", name))
+            })
+        };
+
+        Ok(EstimationProcedure {
+            id: v.id.as_ref().and_then(|id| id.0.parse().ok()),
+            kind: v.kind.as_str().into(),
+            n_repeats: parse("number_repeats", 1)?,
+            n_folds: parse("number_folds", 1)?,
+            percentage: parameter("percentage")
+                .map(|value| value.parse("estimation_procedure/percentage"))
+                .transpose()?,
+            stratified: parameter("stratified_sampling").map(|value| value.0.as_str())
+                == Some("true"),
+        })
+    }
+}
+
+fn splits_from_json(
+    openml: &OpenML,
+    v: &EstimationProcedureDescription,
+    procedure: &EstimationProcedure,
+    data: &DataSet,
+) -> Result<Box<dyn Procedure>> {
+    match (procedure.kind(), v.data_splits_url.as_deref()) {
+        (_, Some(url)) => {
+            frozen_sets_from_url(openml, url).context(url, || "fetching splits".to_owned())
+        }
+        (ProcedureType::CrossValidation, None) => {
+            Ok(Box::new(generate_cross_validation(procedure, data)))
         }
+        _ => Err(Error::JsonFieldError {
+            path: "estimation_procedure/data_splits_url".to_owned(),
+            message: "no splits file, and splits of this procedure cannot be generated".to_owned(),
+        }),
     }
 }
 
-impl FrozenSets {
-    fn from_json(item: &serde_json::Value) -> Self {
-        let v = &item["estimation_procedure"];
-        let typ = v["type"].as_str();
-        let splits = v["data_splits_url"].as_str();
+/// Generate cross-validation splits locally, for tasks that do not provide a splits file.
+fn generate_cross_validation(procedure: &EstimationProcedure, data: &DataSet) -> FrozenSets {
+    let n_folds = procedure.n_folds();
+    let n_repeats = procedure.n_repeats();
+
+    info!("Generating {}x{}-fold cross-validation", n_repeats, n_folds);
 
-        match (typ, splits) {
-            (_, Some(url)) => FrozenSets::from_url(url).unwrap(),
-            _ => unimplemented!(),
+    let folds = match data.nominal_target() {
+        Some(target) if procedure.is_stratified() => {
+            splits::stratified_k_fold(&target, n_folds, n_repeats, random_seed())
         }
+        _ => splits::k_fold(data.frame.n_rows(), n_folds, n_repeats, random_seed()),
+    };
+
+    FrozenSets { folds }
+}
+
+/// Load pre-defined splits. Files above the streaming threshold are streamed into a compact
+/// representation, unless they list a row twice in a fold; smaller ones are parsed into index
+/// vectors, which keep the rows in file order.
+///
+/// If the file cannot be parsed, the cached copy is removed and the file is downloaded once more.
+fn frozen_sets_from_url(openml: &OpenML, url: &str) -> Result<Box<dyn Procedure>> {
+    match load_splits(openml, url) {
+        Err(e @ Error::SplitsFormatError { .. }) => {
+            warn!(
+                "Failed to parse splits ({:?}); downloading {} again",
+                e, url
+            );
+            openml.remove_cached(url)?;
+            load_splits(openml, url)
+        }
+        result => result,
     }
+}
 
-    fn from_url(url: &str) -> Result<Self> {
-        let raw = get_cached(url)?;
-        let data: Vec<CrossValItem> = arff::from_str(&raw)?;
+fn load_splits(openml: &OpenML, url: &str) -> Result<Box<dyn Procedure>> {
+    let (file, size) = open_cached(openml, url)?;
+    let lenient = lenient_splits();
 
-        let mut folds = vec![];
-        for item in data {
-            if item.repeat >= folds.len() {
-                folds.resize(item.repeat + 1, vec![]);
-            }
-            let rep = &mut folds[item.repeat];
+    if size > splits_streaming_threshold() {
+        if let Some(sets) = CompactSets::from_reader(BufReader::new(file), lenient)? {
+            info!("Streamed {} bytes of splits into compact folds", size);
+            return Ok(Box::new(sets));
+        }
+        info!("Splits list a row twice in a fold; keeping the folds as index vectors");
+    }
 
-            if item.fold >= rep.len() {
-                rep.resize(item.fold + 1, Fold::new());
-            }
-            let fold = &mut rep[item.fold];
+    let (file, _) = open_cached(openml, url)?;
+    Ok(Box::new(FrozenSets::from_reader(
+        BufReader::new(file),
+        lenient,
+    )?))
+}
 
-            match item.purpose {
-                TrainTest::Train => fold.trainset.push(item.rowid),
-                TrainTest::Test => fold.testset.push(item.rowid),
-            }
+#[test]
+fn data_set_status() {
+    let status = |json: &str| {
+        let response: DataSetResponse = parse_response(json).unwrap();
+        check_status("61", &response.data_set_description)
+    };
+    let url = r#""url": "https://www.openml.org/data/v1/download/61/iris.arff""#;
+    assert!(status(&format!(
+        r#"{{"data_set_description": {{"status": "active", {}}}}}"#,
+        url
+    ))
+    .is_ok());
+    assert!(status(&format!(r#"{{"data_set_description": {{{}}}}}"#, url)).is_ok());
+    match status(&format!(
+        r#"{{"data_set_description": {{"status": "deactivated", {}}}}}"#,
+        url
+    )) {
+        Err(Error::DatasetUnavailable { id, status }) => {
+            assert_eq!((id.as_str(), status.as_str()), ("61", "deactivated"))
         }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(status(&format!(
+        r#"{{"data_set_description": {{"status": "in_preparation", {}}}}}"#,
+        url
+    ))
+    .is_err());
+}
+
+#[test]
+fn cost_matrix_from_json() {
+    assert_eq!(CostMatrix::from_json(None).unwrap(), CostMatrix::None);
+    assert_eq!(CostMatrix::from_json(Some(&[])).unwrap(), CostMatrix::None);
+
+    let rows: Vec<Vec<StringOrNumber>> = serde_json::from_str(r#"[["0", "1"], [5, 0]]"#).unwrap();
+    let costs = CostMatrix::from_json(Some(&rows)).unwrap();
+    assert_eq!(
+        costs,
+        CostMatrix::Costs(vec![vec![0.0, 1.0], vec![5.0, 0.0]])
+    );
+    assert_eq!(costs.cost(1, 0), 5.0);
 
-        Ok(FrozenSets { folds })
+    let rows: Vec<Vec<StringOrNumber>> = serde_json::from_str(r#"[["0", "x"]]"#).unwrap();
+    match CostMatrix::from_json(Some(&rows)) {
+        Err(Error::JsonFieldError { path, .. }) => assert_eq!(path, "cost_matrix/0/1"),
+        other => panic!("unexpected {:?}", other),
     }
 }
+
+#[test]
+fn estimation_procedure_from_json() {
+    let v: EstimationProcedureDescription = serde_json::from_str(
+        r#"{"id": "1", "type": "crossvalidation",
+            "parameter": [{"name": "number_repeats", "value": "1"},
+                          {"name": "number_folds", "value": "10"},
+                          {"name": "percentage"},
+                          {"name": "stratified_sampling", "value": "true"}]}"#,
+    )
+    .unwrap();
+
+    let procedure = EstimationProcedure::from_json(&v).unwrap();
+
+    assert_eq!(procedure.id(), Some(1));
+    assert_eq!(procedure.kind(), &ProcedureType::CrossValidation);
+    assert_eq!(procedure.n_repeats(), 1);
+    assert_eq!(procedure.n_folds(), 10);
+    assert_eq!(procedure.percentage(), None);
+    assert!(procedure.is_stratified());
+    let v: EstimationProcedureDescription = serde_json::from_str(
+        r#"{"type": "crossvalidation", "parameter": [{"name": "number_folds", "value": "ten"}]}"#,
+    )
+    .unwrap();
+    assert!(EstimationProcedure::from_json(&v).is_err());
+}
+
+#[test]
+fn small_splits_in_file_order() {
+    use std::{env, fs};
+
+    use super::web_access::cache_path;
+
+    let dir = env::temp_dir().join(format!("openml-test-{}-splits-order", std::process::id()));
+    let openml = OpenML::builder().cache_dir(&dir).build().offline(true);
+    let url = "https://www.openml.org/api_splits/get/9201/Task_9201_splits.arff";
+    fs::write(
+        cache_path(&openml, url).unwrap(),
+        "@RELATION splits\n\
+         @ATTRIBUTE type {TRAIN,TEST}\n\
+         @ATTRIBUTE rowid NUMERIC\n\
+         @ATTRIBUTE repeat NUMERIC\n\
+         @ATTRIBUTE fold NUMERIC\n\
+         @DATA\n\
+         TRAIN,2,0,0\n\
+         TRAIN,0,0,0\n\
+         TRAIN,2,0,0\n\
+         TEST,3,0,0\n\
+         TEST,1,0,0\n",
+    )
+    .unwrap();
+
+    let splits = load_splits(&openml, url).unwrap();
+    let folds: Vec<_> = splits
+        .iter()
+        .map(|(_, _, fold)| fold.into_owned())
+        .collect();
+    assert_eq!(folds.len(), 1);
+    assert_eq!(folds[0].trainset(), &[2, 0, 2]);
+    assert_eq!(folds[0].testset(), &[3, 1]);
+    fs::remove_dir_all(&dir).unwrap();
+}
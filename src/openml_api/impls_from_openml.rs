@@ -1,41 +1,173 @@
 //! implementations to load tasks from the OpenML API.
-use serde_json;
 
-use crate::error::Result;
-use crate::tasks::{SupervisedClassification, SupervisedRegression};
+use crate::error::{Context, Error, Result};
+use crate::tasks::{SupervisedClassification, SupervisedRegression, TaskKind};
 
-use super::api_types::GenericResponse;
+use super::api_types::{parse_response, TaskDescription, TaskResponse};
+use super::client::default_client;
+use super::prefetch::fetch_task_inputs;
 use super::web_access::get_cached;
-use super::Id;
+use super::{OpenML, TaskIdentifier};
 
-impl SupervisedClassification {
-    pub fn from_openml<'a, T: Id>(id: T) -> Result<Self> {
-        let url = format!("https://www.openml.org/api/v1/json/task/{}", id.as_string());
-        let raw_task = get_cached(&url)?;
-        let response: GenericResponse = serde_json::from_str(&raw_task)?;
+impl OpenML {
+    /// load a task of any supported type. Fails with `Error::JsonFieldError` if the type of the
+    /// task is not supported.
+    pub fn task<T: TaskIdentifier>(&self, id: T) -> Result<TaskKind> {
+        let url = self.api_url(&format!("task/{}", id.as_u32()?));
+        let load = || {
+            let task = fetch_description(self, &url)?;
+            task_kind(task, self)
+        };
+        load().context(&url, || format!("loading task {}", id.as_string()))
+    }
 
-        let task = response.look_up("/task").unwrap();
+    /// load a classification task
+    pub fn classification_task<T: TaskIdentifier>(
+        &self,
+        id: T,
+    ) -> Result<SupervisedClassification> {
+        let url = self.api_url(&format!("task/{}", id.as_u32()?));
+        let load = || {
+            let task = task_description(self, &url, "1")?;
+            SupervisedClassification::from_description(task, self)
+        };
+        load().context(&url, || format!("loading task {}", id.as_string()))
+    }
 
-        match response.look_up("/task/task_type_id").unwrap().as_str() {
-            Some("1") => Ok(SupervisedClassification::from_json(task)),
-            Some(id) => panic!("Wrong task type ID. Expected \"1\" but got \"{}\"", id),
-            None => panic!("Invalid task type ID"),
-        }
+    /// load a regression task
+    pub fn regression_task<T: TaskIdentifier>(&self, id: T) -> Result<SupervisedRegression> {
+        let url = self.api_url(&format!("task/{}", id.as_u32()?));
+        let load = || {
+            let task = task_description(self, &url, "2")?;
+            SupervisedRegression::from_description(task, self)
+        };
+        load().context(&url, || format!("loading task {}", id.as_string()))
+    }
+}
+
+impl SupervisedClassification {
+    /// load a task with the default client; see `OpenML::classification_task`
+    pub fn from_openml<T: TaskIdentifier>(id: T) -> Result<Self> {
+        default_client().classification_task(id)
     }
 }
 
 impl SupervisedRegression {
-    pub fn from_openml<'a, T: Id>(id: T) -> Result<Self> {
-        let url = format!("https://www.openml.org/api/v1/json/task/{}", id.as_string());
-        let raw_task = get_cached(&url)?;
-        let response: GenericResponse = serde_json::from_str(&raw_task)?;
+    /// load a task with the default client; see `OpenML::regression_task`
+    pub fn from_openml<T: TaskIdentifier>(id: T) -> Result<Self> {
+        default_client().regression_task(id)
+    }
+}
 
-        let task = response.look_up("/task").unwrap();
+impl TaskKind {
+    /// load a task of any supported type with the default client; see `OpenML::task`
+    pub fn from_openml<T: TaskIdentifier>(id: T) -> Result<Self> {
+        default_client().task(id)
+    }
+}
 
-        match response.look_up("/task/task_type_id").unwrap().as_str() {
-            Some("2") => Ok(SupervisedRegression::from_json(task)),
-            Some(id) => panic!("Wrong task type ID. Expected \"2\" but got \"{}\"", id),
-            None => panic!("Invalid task type ID"),
-        }
+/// build the task of the type given in its description. Fails with `Error::JsonFieldError` if the
+/// type is not supported.
+pub(crate) fn task_kind(task: TaskDescription, openml: &OpenML) -> Result<TaskKind> {
+    match task.task_type_id.0.as_str() {
+        "1" => Ok(SupervisedClassification::from_description(task, openml)?.into()),
+        "2" => Ok(SupervisedRegression::from_description(task, openml)?.into()),
+        other => Err(Error::JsonFieldError {
+            path: "/task/task_type_id".to_owned(),
+            message: format!("unsupported task type \"{}\"", other),
+        }),
     }
 }
+
+/// fetch the description of a task, and check that it has the expected type
+fn task_description(openml: &OpenML, url: &str, task_type: &str) -> Result<TaskDescription> {
+    let task = fetch_description(openml, url)?;
+    if task.task_type_id.0 != task_type {
+        return Err(Error::JsonFieldError {
+            path: "/task/task_type_id".to_owned(),
+            message: format!("expected task type \"{}\"", task_type),
+        });
+    }
+    Ok(task)
+}
+
+fn fetch_description(openml: &OpenML, url: &str) -> Result<TaskDescription> {
+    let raw_task = get_cached(openml, url)?;
+    fetch_task_inputs(openml, &raw_task);
+    Ok(parse_response::<TaskResponse>(&raw_task)?.task)
+}
+
+#[test]
+fn task_kinds() {
+    use std::{env, fs};
+
+    use super::web_access::cache_path;
+
+    let dir = env::temp_dir().join(format!("openml-test-{}-task-kinds", std::process::id()));
+    let openml = OpenML::builder().cache_dir(&dir).build().offline(true);
+    let cache = |url: &str, content: &str| {
+        fs::write(cache_path(&openml, url).unwrap(), content).unwrap();
+    };
+
+    let data_url = "https://www.openml.org/data/v1/download/9001/task_kinds.arff";
+    cache(
+        &openml.api_url("data/9001"),
+        &format!(
+            r#"{{"data_set_description": {{"status": "active", "url": "{}"}}}}"#,
+            data_url
+        ),
+    );
+    cache(
+        data_url,
+        "@relation task_kinds\n\
+         @attribute x numeric\n\
+         @attribute y numeric\n\
+         @attribute class {a,b}\n\
+         @data\n\
+         1,0.5,a\n2,1.5,b\n3,2.5,a\n4,3.5,b\n",
+    );
+    for &(id, task_type, target) in &[(9101, 1, "class"), (9102, 2, "y"), (9103, 5, "class")] {
+        cache(
+            &openml.api_url(&format!("task/{}", id)),
+            &format!(
+                r#"{{"task": {{"task_id": "{}", "task_name": "task {}", "task_type_id": "{}",
+                    "input": [
+                        {{"name": "source_data",
+                          "data_set": {{"data_set_id": "9001", "target_feature": "{}"}}}},
+                        {{"name": "estimation_procedure",
+                          "estimation_procedure": {{"type": "crossvalidation",
+                            "parameter": [{{"name": "number_folds", "value": "2"}}]}}}}
+                    ]}}}}"#,
+                id, id, task_type, target
+            ),
+        );
+    }
+
+    match openml.task(9101).unwrap() {
+        TaskKind::Classification(task) => assert_eq!(task.id(), "9101"),
+        other => panic!("unexpected {:?}", other),
+    }
+    match openml.task(9102).unwrap() {
+        TaskKind::Regression(task) => assert_eq!(task.id(), "9102"),
+        other => panic!("unexpected {:?}", other),
+    }
+    let message = |result: Result<()>| match result.as_ref().map_err(Error::root_cause) {
+        Err(Error::JsonFieldError { path, message }) => {
+            assert_eq!(path, "/task/task_type_id");
+            message.clone()
+        }
+        other => panic!("unexpected {:?}", other),
+    };
+    assert_eq!(
+        message(openml.task(9103).map(drop)),
+        "unsupported task type \"5\""
+    );
+
+    // the typed loaders check the type of the task
+    assert!(openml.classification_task(9101).is_ok());
+    assert_eq!(
+        message(openml.regression_task(9101).map(drop)),
+        "expected task type \"2\""
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,65 @@
+//! Coalescing of concurrent downloads
+//!
+//! Threads that miss the cache for the same URL at the same time would all download it. Instead,
+//! the first one holds a lock on the URL while it downloads, and the others wait for the lock and
+//! then find the file in the cache. If the download failed, the next waiting thread tries again.
+
+use std::collections::BTreeSet;
+use std::sync::{Condvar, Mutex};
+
+/// URLs that are being downloaded
+static ACTIVE: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// Signalled whenever a download finishes
+static FINISHED: Condvar = Condvar::new();
+
+/// A scoped lock on a URL for its download
+#[derive(Debug)]
+pub(crate) struct DownloadLock {
+    url: String,
+}
+
+impl DownloadLock {
+    /// acquire the lock on a URL, waiting while another thread holds it
+    pub(crate) fn new(url: &str) -> Self {
+        let mut active = ACTIVE.lock().unwrap();
+        while active.contains(url) {
+            active = FINISHED.wait(active).unwrap();
+        }
+        active.insert(url.to_owned());
+        DownloadLock {
+            url: url.to_owned(),
+        }
+    }
+}
+
+impl Drop for DownloadLock {
+    /// release the lock and wake up waiting threads
+    fn drop(&mut self) {
+        ACTIVE.lock().unwrap().remove(&self.url);
+        FINISHED.notify_all();
+    }
+}
+
+#[test]
+fn download_lock() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    let url = "https://example.org/download_lock";
+    let acquired = AtomicBool::new(false);
+    let lock = DownloadLock::new(url);
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            let _lock = DownloadLock::new(url);
+            acquired.store(true, Ordering::SeqCst);
+        });
+        // other URLs are not blocked
+        drop(DownloadLock::new("https://example.org/other"));
+        thread::sleep(Duration::from_millis(50));
+        assert!(!acquired.load(Ordering::SeqCst));
+        drop(lock);
+    });
+    assert!(acquired.load(Ordering::SeqCst));
+}
@@ -0,0 +1,322 @@
+//! Provenance of cached files
+//!
+//! Every downloaded file is accompanied by a small JSON file that records where and when it was
+//! downloaded. Keeping one record per file, instead of a single manifest, lets processes that
+//! share the cache download files concurrently without contending for the manifest.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use reqwest::blocking::RequestBuilder;
+use reqwest::header::{
+    HeaderMap, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+
+use crate::error::Result;
+
+use super::client::default_client;
+use super::web_access::cache_files;
+use super::OpenML;
+
+/// Suffix of the names of record files
+const RECORD_SUFFIX: &str = ".meta.json";
+
+/// The provenance of a file in the cache
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    url: String,
+    path: PathBuf,
+    fetched: SystemTime,
+    size: u64,
+    md5: String,
+    api_version: Option<String>,
+    #[serde(default)]
+    etag: Option<String>,
+    #[serde(default)]
+    last_modified: Option<String>,
+}
+
+impl CacheEntry {
+    /// get the URL the file was downloaded from
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// get the location of the file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// get the time of the download
+    pub fn fetched(&self) -> SystemTime {
+        self.fetched
+    }
+
+    /// get the size of the file on disk in bytes, which is smaller than the response if the file
+    /// is compressed
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// get the MD5 checksum of the response as a hexadecimal string
+    pub fn md5(&self) -> &str {
+        &self.md5
+    }
+
+    /// get the version of the REST API that served the file, e.g. `"v1"`, if the URL tells
+    pub fn api_version(&self) -> Option<&str> {
+        self.api_version.as_deref()
+    }
+
+    /// get the `ETag` header of the response, which identifies its version
+    pub fn etag(&self) -> Option<&str> {
+        self.etag.as_deref()
+    }
+
+    /// get the `Last-Modified` header of the response
+    pub fn last_modified(&self) -> Option<&str> {
+        self.last_modified.as_deref()
+    }
+}
+
+/// The headers of a response that allow asking the server whether it changed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Validators {
+    pub(crate) etag: Option<String>,
+    pub(crate) last_modified: Option<String>,
+}
+
+impl Validators {
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Self {
+        let get = |name| {
+            headers
+                .get(name)
+                .and_then(|v: &HeaderValue| v.to_str().ok())
+                .map(str::to_owned)
+        };
+        Validators {
+            etag: get(ETAG),
+            last_modified: get(LAST_MODIFIED),
+        }
+    }
+
+    /// add the headers of a conditional request, which the server answers with 304 Not Modified
+    /// if the response did not change
+    pub(crate) fn conditional(&self, mut request: RequestBuilder) -> RequestBuilder {
+        if let Some(ref etag) = self.etag {
+            request = request.header(IF_NONE_MATCH, etag.as_str());
+        }
+        if let Some(ref last_modified) = self.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified.as_str());
+        }
+        request
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+impl OpenML {
+    /// List the files in the cache with their provenance, ordered by URL. Files downloaded by
+    /// older versions of this crate have no records and are not listed.
+    ///
+    /// Together with `remove_cached` this allows pruning the cache, e.g. of files that were
+    /// fetched long ago:
+    ///
+    /// ```no_run
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// let openml = openml::OpenML::new();
+    /// let month = Duration::from_secs(30 * 24 * 60 * 60);
+    /// for entry in openml.cache_entries().unwrap() {
+    ///     if entry.fetched() + month < SystemTime::now() {
+    ///         openml.remove_cached(entry.url()).unwrap();
+    ///     }
+    /// }
+    /// ```
+    pub fn cache_entries(&self) -> Result<Vec<CacheEntry>> {
+        entries_in(&self.cache_dir()?)
+    }
+}
+
+/// List the files in the cache of the default client, like `OpenML::cache_entries`
+pub fn cache_entries() -> Result<Vec<CacheEntry>> {
+    default_client().cache_entries()
+}
+
+fn entries_in(dir: &Path) -> Result<Vec<CacheEntry>> {
+    let mut entries = vec![];
+    for path in cache_files(dir)? {
+        if !path.to_string_lossy().ends_with(RECORD_SUFFIX) {
+            continue;
+        }
+        // records are replaced as a whole, but may be left over from a removed file
+        match fs::read(&path).map(|data| serde_json::from_slice::<CacheEntry>(&data)) {
+            Ok(Ok(entry)) if entry.path.exists() => entries.push(entry),
+            _ => {}
+        }
+    }
+    entries.sort_by(|a, b| a.url.cmp(&b.url));
+    Ok(entries)
+}
+
+/// record the provenance of a file that was just downloaded from `url`
+pub(crate) fn record(
+    url: &str,
+    path: &Path,
+    md5: md5::Digest,
+    validators: Validators,
+) -> Result<()> {
+    write_record(&CacheEntry {
+        url: url.to_owned(),
+        path: path.to_owned(),
+        fetched: SystemTime::now(),
+        size: path.metadata()?.len(),
+        md5: format!("{:x}", md5),
+        api_version: api_version(url),
+        etag: validators.etag,
+        last_modified: validators.last_modified,
+    })
+}
+
+/// read the record of a file
+pub(crate) fn read_record(path: &Path) -> Option<CacheEntry> {
+    let data = fs::read(record_path(path)).ok()?;
+    serde_json::from_slice(&data).ok()
+}
+
+/// get the validators of the response a file holds, if the server sent any
+pub(crate) fn validators(path: &Path) -> Validators {
+    read_record(path)
+        .map(|entry| Validators {
+            etag: entry.etag,
+            last_modified: entry.last_modified,
+        })
+        .unwrap_or_default()
+}
+
+/// mark a file as fetched now, after the server confirmed that it did not change
+pub(crate) fn renew(path: &Path) -> Result<()> {
+    let now = SystemTime::now();
+    OpenOptions::new()
+        .write(true)
+        .open(path)?
+        .set_modified(now)?;
+    if let Some(mut entry) = read_record(path) {
+        entry.fetched = now;
+        write_record(&entry)?;
+    }
+    Ok(())
+}
+
+fn write_record(entry: &CacheEntry) -> Result<()> {
+    let record = record_path(&entry.path);
+    let mut tmp = record.as_os_str().to_owned();
+    tmp.push(format!(".{}.tmp", std::process::id()));
+    fs::write(&tmp, serde_json::to_vec(entry)?)?;
+    fs::rename(&tmp, &record)?;
+    Ok(())
+}
+
+/// remove the record of a file
+pub(crate) fn remove(path: &Path) -> Result<()> {
+    match fs::remove_file(record_path(path)) {
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => Ok(result?),
+    }
+}
+
+fn record_path(path: &Path) -> PathBuf {
+    let mut record = path.as_os_str().to_owned();
+    record.push(RECORD_SUFFIX);
+    record.into()
+}
+
+/// get the API version from a URL such as `https://www.openml.org/api/v1/json/task/59`
+fn api_version(url: &str) -> Option<String> {
+    let rest = &url[url.find("/api/")? + "/api/".len()..];
+    let version = rest.split('/').next()?;
+    if version.starts_with('v') {
+        Some(version.to_owned())
+    } else {
+        None
+    }
+}
+
+/// A writer that computes the MD5 checksum of everything written through it
+pub(crate) struct Md5Writer<W> {
+    inner: W,
+    context: md5::Context,
+}
+
+impl<W: Write> Md5Writer<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        Md5Writer {
+            inner,
+            context: md5::Context::new(),
+        }
+    }
+
+    pub(crate) fn digest(self) -> md5::Digest {
+        self.context.compute()
+    }
+}
+
+impl<W: Write> Write for Md5Writer<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.context.consume(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[test]
+fn records() {
+    let dir = std::env::temp_dir().join(format!("openml-test-{}-manifest", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let url = "https://www.openml.org/api/v1/json/task/59";
+    let path = dir.join("https_www.openml.org_api_v1_json_task_59");
+
+    let mut writer = Md5Writer::new(vec![]);
+    writer.write_all(b"{}").unwrap();
+    fs::write(&path, "{}").unwrap();
+    let mut headers = HeaderMap::new();
+    headers.insert(ETAG, HeaderValue::from_static("\"v1\""));
+    let sent = Validators::from_headers(&headers);
+    assert_eq!(sent.etag.as_deref(), Some("\"v1\""));
+    assert!(!sent.is_empty());
+    record(url, &path, writer.digest(), sent).unwrap();
+
+    let entries = entries_in(&dir).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].url(), url);
+    assert_eq!(entries[0].size(), 2);
+    assert_eq!(entries[0].md5(), format!("{:x}", md5::compute("{}")));
+    assert_eq!(entries[0].api_version(), Some("v1"));
+    assert_eq!(
+        api_version("https://www.openml.org/api_splits/get/59/Task_59_splits.arff"),
+        None
+    );
+
+    assert_eq!(entries[0].etag(), Some("\"v1\""));
+
+    let fetched = entries[0].fetched();
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    renew(&path).unwrap();
+    assert!(read_record(&path).unwrap().fetched() > fetched);
+    assert_eq!(validators(&path).etag.as_deref(), Some("\"v1\""));
+
+    // records of removed files are not listed
+    fs::remove_file(&path).unwrap();
+    assert!(entries_in(&dir).unwrap().is_empty());
+    remove(&path).unwrap();
+    remove(&path).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+}
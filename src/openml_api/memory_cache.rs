@@ -0,0 +1,191 @@
+//! In-memory cache of responses and parsed data sets
+//!
+//! Loading the same task twice in one process would otherwise read and parse the same files from
+//! the file cache again. The least recently used entries are evicted when the cache holds more
+//! entries or bytes than configured with `OpenMLBuilder::memory_cache_limits`.
+
+use std::sync::{Arc, Mutex};
+
+use crate::data_frame::DataFrame;
+
+/// A cached value
+#[derive(Debug, Clone)]
+pub(crate) enum Entry {
+    /// the text of a response
+    Response(Arc<String>),
+    /// a data set parsed from a response
+    Frame(Arc<DataFrame>),
+}
+
+impl Entry {
+    /// estimate the number of bytes the value occupies
+    fn size(&self) -> usize {
+        match self {
+            Entry::Response(text) => text.len(),
+            Entry::Frame(frame) => frame.heap_size(),
+        }
+    }
+}
+
+/// The in-memory cache of a client, which its clones share
+#[derive(Debug)]
+pub(crate) struct MemoryCache {
+    lru: Mutex<Lru>,
+    max_entries: usize,
+    max_bytes: usize,
+}
+
+impl MemoryCache {
+    /// create an empty cache that holds at most `max_entries` entries and `max_bytes` bytes
+    pub(crate) fn new(max_entries: usize, max_bytes: usize) -> Self {
+        MemoryCache {
+            lru: Mutex::new(Lru::new()),
+            max_entries,
+            max_bytes,
+        }
+    }
+
+    /// get a cached response
+    pub(crate) fn get_response(&self, url: &str) -> Option<Arc<String>> {
+        match self.lru.lock().unwrap().get(&key("response", url)) {
+            Some(Entry::Response(text)) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// keep a response in memory
+    pub(crate) fn insert_response(&self, url: &str, text: String) {
+        self.insert(key("response", url), Entry::Response(Arc::new(text)));
+    }
+
+    /// get a data set that was parsed from the response of a URL
+    pub(crate) fn get_frame(&self, url: &str) -> Option<Arc<DataFrame>> {
+        match self.lru.lock().unwrap().get(&key("frame", url)) {
+            Some(Entry::Frame(frame)) => Some(frame),
+            _ => None,
+        }
+    }
+
+    /// keep a data set that was parsed from the response of a URL in memory
+    pub(crate) fn insert_frame(&self, url: &str, frame: DataFrame) {
+        self.insert(key("frame", url), Entry::Frame(Arc::new(frame)));
+    }
+
+    fn insert(&self, key: String, value: Entry) {
+        self.lru
+            .lock()
+            .unwrap()
+            .insert(key, value, self.max_entries, self.max_bytes);
+    }
+
+    /// forget the response of a URL and everything parsed from it
+    pub(crate) fn remove(&self, url: &str) {
+        let mut lru = self.lru.lock().unwrap();
+        lru.remove(&key("response", url));
+        lru.remove(&key("frame", url));
+    }
+
+    /// forget everything
+    pub(crate) fn clear(&self) {
+        *self.lru.lock().unwrap() = Lru::new();
+    }
+}
+
+fn key(kind: &str, url: &str) -> String {
+    format!("{} {}", kind, url)
+}
+
+/// Entries ordered from least to most recently used, with their sizes
+#[derive(Debug)]
+struct Lru {
+    entries: Vec<(String, Entry, usize)>,
+    bytes: usize,
+}
+
+impl Lru {
+    fn new() -> Self {
+        Lru {
+            entries: Vec::new(),
+            bytes: 0,
+        }
+    }
+
+    fn get(&mut self, key: &str) -> Option<Entry> {
+        let i = self.entries.iter().position(|(k, _, _)| k == key)?;
+        let entry = self.entries.remove(i);
+        let value = entry.1.clone();
+        self.entries.push(entry);
+        Some(value)
+    }
+
+    /// insert an entry and evict the least recently used entries until the limits are met.
+    /// Values larger than `max_bytes` are not inserted.
+    fn insert(&mut self, key: String, value: Entry, max_entries: usize, max_bytes: usize) {
+        self.remove(&key);
+        let size = value.size();
+        if max_entries == 0 || size > max_bytes {
+            return;
+        }
+        while self.entries.len() >= max_entries || self.bytes + size > max_bytes {
+            let (_, _, evicted) = self.entries.remove(0);
+            self.bytes -= evicted;
+        }
+        self.bytes += size;
+        self.entries.push((key, value, size));
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(i) = self.entries.iter().position(|(k, _, _)| k == key) {
+            let (_, _, size) = self.entries.remove(i);
+            self.bytes -= size;
+        }
+    }
+}
+
+#[test]
+fn least_recently_used() {
+    let response = |s: &str| Entry::Response(Arc::new(s.to_owned()));
+    let keys = |lru: &Lru| {
+        lru.entries
+            .iter()
+            .map(|(k, _, _)| k.as_str())
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+
+    let mut lru = Lru::new();
+    lru.insert("a".to_owned(), response("1"), 3, 100);
+    lru.insert("b".to_owned(), response("22"), 3, 100);
+    lru.insert("c".to_owned(), response("333"), 3, 100);
+    assert!(lru.get("a").is_some());
+    lru.insert("d".to_owned(), response("4444"), 3, 100);
+    assert_eq!(keys(&lru), "c,a,d");
+    assert_eq!(lru.bytes, 8);
+
+    // evict by size
+    lru.insert("e".to_owned(), response("55555"), 3, 10);
+    assert_eq!(keys(&lru), "a,d,e");
+    // too large to be cached at all
+    lru.insert("f".to_owned(), response(&"6".repeat(11)), 3, 10);
+    assert_eq!(keys(&lru), "a,d,e");
+
+    lru.remove("d");
+    assert!(lru.get("d").is_none());
+    assert_eq!(lru.bytes, 6);
+}
+
+#[test]
+fn limits() {
+    let cache = MemoryCache::new(1, 100);
+    cache.insert_response("a", "1".to_owned());
+    cache.insert_response("b", "2".to_owned());
+    assert!(cache.get_response("a").is_none());
+    assert_eq!(cache.get_response("b").unwrap().as_str(), "2");
+
+    cache.clear();
+    assert!(cache.get_response("b").is_none());
+
+    let disabled = MemoryCache::new(0, 100);
+    disabled.insert_response("a", "1".to_owned());
+    assert!(disabled.get_response("a").is_none());
+}
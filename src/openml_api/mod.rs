@@ -1,50 +1,240 @@
 //! Cached access to the OpenML REST API
 
+mod adaptive_limit;
 mod api_types;
+mod cache_control;
+mod client;
 mod file_lock;
+mod ids;
+mod impls_from_arff;
 mod impls_from_json;
 mod impls_from_openml;
+mod in_flight;
+mod manifest;
+mod memory_cache;
+mod parsed_cache;
+mod prefetch;
+mod retry;
+mod stats;
+mod upload;
+mod verify;
 mod web_access;
 
-use std::borrow::Cow;
+pub use self::cache_control::{clear_cache, remove_cached_data_set};
+pub use self::client::{OpenML, OpenMLBuilder};
+pub use self::ids::{
+    DataSetId, DataSetIdentifier, FlowId, FlowIdentifier, Id, RunId, RunIdentifier, TaskId,
+    TaskIdentifier,
+};
+pub use self::manifest::{cache_entries, CacheEntry};
+pub use self::prefetch::{prefetch_suite, prefetch_tasks};
+pub use self::retry::RetryPolicy;
+pub use self::stats::{cache_stats, reset_cache_stats, CacheStats};
+pub(crate) use self::upload::upload_run;
+pub use self::verify::verify_run;
+pub use self::web_access::{cache_dir, download_all, remove_cached};
 
-pub trait Id {
-    fn as_string(&self) -> Cow<str>;
-    fn as_u32(&self) -> u32;
+#[cfg(feature = "mmap")]
+pub(crate) use self::web_access::cache_path;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::error::Result;
+use crate::runs::UploadOptions;
+
+use self::client::{configure_default_client, try_configure_default_client};
+
+/// Split files larger than this are parsed in streaming fashion
+static SPLITS_STREAMING_THRESHOLD: AtomicU64 = AtomicU64::new(64 * 1024 * 1024);
+
+/// Set the size (in bytes) above which split files are parsed line by line into a compact bit set
+/// representation, instead of being deserialized in one piece. The default is 64 MiB.
+///
+/// Large tasks (e.g. 10 times repeated 10-fold cross-validation on big data sets) come with split
+/// files of several gigabytes that would otherwise exhaust memory.
+pub fn set_splits_streaming_threshold(bytes: u64) {
+    SPLITS_STREAMING_THRESHOLD.store(bytes, Ordering::Relaxed);
 }
 
-impl Id for String {
-    #[inline(always)]
-    fn as_string(&self) -> Cow<str> {
-        Cow::from(self.as_str())
-    }
+pub(crate) fn splits_streaming_threshold() -> u64 {
+    SPLITS_STREAMING_THRESHOLD.load(Ordering::Relaxed)
+}
 
-    #[inline(always)]
-    fn as_u32(&self) -> u32 {
-        self.parse().unwrap()
-    }
+/// Seed for the splits of tasks that do not come with pre-defined splits
+static RANDOM_SEED: AtomicU64 = AtomicU64::new(0);
+
+/// Set the seed used to generate the splits of tasks that have no pre-defined splits. The default
+/// is 0, so that the same task always gets the same splits.
+///
+/// Together with the `*_with_rng` functions in `splits`, which take a caller-provided random
+/// number generator, this makes a whole benchmark reproducible from a single seed.
+pub fn set_random_seed(seed: u64) {
+    RANDOM_SEED.store(seed, Ordering::Relaxed);
 }
 
-impl<'a> Id for &'a str {
-    #[inline(always)]
-    fn as_string(&self) -> Cow<str> {
-        Cow::from(*self)
-    }
+pub(crate) fn random_seed() -> u64 {
+    RANDOM_SEED.load(Ordering::Relaxed)
+}
 
-    #[inline(always)]
-    fn as_u32(&self) -> u32 {
-        self.parse().unwrap()
-    }
+/// Keep the folds parsed before a malformed line in a split file
+static LENIENT_SPLITS: AtomicBool = AtomicBool::new(false);
+
+/// Choose whether malformed split files are rejected (the default) or the folds parsed before the
+/// first malformed line are used, with a warning.
+///
+/// Either way a malformed file is downloaded a second time before giving up, in case the cached
+/// copy is truncated or corrupt. Errors report the line, repeat and fold at which parsing failed.
+pub fn set_lenient_splits_parsing(lenient: bool) {
+    LENIENT_SPLITS.store(lenient, Ordering::Relaxed);
 }
 
-impl Id for u32 {
-    #[inline(always)]
-    fn as_string(&self) -> Cow<str> {
-        Cow::from(format!("{}", self))
+pub(crate) fn lenient_splits() -> bool {
+    LENIENT_SPLITS.load(Ordering::Relaxed)
+}
+
+/// Keep parsed data sets in a binary cache
+static BINARY_CACHE: AtomicBool = AtomicBool::new(true);
+
+/// Choose whether parsed data sets are kept in a binary cache next to the downloaded ARFF files
+/// (the default). Loading the binary file skips parsing the ARFF file, which takes most of the
+/// time when a task is loaded a second time. The binary file is ignored and replaced if the ARFF
+/// file it was parsed from changed.
+pub fn set_binary_cache(enabled: bool) {
+    BINARY_CACHE.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn binary_cache() -> bool {
+    BINARY_CACHE.load(Ordering::Relaxed)
+}
+
+/// Set the size of the in-memory cache of the default client, as the maximum number of entries
+/// and the maximum number of bytes; see `OpenMLBuilder::memory_cache_limits`. Changing the limits
+/// empties the cache.
+pub fn set_memory_cache_limits(max_entries: usize, max_bytes: usize) {
+    configure_default_client(|builder| builder.memory_cache_limits(max_entries, max_bytes));
+}
+
+/// Compress cached responses
+static COMPRESS_CACHE: AtomicBool = AtomicBool::new(true);
+
+/// Choose whether downloaded descriptions and ARFF data sets are stored gzip-compressed in the
+/// cache (the default), which makes text-heavy data sets take several times less disk space.
+/// Compressed and uncompressed files are both read, whatever the setting.
+///
+/// Split files and Parquet files are always stored as they are, because they are read from disk
+/// in a streaming fashion.
+pub fn set_cache_compression(compress: bool) {
+    COMPRESS_CACHE.store(compress, Ordering::Relaxed);
+}
+
+pub(crate) fn compress_cache() -> bool {
+    COMPRESS_CACHE.load(Ordering::Relaxed)
+}
+
+/// Choose whether the default client requests responses gzip-compressed (the default); see
+/// `OpenMLBuilder::transfer_compression`.
+pub fn set_transfer_compression(compress: bool) {
+    configure_default_client(|builder| builder.transfer_compression(compress));
+}
+
+/// Set the tags and the visibility of all flows and runs that the default client uploads; see
+/// `OpenMLBuilder::upload_options`. Fails with `Error::InvalidTag` if the server would reject a
+/// tag.
+pub fn set_upload_options(options: UploadOptions) -> Result<()> {
+    try_configure_default_client(|builder| builder.upload_options(options))
+}
+
+/// Set the name and version of the tool that uses this crate, which the default client sends in
+/// the `User-Agent` of all requests; see `OpenMLBuilder::user_agent`.
+///
+/// ```
+/// openml::set_user_agent("mybench/1.2").unwrap();
+/// ```
+pub fn set_user_agent<S: Into<String>>(agent: S) -> Result<()> {
+    try_configure_default_client(|builder| builder.user_agent(agent))
+}
+
+/// Set headers that the default client sends with every request, e.g. the credentials an
+/// institutional proxy requires; see `OpenMLBuilder::extra_headers`.
+///
+/// ```
+/// openml::set_extra_headers(&[("X-Lab", "ml-group")]).unwrap();
+/// assert!(openml::set_extra_headers(&[("no spaces", "x")]).is_err());
+/// ```
+pub fn set_extra_headers(headers: &[(&str, &str)]) -> Result<()> {
+    try_configure_default_client(|builder| builder.extra_headers(headers))
+}
+
+/// Use a pre-populated, read-only cache directory for the default client; see
+/// `OpenMLBuilder::shared_cache`. Pass `None` to stop using a shared directory.
+pub fn set_shared_cache(dir: Option<PathBuf>, fall_through: bool) {
+    configure_default_client(|builder| match dir {
+        Some(dir) => builder.shared_cache(dir, fall_through),
+        None => builder.without_shared_cache(),
+    });
+}
+
+/// The kinds of resources that are downloaded from OpenML, for setting their cache expiry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceKind {
+    /// task descriptions
+    Task,
+    /// data set descriptions
+    DataSetDescription,
+    /// data set files in ARFF or Parquet format
+    DataSetFile,
+    /// files with the splits of a task
+    Splits,
+}
+
+impl ResourceKind {
+    /// guess the kind of resource from its URL
+    pub(crate) fn of_url(url: &str) -> Option<Self> {
+        if url.contains("/json/task/") {
+            Some(ResourceKind::Task)
+        } else if url.contains("/json/data/") {
+            Some(ResourceKind::DataSetDescription)
+        } else if url.contains("/api_splits/") {
+            Some(ResourceKind::Splits)
+        } else if url.contains("/data/v1/download/") || url.ends_with(".pq") {
+            Some(ResourceKind::DataSetFile)
+        } else {
+            None
+        }
     }
 
-    #[inline(always)]
-    fn as_u32(&self) -> u32 {
-        *self
+    fn index(self) -> usize {
+        self as usize
     }
 }
+
+/// Set how long the default client uses cached resources of a kind before they are downloaded
+/// again, or `None` to keep them forever; see `OpenMLBuilder::cache_ttl`.
+///
+/// ```
+/// use std::time::Duration;
+/// use openml::{set_cache_ttl, ResourceKind};
+///
+/// set_cache_ttl(ResourceKind::Task, Some(Duration::from_secs(24 * 60 * 60)));
+/// ```
+pub fn set_cache_ttl(kind: ResourceKind, ttl: Option<Duration>) {
+    configure_default_client(|builder| builder.cache_ttl(kind, ttl));
+}
+
+/// Download data sets in Parquet instead of ARFF format
+#[cfg(feature = "parquet")]
+static PREFER_PARQUET: AtomicBool = AtomicBool::new(false);
+
+/// Choose whether data sets are downloaded in Parquet format, if the server offers it, instead of
+/// ARFF. Parquet files are compressed and typed, so they are much smaller and faster to read for
+/// large data sets. If the Parquet file cannot be loaded, the ARFF file is used instead.
+#[cfg(feature = "parquet")]
+pub fn set_prefer_parquet(prefer: bool) {
+    PREFER_PARQUET.store(prefer, Ordering::Relaxed);
+}
+
+#[cfg(feature = "parquet")]
+pub(crate) fn prefer_parquet() -> bool {
+    PREFER_PARQUET.load(Ordering::Relaxed)
+}
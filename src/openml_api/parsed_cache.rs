@@ -0,0 +1,115 @@
+//! Binary cache of parsed data sets
+//!
+//! Parsing a large ARFF file takes much longer than reading the same data in binary form, so
+//! parsed data sets are stored next to the cached ARFF files. Every binary file records the size
+//! and CRC-32 checksum of the ARFF text it was parsed from, and is only used if they match.
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use crate::data_frame::DataFrame;
+use crate::error::Result;
+
+use super::web_access::cache_path;
+use super::{binary_cache, OpenML};
+
+/// Version of the binary format; files written in another version are ignored
+const FORMAT_VERSION: u32 = 1;
+
+/// Identifies the ARFF text a binary file was parsed from
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Header {
+    version: u32,
+    source_len: u64,
+    source_crc: u32,
+}
+
+impl Header {
+    fn new(source: &str) -> Self {
+        Header {
+            version: FORMAT_VERSION,
+            source_len: source.len() as u64,
+            source_crc: crc32fast::hash(source.as_bytes()),
+        }
+    }
+}
+
+/// parse the ARFF text downloaded from `url`, or load it from the binary cache if it was parsed
+/// before
+pub(crate) fn parse_cached(openml: &OpenML, url: &str, source: &str) -> Result<DataFrame> {
+    if !binary_cache() {
+        return DataFrame::parse_arff(source);
+    }
+
+    let path = binary_path(openml, url)?;
+    let header = Header::new(source);
+    if let Some(frame) = read(&path, &header) {
+        info!("Loading parsed {}", url);
+        return Ok(frame);
+    }
+
+    let frame = DataFrame::parse_arff(source)?;
+    if let Err(e) = write(&path, &header, &frame) {
+        warn!("Could not write binary cache {:?}: {:?}", path, e);
+    }
+    Ok(frame)
+}
+
+/// Location of the binary cache file for a URL
+pub(crate) fn binary_path(openml: &OpenML, url: &str) -> Result<PathBuf> {
+    let mut path = cache_path(openml, url)?.into_os_string();
+    path.push(".bin");
+    Ok(path.into())
+}
+
+/// read a binary file if it exists and was parsed from the expected source
+fn read(path: &Path, expected: &Header) -> Option<DataFrame> {
+    let mut reader = BufReader::new(File::open(path).ok()?);
+    let header: Header = bincode::deserialize_from(&mut reader).ok()?;
+    if header != *expected {
+        return None;
+    }
+    bincode::deserialize_from(reader).ok()
+}
+
+/// write a binary file; it is written under a temporary name first, so that readers never see a
+/// partially written file
+fn write(path: &Path, header: &Header, frame: &DataFrame) -> Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".{}.tmp", std::process::id()));
+
+    let result = File::create(&tmp)
+        .map_err(Into::into)
+        .and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            bincode::serialize_into(&mut writer, header)?;
+            bincode::serialize_into(&mut writer, frame)?;
+            writer.into_inner().map_err(|e| e.into_error())?;
+            Ok(())
+        })
+        .and_then(|()| Ok(fs::rename(&tmp, path)?));
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+#[test]
+fn binary_cache_roundtrip() {
+    let source = "@RELATION test\n\
+                  @ATTRIBUTE x NUMERIC\n\
+                  @ATTRIBUTE when DATE\n\
+                  @ATTRIBUTE class {a,b}\n\
+                  @DATA\n\
+                  1.5,2001-02-03T04:05:06,b\n\
+                  ?,?,a\n";
+    let path = std::env::temp_dir().join(format!("openml-test-{}.bin", std::process::id()));
+    let frame = DataFrame::parse_arff(source).unwrap();
+
+    write(&path, &Header::new(source), &frame).unwrap();
+    assert_eq!(read(&path, &Header::new(source)), Some(frame));
+    assert_eq!(read(&path, &Header::new("@RELATION changed")), None);
+    fs::remove_file(&path).unwrap();
+}
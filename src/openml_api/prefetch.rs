@@ -0,0 +1,172 @@
+//! Downloading everything that tasks need ahead of time
+
+use std::thread;
+
+use crate::error::Result;
+
+use super::api_types::{parse_response, DataSetResponse, StudyResponse, TaskInput, TaskResponse};
+use super::client::default_client;
+use super::web_access::get_cached;
+use super::{Id, OpenML, TaskIdentifier};
+
+impl OpenML {
+    /// Download the descriptions, data sets and splits of tasks into the cache, with parallel
+    /// requests. Running the tasks afterwards needs no network access, so that a benchmark can
+    /// run offline (see `OpenMLBuilder::offline`) and its timing does not depend on the network.
+    ///
+    /// All downloads are attempted; the first error is returned.
+    ///
+    /// ```no_run
+    /// use openml::OpenML;
+    ///
+    /// OpenML::new().prefetch_tasks(&[59, 61, 145677]).unwrap();
+    /// let offline = OpenML::builder().offline(true).build();
+    /// ```
+    pub fn prefetch_tasks<T: TaskIdentifier>(&self, ids: &[T]) -> Result<()> {
+        let task_urls = ids
+            .iter()
+            .map(|id| Ok(self.api_url(&format!("task/{}", id.as_u32()?))))
+            .collect::<Result<Vec<String>>>()?;
+        let mut result = first_error(self.download_all(&as_strs(&task_urls)));
+
+        let mut inputs = vec![];
+        for url in &task_urls {
+            match get_cached(self, url).and_then(|task| task_inputs(self, &task)) {
+                Ok(mut urls) => inputs.append(&mut urls),
+                Err(e) => result = result.and(Err(e)),
+            }
+        }
+        result = result.and(first_error(self.download_all(&as_strs(&inputs))));
+
+        let mut files = vec![];
+        for url in inputs.iter().filter(|url| url.contains("/json/data/")) {
+            match get_cached(self, url).and_then(|info| data_set_files(&info)) {
+                Ok(mut urls) => files.append(&mut urls),
+                Err(e) => result = result.and(Err(e)),
+            }
+        }
+        result.and(first_error(self.download_all(&as_strs(&files))))
+    }
+
+    /// Download everything the tasks of a benchmark suite (or study) need, like
+    /// `prefetch_tasks`. The suite is identified by its id or its alias, e.g. `"OpenML-CC18"`.
+    pub fn prefetch_suite<T: Id>(&self, id: T) -> Result<()> {
+        let url = self.api_url(&format!("study/{}", id.as_string()));
+        let suite: StudyResponse = parse_response(&get_cached(self, &url)?)?;
+        let task_ids: Vec<String> = suite
+            .study
+            .tasks
+            .map(|tasks| tasks.task_id.into_iter().map(|id| id.0).collect())
+            .unwrap_or_default();
+        self.prefetch_tasks(&task_ids)
+    }
+}
+
+/// Download everything that tasks need into the cache of the default client, like
+/// `OpenML::prefetch_tasks`
+pub fn prefetch_tasks<T: TaskIdentifier>(ids: &[T]) -> Result<()> {
+    default_client().prefetch_tasks(ids)
+}
+
+/// Download everything that the tasks of a benchmark suite need into the cache of the default
+/// client, like `OpenML::prefetch_suite`
+pub fn prefetch_suite<T: Id>(id: T) -> Result<()> {
+    default_client().prefetch_suite(id)
+}
+
+/// Download the inputs of a task into the cache concurrently, before the task is loaded: the
+/// splits are downloaded while the data set description and then the data set are. Errors are
+/// ignored, because loading the task reports them.
+pub(crate) fn fetch_task_inputs(openml: &OpenML, task: &str) {
+    let inputs = match task_inputs(openml, task) {
+        Ok(inputs) => inputs,
+        Err(_) => return,
+    };
+    let (descriptions, others): (Vec<String>, Vec<String>) = inputs
+        .into_iter()
+        .partition(|url| url.contains("/json/data/"));
+
+    thread::scope(|scope| {
+        scope.spawn(|| openml.download_all(&as_strs(&others)));
+
+        openml.download_all(&as_strs(&descriptions));
+        let files: Vec<String> = descriptions
+            .iter()
+            .filter_map(|url| {
+                get_cached(openml, url)
+                    .and_then(|info| data_set_files(&info))
+                    .ok()
+            })
+            .flatten()
+            .collect();
+        openml.download_all(&as_strs(&files));
+    });
+}
+
+/// get the URLs of the data set descriptions and split files of a task
+fn task_inputs(openml: &OpenML, task: &str) -> Result<Vec<String>> {
+    let task = parse_response::<TaskResponse>(task)?.task;
+
+    let mut urls = vec![];
+    for input in task.input {
+        match input {
+            TaskInput::SourceData { data_set } => {
+                urls.push(openml.api_url(&format!("data/{}", data_set.data_set_id)))
+            }
+            TaskInput::EstimationProcedure {
+                estimation_procedure,
+            } => urls.extend(estimation_procedure.data_splits_url),
+            _ => {}
+        }
+    }
+    Ok(urls)
+}
+
+/// get the URLs of the files of a data set, as they would be loaded
+fn data_set_files(info: &str) -> Result<Vec<String>> {
+    let description = parse_response::<DataSetResponse>(info)?.data_set_description;
+
+    #[cfg(feature = "parquet")]
+    {
+        if let (true, Some(parquet_url)) = (super::prefer_parquet(), description.parquet_url) {
+            return Ok(vec![parquet_url]);
+        }
+    }
+    Ok(vec![description.url])
+}
+
+fn as_strs(urls: &[String]) -> Vec<&str> {
+    urls.iter().map(String::as_str).collect()
+}
+
+fn first_error(results: Vec<Result<()>>) -> Result<()> {
+    results.into_iter().collect()
+}
+
+#[test]
+fn prefetched_urls() {
+    let task = r#"{"task": {"task_id": "59", "task_name": "Task 59: iris", "task_type_id": "1",
+        "input": [
+        {"name": "source_data", "data_set": {"data_set_id": "61", "target_feature": "class"}},
+        {"name": "estimation_procedure", "estimation_procedure": {
+            "id": "1", "type": "crossvalidation",
+            "data_splits_url": "https://www.openml.org/api_splits/get/59/Task_59_splits.arff"}},
+        {"name": "evaluation_measures", "evaluation_measures": []}
+    ]}}"#;
+    let openml = OpenML::new();
+    assert_eq!(
+        task_inputs(&openml, task).unwrap(),
+        vec![
+            "https://www.openml.org/api/v1/json/data/61",
+            "https://www.openml.org/api_splits/get/59/Task_59_splits.arff",
+        ]
+    );
+
+    let info = r#"{"data_set_description": {"id": "61",
+        "url": "https://www.openml.org/data/v1/download/61/iris.arff"}}"#;
+    assert_eq!(
+        data_set_files(info).unwrap(),
+        vec!["https://www.openml.org/data/v1/download/61/iris.arff"]
+    );
+    assert!(task_inputs(&openml, "not json").is_err());
+}
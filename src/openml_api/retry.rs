@@ -0,0 +1,111 @@
+//! Retries of failed requests
+//!
+//! OpenML servers intermittently fail with 5xx responses or reset connections. Such requests are
+//! sent again after a delay that doubles with every attempt.
+
+use std::thread;
+use std::time::Duration;
+
+use reqwest::blocking::{RequestBuilder, Response};
+
+use crate::error::Error;
+
+/// How requests that failed with a connection error or a retryable HTTP status are repeated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// number of attempts including the first one; `1` disables retries
+    pub max_attempts: u32,
+    /// delay before the first retry; it doubles with every further retry
+    pub initial_backoff: Duration,
+    /// upper limit of the delay between attempts
+    pub max_backoff: Duration,
+    /// HTTP status codes of responses that are retried
+    pub retry_statuses: Vec<u16>,
+}
+
+impl Default for RetryPolicy {
+    /// three attempts, half a second apart at first, for 429 (too many requests) and the 5xx
+    /// statuses of overloaded or unavailable servers
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            retry_statuses: vec![429, 500, 502, 503, 504],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// a policy that sends every request only once
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// get the delay before the `retry`th retry, counting from 1
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.initial_backoff
+            .checked_mul(factor)
+            .map_or(self.max_backoff, |delay| delay.min(self.max_backoff))
+    }
+
+    /// check whether a response with the given status is retried
+    pub fn retries_status(&self, status: u16) -> bool {
+        self.retry_statuses.contains(&status)
+    }
+
+    /// check whether a request that failed with `error` is retried: connection errors and
+    /// retryable statuses are
+    pub(crate) fn retries_error(&self, error: &Error) -> bool {
+        match error {
+            Error::HttpsError(e) => !e.is_redirect(),
+            e => e.status().is_some_and(|status| self.retries_status(status)),
+        }
+    }
+}
+
+/// Send the request built by `request` according to `policy`. Once all attempts are used up, the
+/// response or error of the last attempt is returned.
+pub(crate) fn send<F>(url: &str, policy: &RetryPolicy, request: F) -> reqwest::Result<Response>
+where
+    F: Fn() -> RequestBuilder,
+{
+    let mut attempt = 1;
+    loop {
+        let result = request().send();
+        let retry = match result {
+            Ok(ref response) => policy.retries_status(response.status().as_u16()),
+            Err(ref e) => !e.is_redirect(),
+        };
+        if !retry || attempt >= policy.max_attempts {
+            return result;
+        }
+        let delay = policy.backoff(attempt);
+        warn!(
+            "Request for {} failed (attempt {}); retrying in {:?}",
+            url, attempt, delay
+        );
+        thread::sleep(delay);
+        attempt += 1;
+    }
+}
+
+#[test]
+fn exponential_backoff() {
+    let policy = RetryPolicy {
+        initial_backoff: Duration::from_millis(100),
+        max_backoff: Duration::from_millis(350),
+        ..RetryPolicy::default()
+    };
+    assert_eq!(policy.backoff(1), Duration::from_millis(100));
+    assert_eq!(policy.backoff(2), Duration::from_millis(200));
+    assert_eq!(policy.backoff(3), Duration::from_millis(350));
+    assert_eq!(policy.backoff(100), Duration::from_millis(350));
+    assert!(policy.retries_status(503));
+    assert!(!policy.retries_status(404));
+    assert_eq!(RetryPolicy::none().max_attempts, 1);
+}
@@ -0,0 +1,125 @@
+//! Counters of cache hits and downloads
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::client::default_client;
+use super::OpenML;
+
+/// Statistics of the cache of a client since it was built or since the last `reset_cache_stats`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    hits: u64,
+    misses: u64,
+    bytes_downloaded: u64,
+    bytes_from_cache: u64,
+}
+
+impl CacheStats {
+    /// get the number of queries answered from the cache, in memory or on disk
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// get the number of queries that needed a download
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// get the number of bytes downloaded
+    pub fn bytes_downloaded(&self) -> u64 {
+        self.bytes_downloaded
+    }
+
+    /// get the number of bytes of the responses answered from the cache
+    pub fn bytes_from_cache(&self) -> u64 {
+        self.bytes_from_cache
+    }
+}
+
+/// The counters behind the `CacheStats` of a client, which its clones share
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    bytes_downloaded: AtomicU64,
+    bytes_from_cache: AtomicU64,
+}
+
+impl Counters {
+    /// count a query answered from the cache with a response of `bytes` bytes
+    pub(crate) fn hit(&self, bytes: u64) {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        self.bytes_from_cache.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// count a download of `bytes` bytes
+    pub(crate) fn miss(&self, bytes: u64) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        self.bytes_downloaded.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    fn get(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            bytes_downloaded: self.bytes_downloaded.load(Ordering::Relaxed),
+            bytes_from_cache: self.bytes_from_cache.load(Ordering::Relaxed),
+        }
+    }
+
+    fn reset(&self) {
+        for counter in &[
+            &self.hits,
+            &self.misses,
+            &self.bytes_downloaded,
+            &self.bytes_from_cache,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+impl OpenML {
+    /// Get the statistics of the cache of this client and its clones, e.g. to report how much
+    /// network traffic a benchmark caused
+    pub fn cache_stats(&self) -> CacheStats {
+        self.stats().get()
+    }
+
+    /// Set all statistics of the cache of this client and its clones to zero
+    pub fn reset_cache_stats(&self) {
+        self.stats().reset()
+    }
+}
+
+/// Get the statistics of the cache of the default client, like `OpenML::cache_stats`
+pub fn cache_stats() -> CacheStats {
+    default_client().cache_stats()
+}
+
+/// Set all statistics of the cache of the default client to zero, like
+/// `OpenML::reset_cache_stats`
+pub fn reset_cache_stats() {
+    default_client().reset_cache_stats()
+}
+
+#[test]
+fn counters() {
+    let openml = OpenML::new();
+    let clone = openml.clone();
+    openml.stats().hit(10);
+    clone.stats().miss(5);
+
+    let stats = openml.cache_stats();
+    assert_eq!((stats.hits(), stats.misses()), (1, 1));
+    assert_eq!(
+        (stats.bytes_from_cache(), stats.bytes_downloaded()),
+        (10, 5)
+    );
+    assert_eq!(clone.cache_stats(), stats);
+    // other clients count separately
+    assert_eq!(OpenML::new().cache_stats(), CacheStats::default());
+
+    clone.reset_cache_stats();
+    assert_eq!(openml.cache_stats(), CacheStats::default());
+}
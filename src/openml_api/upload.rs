@@ -0,0 +1,117 @@
+//! Uploads of flows and runs to the OpenML server
+
+use reqwest::blocking::multipart::{Form, Part};
+
+use crate::error::{Context, Result};
+use crate::runs::{run_xml, FlowMetadata, ParameterSettings, UploadOptions, UploadedRun};
+
+use super::api_types::{parse_response, FlowExistsResponse, UploadFlowResponse, UploadRunResponse};
+use super::web_access::{get_uncached, post_form};
+use super::{FlowId, OpenML};
+
+/// upload the predictions of a flow on a task as a new run, with the values of the flow's
+/// hyperparameters in the run and the trace of its hyperparameter search, if it did one. The flow
+/// is created first if the server does not know it yet.
+pub(crate) fn upload_run(
+    openml: &OpenML,
+    task_id: &str,
+    flow: &FlowMetadata,
+    parameters: &ParameterSettings,
+    predictions: String,
+    trace: Option<String>,
+) -> Result<UploadedRun> {
+    let api_key = openml.require_api_key()?;
+    parameters.check(flow)?;
+    let options = openml.upload_options();
+    let flow_id = ensure_flow(openml, flow, api_key, options)?;
+
+    let url = openml.api_url("run");
+    let upload = || {
+        let mut form = Form::new()
+            .text("api_key", api_key.to_owned())
+            .part(
+                "description",
+                Part::text(run_xml(task_id, flow_id, parameters, options))
+                    .file_name("description.xml"),
+            )
+            .part(
+                "predictions",
+                Part::text(predictions).file_name("predictions.arff"),
+            );
+        if let Some(trace) = trace {
+            form = form.part("trace", Part::text(trace).file_name("trace.arff"));
+        }
+        let response: UploadRunResponse = parse_response(&post_form(openml, &url, form)?)?;
+        response.upload_run.run_id.parse("/upload_run/run_id")
+    };
+    let run_id = upload().context(&url, || format!("uploading run of task {}", task_id))?;
+    info!("Uploaded run {} of task {}", run_id, task_id);
+
+    Ok(UploadedRun {
+        run_id,
+        task_id: task_id.to_owned(),
+        flow_id,
+        parameter_settings: parameters.clone(),
+        url: format!("{}/r/{}", openml.server(), run_id),
+    })
+}
+
+/// get the id of a flow, uploading it if the server does not know it yet
+fn ensure_flow(
+    openml: &OpenML,
+    flow: &FlowMetadata,
+    api_key: &str,
+    options: &UploadOptions,
+) -> Result<FlowId> {
+    let url = openml.api_url(&format!(
+        "flow/exists/{}/{}",
+        path_segment(&flow.name),
+        path_segment(&flow.external_version)
+    ));
+    let exists = || {
+        let response: FlowExistsResponse = parse_response(&get_uncached(openml, &url)?)?;
+        let flow_exists = response.flow_exists;
+        if flow_exists.exists == "true" {
+            Ok(Some(FlowId(flow_exists.id.parse("/flow_exists/id")?)))
+        } else {
+            Ok(None)
+        }
+    };
+    let describe = || format!("looking up flow {} {}", flow.name, flow.external_version);
+    if let Some(id) = exists().context(&url, describe)? {
+        return Ok(id);
+    }
+
+    let url = openml.api_url("flow");
+    let upload = || {
+        let form = Form::new().text("api_key", api_key.to_owned()).part(
+            "description",
+            Part::text(flow.to_xml(options)).file_name("description.xml"),
+        );
+        let response: UploadFlowResponse = parse_response(&post_form(openml, &url, form)?)?;
+        Ok(FlowId(response.upload_flow.id.parse("/upload_flow/id")?))
+    };
+    let id = upload().context(&url, || format!("uploading flow {}", flow.name))?;
+    info!("Uploaded flow {} as {}", flow.name, id);
+    Ok(id)
+}
+
+/// percent-encode text for use as a segment of a URL path
+fn path_segment(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            byte => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[test]
+fn flow_path_segments() {
+    assert_eq!(path_segment("rust.knn_0.1"), "rust.knn_0.1");
+    assert_eq!(path_segment("k nn/ü"), "k%20nn%2F%C3%BC");
+}
@@ -0,0 +1,71 @@
+//! Verification of runs stored on the OpenML server
+
+use crate::error::{Context, Error, Result};
+use crate::measures::Measure;
+use crate::runs::{verify_predictions, RunVerification};
+
+use super::api_types::{parse_response, RunResponse};
+use super::client::default_client;
+use super::web_access::get_cached;
+use super::{OpenML, RunIdentifier};
+
+impl OpenML {
+    /// Download the predictions of a run from the OpenML server, recompute the measures the
+    /// server evaluated them with, and compare both.
+    ///
+    /// Only measures over the whole run are compared, not those of single folds, and only
+    /// measures this crate can compute from the predictions file; the area under the ROC curve
+    /// and the mean class complexity are only recomputed for binary tasks. The description and
+    /// the predictions of the run are cached like those of tasks.
+    ///
+    /// ```no_run
+    /// let verification = openml::OpenML::new().verify_run(10437).unwrap();
+    /// print!("{}", verification);
+    /// assert!(verification.discrepancies(1e-6).is_empty());
+    /// ```
+    pub fn verify_run<T: RunIdentifier>(&self, id: T) -> Result<RunVerification> {
+        let run_id = id.as_u32()?;
+        let url = self.api_url(&format!("run/{}", run_id));
+        let verify = || {
+            let run = parse_response::<RunResponse>(&get_cached(self, &url)?)?.run;
+            let output = run
+                .output_data
+                .ok_or_else(|| no_predictions("/run/output_data"))?;
+            let file = output
+                .file
+                .iter()
+                .find(|file| file.name == "predictions")
+                .ok_or_else(|| no_predictions("/run/output_data/file"))?;
+
+            let mut server = vec![];
+            for evaluation in &output.evaluation {
+                if evaluation.repeat.is_some() || evaluation.fold.is_some() {
+                    continue;
+                }
+                if let (Some(measure), Some(value)) = (
+                    Measure::from_openml_name(&evaluation.name),
+                    &evaluation.value,
+                ) {
+                    server.push((measure, value.parse(&evaluation.name)?));
+                }
+            }
+
+            let predictions = get_cached(self, &file.url)
+                .context(&file.url, || "fetching the predictions".to_owned())?;
+            verify_predictions(run_id, run.task_id.0, &predictions, &server)
+        };
+        verify().context(&url, || format!("verifying run {}", run_id))
+    }
+}
+
+/// Verify a run with the default client, like `OpenML::verify_run`
+pub fn verify_run<T: RunIdentifier>(id: T) -> Result<RunVerification> {
+    default_client().verify_run(id)
+}
+
+fn no_predictions(path: &str) -> Error {
+    Error::JsonFieldError {
+        path: path.to_owned(),
+        message: "the run has no predictions file".to_owned(),
+    }
+}
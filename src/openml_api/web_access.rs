@@ -1,65 +1,1121 @@
 //! Access the OpenML REST API
 
-use std::fs::{File, OpenOptions};
+use std::env;
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
 use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(test)]
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
 use app_dirs::{app_root, AppDataType, AppInfo};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use reqwest::blocking::multipart::Form;
+use reqwest::header::{HeaderMap, CONTENT_RANGE, RANGE};
+use reqwest::StatusCode;
 
-use crate::error::Result;
+use crate::error::{Error, Result};
 
+use super::adaptive_limit::{AdaptiveLimit, Outcome};
+use super::api_types::api_error;
+use super::client::default_client;
 use super::file_lock::{ExclusiveLock, SharedLock};
+use super::in_flight::DownloadLock;
+use super::manifest::{self, Md5Writer, Validators};
+use super::parsed_cache::binary_path;
+use super::retry::{self, RetryPolicy};
+use super::{compress_cache, OpenML, ResourceKind};
 
 const APP_INFO: AppInfo = AppInfo {
     name: "openml-rust",
     author: "openml-rust",
 };
 
+/// Environment variable that sets the cache directory of clients without one
+const CACHE_DIR_VAR: &str = "OPENML_CACHE_DIR";
+
 /// Query a URL. If possible read the response from local cache
-pub fn get_cached(url: &str) -> Result<String> {
-    // todo: is there a potential race condition with a process locking the file for reading while
-    //       the writer has created but not yet locked the file?
+pub(crate) fn get_cached(openml: &OpenML, url: &str) -> Result<String> {
+    let path = lookup_path(openml, url)?;
 
-    let mut path = app_root(AppDataType::UserCache, &APP_INFO)?;
-    path.push(url_to_file(url));
+    // descriptions are small and often queried repeatedly, so they are also kept in memory
+    let in_memory = matches!(
+        ResourceKind::of_url(url),
+        Some(ResourceKind::Task) | Some(ResourceKind::DataSetDescription)
+    );
+    if in_memory {
+        if let Some(data) = openml.memory_cache().get_response(url) {
+            openml.stats().hit(data.len() as u64);
+            return Ok(data.as_ref().clone());
+        }
+    }
+    let data = read_or_download(openml, url, &path)?;
+    if in_memory {
+        openml.memory_cache().insert_response(url, data.clone());
+    }
+    Ok(data)
+}
+
+/// Read the cached response of a URL from a file, or download it into the file
+fn read_or_download(openml: &OpenML, url: &str, path: &Path) -> Result<String> {
+    if let Some(data) = read_cached(openml, url, path)? {
+        return Ok(data);
+    }
+    check_online(openml, url)?;
+
+    // another thread may be downloading the URL; then its file is read once it is done
+    let _download = DownloadLock::new(url);
+    if let Some(data) = read_cached(openml, url, path)? {
+        return Ok(data);
+    }
+    info!("Downloading {}", url);
+    let resumable = resumable(url);
+    let (data, validators) = if resumable {
+        let part = partial_path(path);
+        let validators = download_partial(openml, url, &part, openml.retry_policy())?;
+        (fs::read_to_string(&part)?, validators)
+    } else {
+        let (data, validators) = download(openml, url)?;
+        openml.stats().miss(data.len() as u64);
+        (data, validators)
+    };
+    write_atomically(url, path, validators, |file| {
+        write_text(file, &data)?;
+        Ok(md5::compute(&data))
+    })?;
+    if resumable {
+        fs::remove_file(partial_path(path))?;
+    }
+    Ok(data)
+}
+
+/// Read the cached response of a URL, if the file exists
+fn read_cached(openml: &OpenML, url: &str, path: &Path) -> Result<Option<String>> {
+    match File::open(path) {
+        Ok(f) => {
+            info!("Loading cached {}", url);
+            let data = read_text(SharedLock::new(f)?)?;
+            openml.stats().hit(data.len() as u64);
+            Ok(Some(data))
+        }
+        Err(_) => Ok(None),
+    }
+}
+
+/// Number of temporary files created by this process, to give each a unique name
+static TMP_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Write a downloaded file into the cache. `write` writes the file and returns the MD5 checksum
+/// of the response. The file is written under a temporary name and renamed when it is complete,
+/// so that readers never see a partially written file. Threads of one process never download the
+/// same file at once, but if several processes do, the last one to finish replaces the file of the
+/// others.
+fn write_atomically<F>(url: &str, path: &Path, validators: Validators, write: F) -> Result<()>
+where
+    F: FnOnce(&mut File) -> Result<md5::Digest>,
+{
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        TMP_FILES.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let result = File::create(&tmp)
+        .map_err(Error::from)
+        .and_then(|mut file| write(&mut file))
+        .and_then(|md5| {
+            fs::rename(&tmp, path)?;
+            Ok(md5)
+        });
+    match result {
+        Ok(md5) => {
+            record(url, path, md5, validators);
+            Ok(())
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&tmp);
+            Err(e)
+        }
+    }
+}
+
+/// Query a URL like `get_cached`, and check that the MD5 checksum of the response matches
+/// `md5`, given as a hexadecimal string. A cached response that does not match is downloaded
+/// again once, in case the cached file is truncated or corrupt. A corrupt file in the shared
+/// cache is left alone, and the response is downloaded into the writable cache instead.
+pub(crate) fn get_cached_verified(openml: &OpenML, url: &str, md5: &str) -> Result<String> {
+    let data = get_cached(openml, url)?;
+    match check_md5(url, &data, md5) {
+        Err(Error::ChecksumMismatch { .. }) if !openml.is_offline() => {
+            warn!(
+                "Checksum of cached {} does not match; downloading it again",
+                url
+            );
+            // the writable copy may be the one that does not match
+            openml.remove_cached(url)?;
+            let data = read_or_download(openml, url, &cache_path(openml, url)?)?;
+            check_md5(url, &data, md5)?;
+            Ok(data)
+        }
+        result => result.map(|()| data),
+    }
+}
+
+/// Fail with `Error::ChecksumMismatch` if the MD5 checksum of `data` is not `expected`
+fn check_md5(url: &str, data: &str, expected: &str) -> Result<()> {
+    let actual = format!("{:x}", md5::compute(data));
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        return Err(Error::ChecksumMismatch {
+            url: url.to_owned(),
+            expected: expected.to_owned(),
+            actual,
+        });
+    }
+    Ok(())
+}
+
+/// record the provenance of a downloaded file; failing to do so is not an error
+fn record(url: &str, path: &Path, md5: md5::Digest, validators: Validators) {
+    if let Err(e) = manifest::record(url, path, md5, validators) {
+        warn!("Could not record the provenance of {}: {:?}", url, e);
+    }
+}
+
+/// First bytes of a gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// read a cached response, which may be compressed
+pub(crate) fn read_text<R: Read>(mut reader: R) -> Result<String> {
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes)?;
+    if !bytes.starts_with(&GZIP_MAGIC) {
+        return Ok(String::from_utf8(bytes)?);
+    }
+    let mut data = String::new();
+    GzDecoder::new(&bytes[..]).read_to_string(&mut data)?;
+    Ok(data)
+}
+
+/// write a response to the cache, compressed if `set_cache_compression` is on
+fn write_text<W: Write>(writer: W, data: &str) -> Result<()> {
+    let mut writer = writer;
+    if !compress_cache() {
+        writer.write_all(data.as_bytes())?;
+        return Ok(());
+    }
+    let mut encoder = GzEncoder::new(writer, Compression::fast());
+    encoder.write_all(data.as_bytes())?;
+    encoder.finish()?;
+    Ok(())
+}
+
+/// Query a URL and return a reader over the cached response, together with its size in bytes.
+///
+/// In contrast to `get_cached` the response is streamed to disk and never held in memory as a
+/// whole, which makes this function suitable for very large files.
+pub(crate) fn open_cached(openml: &OpenML, url: &str) -> Result<(SharedLock, u64)> {
+    let path = lookup_path(openml, url)?;
+
+    let _download = if path.is_file() {
+        None
+    } else {
+        check_online(openml, url)?;
+        Some(DownloadLock::new(url))
+    };
+    if !path.is_file() && resumable(url) {
+        info!("Downloading {}", url);
+        let part = partial_path(&path);
+        let validators = download_partial(openml, url, &part, openml.retry_policy())?;
+        finish_partial(url, &part, &path, validators)?;
+    } else if !path.is_file() {
+        info!("Downloading {}", url);
+        let client = openml.http()?;
+        let mut response = retry::send(url, openml.retry_policy(), || client.get(url))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(status_error(url, status, response.text()?));
+        }
+        let validators = Validators::from_headers(response.headers());
+        write_atomically(url, &path, validators, |file| {
+            let mut writer = Md5Writer::new(file);
+            openml.stats().miss(response.copy_to(&mut writer)?);
+            Ok(writer.digest())
+        })?;
+    } else {
+        info!("Loading cached {}", url);
+        openml.stats().hit(path.metadata()?.len());
+    }
+
+    let f = File::open(&path)?;
+    let size = f.metadata()?.len();
+    Ok((SharedLock::new(f)?, size))
+}
+
+/// Maximum number of concurrent downloads in `download_all`
+const MAX_CONCURRENT_DOWNLOADS: usize = 16;
+
+impl OpenML {
+    /// Download several URLs into the cache in parallel; URLs that are cached already are
+    /// skipped.
+    ///
+    /// The number of concurrent downloads adapts to the server: it grows while responses are fast
+    /// and successful, and is halved whenever the server responds slowly, asks to slow down (429)
+    /// or fails (5xx). Failed downloads are retried according to the retry policy of the client.
+    pub fn download_all(&self, urls: &[&str]) -> Vec<Result<()>> {
+        let limit = AdaptiveLimit::new(2, 1, MAX_CONCURRENT_DOWNLOADS);
+        let next = AtomicUsize::new(0);
+        let results: Mutex<Vec<Option<Result<()>>>> =
+            Mutex::new(urls.iter().map(|_| None).collect());
+
+        thread::scope(|scope| {
+            for _ in 0..MAX_CONCURRENT_DOWNLOADS.min(urls.len()) {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= urls.len() {
+                        break;
+                    }
+                    let result = download_with_limit(self, urls[i], &limit);
+                    results.lock().unwrap()[i] = Some(result);
+                });
+            }
+        });
+
+        results
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|result| result.expect("every URL is processed"))
+            .collect()
+    }
+
+    /// Remove the cached response of a URL, so that the next query downloads it again. The data
+    /// set parsed from the response is removed, too. URLs that are not cached are ignored.
+    pub fn remove_cached(&self, url: &str) -> Result<()> {
+        self.memory_cache().remove(url);
+        let path = cache_path(self, url)?;
+        let file = match OpenOptions::new().write(true).open(&path) {
+            Ok(file) => file,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        let _lock = ExclusiveLock::new(file)?;
+        std::fs::remove_file(&path)?;
+        manifest::remove(&path)?;
 
+        if let Err(e) = std::fs::remove_file(binary_path(self, url)?) {
+            if e.kind() != io::ErrorKind::NotFound {
+                return Err(e.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the directory where downloads are cached, and create it if necessary.
+    ///
+    /// This is the directory given to `OpenMLBuilder::cache_dir` or in the `OPENML_CACHE_DIR`
+    /// environment variable. By default it is the platform's per-user cache directory,
+    /// independent of the working directory:
+    ///
+    /// - Linux: `$XDG_CACHE_HOME/openml-rust` or `~/.cache/openml-rust`
+    /// - macOS: `~/Library/Caches/openml-rust`
+    /// - Windows: `%LOCALAPPDATA%\openml-rust\openml-rust`
+    pub fn cache_dir(&self) -> Result<PathBuf> {
+        resolve_cache_dir(self.configured_cache_dir(), env::var_os(CACHE_DIR_VAR))
+    }
+}
+
+/// Download several URLs into the cache in parallel, like `OpenML::download_all`, with the
+/// default client
+pub fn download_all(urls: &[&str]) -> Vec<Result<()>> {
+    default_client().download_all(urls)
+}
+
+/// Remove the cached response of a URL from the cache of the default client, like
+/// `OpenML::remove_cached`
+pub fn remove_cached(url: &str) -> Result<()> {
+    default_client().remove_cached(url)
+}
+
+/// Get the cache directory of the default client, like `OpenML::cache_dir`
+pub fn cache_dir() -> Result<PathBuf> {
+    default_client().cache_dir()
+}
+
+fn download_with_limit(openml: &OpenML, url: &str, limit: &AdaptiveLimit) -> Result<()> {
+    let policy = openml.retry_policy();
+    let mut attempt = 1;
+    loop {
+        limit.acquire();
+        let start = Instant::now();
+        let result = download_to_cache(openml, url);
+
+        let outcome = match result {
+            Ok(true) => Outcome::Success(start.elapsed()),
+            Err(Error::RateLimited { .. }) | Err(Error::ServerError { .. }) => Outcome::Overloaded,
+            _ => Outcome::Unrelated,
+        };
+        limit.release(outcome);
+
+        let retry = match result {
+            Err(ref e) => policy.retries_error(e),
+            Ok(_) => false,
+        };
+        if retry && attempt < policy.max_attempts {
+            let delay = policy.backoff(attempt);
+            warn!(
+                "Downloading {} failed (attempt {}); retrying in {:?}",
+                url, attempt, delay
+            );
+            thread::sleep(delay);
+            attempt += 1;
+            continue;
+        }
+        return result.map(|_| ());
+    }
+}
+
+/// Stream a URL into the cache, unless it is cached already. Returns `true` if it was downloaded.
+/// Unsuccessful responses are not cached.
+fn download_to_cache(openml: &OpenML, url: &str) -> Result<bool> {
+    let path = lookup_path(openml, url)?;
+    if path.exists() {
+        return Ok(false);
+    }
+    check_online(openml, url)?;
+
+    let _download = DownloadLock::new(url);
+    if path.exists() {
+        return Ok(false);
+    }
+    info!("Downloading {}", url);
+    if resumable(url) {
+        // retries are up to `download_all`
+        let part = partial_path(&path);
+        let validators = download_partial(openml, url, &part, &RetryPolicy::none())?;
+        finish_partial(url, &part, &path, validators)?;
+        return Ok(true);
+    }
+    let mut response = openml.http()?.get(url).send()?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(status_error(url, status, response.text()?));
+    }
+    let validators = Validators::from_headers(response.headers());
+    write_atomically(url, &path, validators, |file| {
+        let mut writer = Md5Writer::new(file);
+        openml.stats().miss(response.copy_to(&mut writer)?);
+        Ok(writer.digest())
+    })?;
+    Ok(true)
+}
+
+/// Check whether an interrupted download of a URL is resumed, rather than started again; this is
+/// the case for data set files, which may be several GB large
+fn resumable(url: &str) -> bool {
+    ResourceKind::of_url(url) == Some(ResourceKind::DataSetFile)
+}
+
+/// Location of the partial file of an unfinished download
+fn partial_path(path: &Path) -> PathBuf {
+    let mut part = path.as_os_str().to_owned();
+    part.push(".part");
+    part.into()
+}
+
+/// Download a URL into the partial file `part`. If the connection fails, the download is resumed
+/// where it stopped with a `Range` request, as often as the retry policy allows. The partial file
+/// is kept if the download fails, so that the next attempt resumes it, too.
+///
+/// The file is not checked against the response of an earlier attempt; checksums of data sets are
+/// verified when they are loaded.
+fn download_partial(
+    openml: &OpenML,
+    url: &str,
+    part: &Path,
+    policy: &RetryPolicy,
+) -> Result<Validators> {
+    let mut attempt = 1;
     loop {
-        match File::open(&path) {
-            Ok(f) => {
-                info!("Loading cached {}", url);
-                let mut file = SharedLock::new(f)?;
-                let mut data = String::new();
-                file.read_to_string(&mut data)?;
-                return Ok(data);
+        match download_rest(openml, url, part) {
+            Err(ref e) if policy.retries_error(e) && attempt < policy.max_attempts => {
+                let delay = policy.backoff(attempt);
+                warn!(
+                    "Downloading {} failed (attempt {}); resuming in {:?}",
+                    url, attempt, delay
+                );
+                thread::sleep(delay);
+                attempt += 1;
             }
-            Err(_) => {}
+            result => return result,
+        }
+    }
+}
+
+/// Request the part of a URL that is missing from the partial file `part`, and append it
+fn download_rest(openml: &OpenML, url: &str, part: &Path) -> Result<Validators> {
+    let offset = part.metadata().map_or(0, |m| m.len());
+    let mut request = openml.http()?.get(url);
+    if offset > 0 {
+        request = request.header(RANGE, format!("bytes={}-", offset));
+    }
+    let mut response = request.send()?;
+
+    let status = response.status();
+    let resumed = status == StatusCode::PARTIAL_CONTENT;
+    if offset > 0
+        && (status == StatusCode::RANGE_NOT_SATISFIABLE
+            || resumed && range_start(response.headers()) != Some(offset))
+    {
+        // the partial file does not fit the response; start from scratch
+        fs::remove_file(part)?;
+        return download_rest(openml, url, part);
+    }
+    if !status.is_success() {
+        return Err(status_error(url, status, response.text()?));
+    }
+    if resumed {
+        info!("Resuming download of {} at byte {}", url, offset);
+    }
+
+    let validators = Validators::from_headers(response.headers());
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(part)?;
+    openml.stats().miss(response.copy_to(&mut file)?);
+    Ok(validators)
+}
+
+/// get the first byte of a partial response from its `Content-Range` header, e.g.
+/// `bytes 100-999/1000`
+fn range_start(headers: &HeaderMap) -> Option<u64> {
+    let range = headers.get(CONTENT_RANGE)?.to_str().ok()?;
+    let range = range.trim().strip_prefix("bytes")?.trim_start();
+    range.split('-').next()?.parse().ok()
+}
+
+/// Move a completely downloaded partial file into the cache
+fn finish_partial(url: &str, part: &Path, path: &Path, validators: Validators) -> Result<()> {
+    let mut writer = Md5Writer::new(io::sink());
+    io::copy(&mut File::open(part)?, &mut writer)?;
+    fs::rename(part, path)?;
+    record(url, path, writer.digest(), validators);
+    Ok(())
+}
+
+/// Revalidate the cached response of a URL if it is older than the time to live of its kind.
+/// Nothing happens in offline mode.
+fn remove_if_expired(openml: &OpenML, path: &Path, url: &str) -> Result<()> {
+    if openml.is_offline() || !is_expired(path, openml.cache_ttl(url), SystemTime::now()) {
+        return Ok(());
+    }
+    info!("Cached {} expired", url);
+    revalidate(openml, path, url)
+}
+
+/// Ask the server whether the cached response of a URL changed. If the server sent an `ETag` or
+/// `Last-Modified` header with the response, a conditional request asks whether it changed, and
+/// an unchanged file is kept. Otherwise the file is removed, so that it is downloaded again.
+///
+/// If the server cannot be reached or fails, the file is kept, so that an expired file is still
+/// used while the network is down; it is revalidated again on the next query.
+fn revalidate(openml: &OpenML, path: &Path, url: &str) -> Result<()> {
+    let validators = manifest::validators(path);
+    if validators.is_empty() {
+        return openml.remove_cached(url);
+    }
+    let client = openml.http()?;
+    let response = retry::send(url, openml.retry_policy(), || {
+        validators.conditional(client.get(url))
+    });
+    match response.map(|response| response.status()) {
+        Ok(StatusCode::NOT_MODIFIED) => {
+            info!("Cached {} is still up to date", url);
+            manifest::renew(path)
         }
+        Ok(status) if !openml.retry_policy().retries_status(status.as_u16()) => {
+            openml.remove_cached(url)
+        }
+        Ok(status) => {
+            warn!("Could not revalidate cached {} ({}); using it", url, status);
+            Ok(())
+        }
+        Err(e) => {
+            warn!("Could not revalidate cached {} ({}); using it", url, e);
+            Ok(())
+        }
+    }
+}
+
+/// check whether a file was last modified more than `ttl` before `now`
+fn is_expired(path: &Path, ttl: Option<Duration>, now: SystemTime) -> bool {
+    let (ttl, modified) = match (ttl, path.metadata().and_then(|m| m.modified())) {
+        (Some(ttl), Ok(modified)) => (ttl, modified),
+        _ => return false,
+    };
+    now.duration_since(modified).is_ok_and(|age| age > ttl)
+}
+
+/// Location of the cache file for a URL
+///
+/// Files downloaded from a server are kept in a subdirectory named after its host, so that files
+/// from different servers, e.g. the production and the test server, are never confused. Keys
+/// that are not URLs are kept in the cache directory itself.
+pub(crate) fn cache_path(openml: &OpenML, url: &str) -> Result<PathBuf> {
+    let path = path_in(&openml.cache_dir()?, url);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(path)
+}
+
+/// get the location of a cached URL below the cache directory `dir`
+fn path_in(dir: &Path, url: &str) -> PathBuf {
+    let mut path = dir.to_owned();
+    if let Some(host) = url_host(url) {
+        path.push(url_to_file(host));
+    }
+    path.push(url_to_file(url));
+    path
+}
 
-        match OpenOptions::new().create_new(true).write(true).open(&path) {
-            Err(e) => {
-                // todo: is this the correct io error raised if another thread has locked the file currently?
-                if let io::ErrorKind::PermissionDenied = e.kind() {
-                    continue;
+/// get the file to read the cached response to a URL from: the file in the shared cache if it
+/// holds the URL, and otherwise the file in the writable cache, after removing it if it expired
+fn lookup_path(openml: &OpenML, url: &str) -> Result<PathBuf> {
+    if let Some((dir, fall_through)) = openml.shared_cache() {
+        if let Some(path) = find_shared(dir, fall_through, url)? {
+            return Ok(path);
+        }
+    }
+    let path = cache_path(openml, url)?;
+    remove_if_expired(openml, &path, url)?;
+    Ok(path)
+}
+
+/// find the cached response to a URL in the read-only cache directory `dir`. Returns `None` if
+/// it is missing and may be fetched into the writable cache instead.
+fn find_shared(dir: &Path, fall_through: bool, url: &str) -> Result<Option<PathBuf>> {
+    let path = path_in(dir, url);
+    if path.is_file() {
+        Ok(Some(path))
+    } else if fall_through {
+        Ok(None)
+    } else {
+        Err(Error::NotCached {
+            url: url.to_owned(),
+        })
+    }
+}
+
+/// get the host (and port) of a URL
+fn url_host(url: &str) -> Option<&str> {
+    let rest = &url[url.find("://")? + 3..];
+    let host = rest.split('/').next()?;
+    if host.is_empty() {
+        None
+    } else {
+        Some(host)
+    }
+}
+
+/// List the files in the cache directory `dir` and in its subdirectories for servers
+pub(crate) fn cache_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_file() {
+            files.push(entry.path());
+        } else if file_type.is_dir() {
+            for entry in fs::read_dir(entry.path())? {
+                let entry = entry?;
+                if entry.file_type()?.is_file() {
+                    files.push(entry.path());
                 }
-                error!("Error while opening cache for writing: {:?}", e);
-                return Err(e.into());
-            }
-            Ok(f) => {
-                info!("Downloading {}", url);
-                let mut file = ExclusiveLock::new(f)?;
-                let data = download(url)?;
-                file.write_all(data.as_bytes())?;
-                return Ok(data);
             }
         }
     }
+    Ok(files)
 }
 
-/// Query a URL.
-fn download(url: &str) -> Result<String> {
-    Ok(reqwest::get(url)?.text()?)
+/// Get the cache directory and create it if necessary. A configured directory takes precedence
+/// over the environment variable, and both over the platform's cache directory.
+fn resolve_cache_dir(configured: Option<PathBuf>, from_env: Option<OsString>) -> Result<PathBuf> {
+    let dir = configured.or_else(|| from_env.filter(|v| !v.is_empty()).map(PathBuf::from));
+    match dir {
+        Some(dir) => {
+            fs::create_dir_all(&dir)?;
+            Ok(dir)
+        }
+        None => Ok(app_root(AppDataType::UserCache, &APP_INFO)?),
+    }
+}
+
+/// Fail with `Error::Offline` if downloading `url` is forbidden
+fn check_online(openml: &OpenML, url: &str) -> Result<()> {
+    if openml.is_offline() {
+        return Err(Error::Offline {
+            url: url.to_owned(),
+        });
+    }
+    Ok(())
+}
+
+/// Query a URL. Unsuccessful responses fail with an error that depends on their status (see
+/// `status_error`).
+fn download(openml: &OpenML, url: &str) -> Result<(String, Validators)> {
+    let client = openml.http()?;
+    let response = retry::send(url, openml.retry_policy(), || client.get(url))?;
+    let status = response.status();
+    let validators = Validators::from_headers(response.headers());
+    let text = response.text()?;
+    if !status.is_success() {
+        return Err(status_error(url, status, text));
+    }
+    Ok((text, validators))
+}
+
+/// get a response that changes with every upload, e.g. whether a flow exists, bypassing the cache
+pub(crate) fn get_uncached(openml: &OpenML, url: &str) -> Result<String> {
+    check_online(openml, url)?;
+    download(openml, url).map(|(text, _)| text)
+}
+
+/// post a multipart form, e.g. an upload, and get the response. Posts are not retried, because
+/// the server may have processed a request that failed with a timeout.
+pub(crate) fn post_form(openml: &OpenML, url: &str, form: Form) -> Result<String> {
+    check_online(openml, url)?;
+    let response = openml.http()?.post(url).multipart(form).send()?;
+    let status = response.status();
+    let text = response.text()?;
+    if !status.is_success() {
+        return Err(status_error(url, status, text));
+    }
+    Ok(text)
+}
+
+/// Classify an unsuccessful response by its status, keeping its body. OpenML answers queries it
+/// cannot serve, e.g. for unknown ids, with status 412 and an error message in the body.
+fn status_error(url: &str, status: StatusCode, body: String) -> Error {
+    let url = url.to_owned();
+    match status.as_u16() {
+        404 => Error::NotFound { url, body },
+        status @ 401 | status @ 403 => Error::AuthRequired { url, status, body },
+        412 => api_error(&body).unwrap_or(Error::HttpStatus { url, status: 412 }),
+        429 => Error::RateLimited { url, body },
+        status @ 500..=599 => Error::ServerError { url, status, body },
+        status => Error::HttpStatus { url, status },
+    }
 }
 
 /// Convert URL to file name for chching
 fn url_to_file(s: &str) -> String {
     s.replace('/', "_").replace(':', "")
 }
+
+#[test]
+fn cache_dir_precedence() {
+    let base = env::temp_dir().join(format!("openml-test-{}", std::process::id()));
+    let configured = base.join("configured");
+    let from_env = base.join("env");
+
+    assert_eq!(
+        resolve_cache_dir(Some(configured.clone()), Some(from_env.clone().into())).unwrap(),
+        configured
+    );
+    assert_eq!(
+        resolve_cache_dir(None, Some(from_env.clone().into())).unwrap(),
+        from_env
+    );
+    assert!(from_env.is_dir());
+    assert_eq!(
+        resolve_cache_dir(None, Some("".into())).unwrap(),
+        resolve_cache_dir(None, None).unwrap()
+    );
+    fs::remove_dir_all(&base).unwrap();
+}
+
+#[test]
+fn expiry() {
+    use super::ResourceKind;
+
+    assert_eq!(
+        ResourceKind::of_url("https://www.openml.org/api/v1/json/task/59"),
+        Some(ResourceKind::Task)
+    );
+    assert_eq!(
+        ResourceKind::of_url("https://www.openml.org/api/v1/json/data/61"),
+        Some(ResourceKind::DataSetDescription)
+    );
+    assert_eq!(
+        ResourceKind::of_url("https://www.openml.org/data/v1/download/61/iris.arff"),
+        Some(ResourceKind::DataSetFile)
+    );
+    assert_eq!(
+        ResourceKind::of_url("https://www.openml.org/api_splits/get/59/Task_59_splits.arff"),
+        Some(ResourceKind::Splits)
+    );
+
+    let path = env::temp_dir().join(format!("openml-test-{}.ttl", std::process::id()));
+    fs::write(&path, "cached").unwrap();
+    let hour = Duration::from_secs(60 * 60);
+    let now = SystemTime::now();
+    assert!(!is_expired(&path, None, now + 2 * hour));
+    assert!(!is_expired(&path, Some(hour), now));
+    assert!(is_expired(&path, Some(hour), now + 2 * hour));
+    fs::remove_file(&path).unwrap();
+    assert!(!is_expired(&path, Some(hour), now + 2 * hour));
+}
+
+#[test]
+fn md5_checksums() {
+    let url = "https://www.openml.org/data/v1/download/1/test.arff";
+    assert!(check_md5(url, "", "d41d8cd98f00b204e9800998ecf8427e").is_ok());
+    assert!(check_md5(url, "", "D41D8CD98F00B204E9800998ECF8427E").is_ok());
+    match check_md5(url, "truncated", "d41d8cd98f00b204e9800998ecf8427e") {
+        Err(Error::ChecksumMismatch { actual, .. }) => assert_eq!(actual.len(), 32),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn compressed_responses() {
+    let text = "@RELATION r\n@ATTRIBUTE x NUMERIC\n@DATA\n".repeat(100);
+
+    let mut compressed = vec![];
+    let mut encoder = GzEncoder::new(&mut compressed, Compression::fast());
+    encoder.write_all(text.as_bytes()).unwrap();
+    encoder.finish().unwrap();
+    assert!(compressed.len() < text.len() / 10);
+    assert_eq!(read_text(&compressed[..]).unwrap(), text);
+
+    // plain files, e.g. from older versions or `download_all`, are read as they are
+    assert_eq!(read_text(text.as_bytes()).unwrap(), text);
+}
+
+#[test]
+fn atomic_writes() {
+    let dir = env::temp_dir().join(format!("openml-test-{}-atomic", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("https_www.openml.org_api_v1_json_task_1");
+    let url = "https://www.openml.org/api/v1/json/task/1";
+
+    let failed = write_atomically(url, &path, Validators::default(), |file| {
+        file.write_all(b"partial")?;
+        Err(Error::Offline {
+            url: url.to_owned(),
+        })
+    });
+    assert!(failed.is_err());
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+    write_atomically(url, &path, Validators::default(), |file| {
+        file.write_all(b"{}")?;
+        Ok(md5::compute(b"{}"))
+    })
+    .unwrap();
+    assert_eq!(fs::read(&path).unwrap(), b"{}");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn server_namespaces() {
+    assert_eq!(
+        url_host("https://test.openml.org/api/v1/json/task/1"),
+        Some("test.openml.org")
+    );
+    assert_eq!(url_host("http://localhost:8080/"), Some("localhost:8080"));
+    assert_eq!(url_host("features_61_1_00000000.f64"), None);
+    assert_eq!(url_to_file("localhost:8080"), "localhost8080");
+}
+
+#[test]
+fn shared_cache_lookup() {
+    let dir = env::temp_dir().join(format!("openml-test-shared-{}", std::process::id()));
+    let cached = "https://www.openml.org/api/v1/json/task/59";
+    let missing = "https://www.openml.org/api/v1/json/task/61";
+    let path = path_in(&dir, cached);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, "{}").unwrap();
+
+    assert_eq!(find_shared(&dir, false, cached).unwrap(), Some(path));
+    assert_eq!(find_shared(&dir, true, missing).unwrap(), None);
+    match find_shared(&dir, false, missing) {
+        Err(Error::NotCached { url }) => assert_eq!(url, missing),
+        other => panic!("unexpected {:?}", other),
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn status_errors() {
+    let url = "https://www.openml.org/api/v1/json/task/1";
+    let error = |status: u16, body: &str| {
+        status_error(url, StatusCode::from_u16(status).unwrap(), body.to_owned())
+    };
+
+    assert!(matches!(error(404, ""), Error::NotFound { .. }));
+    assert!(matches!(
+        error(403, "denied"),
+        Error::AuthRequired { status: 403, ref body, .. } if body == "denied"
+    ));
+    assert!(matches!(
+        error(
+            412,
+            r#"{"error": {"code": "151", "message": "Unknown task"}}"#
+        ),
+        Error::Api { code: 151, .. }
+    ));
+    assert!(matches!(
+        error(412, "<html>"),
+        Error::HttpStatus { status: 412, .. }
+    ));
+    assert!(matches!(error(429, ""), Error::RateLimited { .. }));
+    assert!(matches!(
+        error(503, ""),
+        Error::ServerError { status: 503, .. }
+    ));
+    assert!(matches!(
+        error(418, ""),
+        Error::HttpStatus { status: 418, .. }
+    ));
+    assert_eq!(error(503, "").status(), Some(503));
+    assert_eq!(error(404, "").status(), Some(404));
+}
+
+#[test]
+fn partial_responses() {
+    use reqwest::header::HeaderValue;
+
+    let mut headers = HeaderMap::new();
+    assert_eq!(range_start(&headers), None);
+    headers.insert(
+        CONTENT_RANGE,
+        HeaderValue::from_static("bytes 100-999/1000"),
+    );
+    assert_eq!(range_start(&headers), Some(100));
+    headers.insert(CONTENT_RANGE, HeaderValue::from_static("bytes */1000"));
+    assert_eq!(range_start(&headers), None);
+
+    assert_eq!(
+        partial_path(Path::new("/cache/data.arff")),
+        Path::new("/cache/data.arff.part")
+    );
+    assert!(resumable(
+        "https://www.openml.org/data/v1/download/61/iris.arff"
+    ));
+    assert!(!resumable("https://www.openml.org/api/v1/json/task/59"));
+}
+
+#[test]
+fn offline_mode() {
+    let dir = env::temp_dir().join(format!("openml-test-{}-offline", std::process::id()));
+    let openml = OpenML::builder().cache_dir(&dir).build().offline(true);
+    assert!(openml.is_offline());
+
+    let missing = "https://www.openml.org/api/v1/json/flow/1";
+    match get_cached(&openml, missing) {
+        Err(Error::Offline { url }) => assert_eq!(url, missing),
+        other => panic!("unexpected {:?}", other),
+    }
+    let data_set = "https://www.openml.org/data/v1/download/1/missing.arff";
+    assert!(matches!(
+        open_cached(&openml, data_set),
+        Err(Error::Offline { .. })
+    ));
+    assert!(matches!(
+        openml.download_all(&[missing])[..],
+        [Err(Error::Offline { .. })]
+    ));
+
+    let cached = "https://www.openml.org/api/v1/json/flow/2";
+    fs::write(cache_path(&openml, cached).unwrap(), "{}").unwrap();
+    assert_eq!(get_cached(&openml, cached).unwrap(), "{}");
+    let (_, size) = open_cached(&openml, cached).unwrap();
+    assert_eq!(size, 2);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// A local HTTP/1.1 server for tests that answers requests with `responses` in turn, keeping
+/// connections open. Returns its URL, the number of connections it accepted and the requests it
+/// received.
+#[cfg(test)]
+fn test_server(responses: Vec<String>) -> (String, Arc<AtomicUsize>, Arc<Mutex<Vec<String>>>) {
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let connections = Arc::new(AtomicUsize::new(0));
+    let requests = Arc::new(Mutex::new(vec![]));
+    let responses = Arc::new(Mutex::new(responses.into_iter()));
+
+    let (accepted, received) = (connections.clone(), requests.clone());
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            accepted.fetch_add(1, Ordering::SeqCst);
+            let (received, responses) = (received.clone(), responses.clone());
+            thread::spawn(move || {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut request = String::new();
+                    while reader.read_line(&mut request).unwrap_or(0) > 2 {}
+                    if request.is_empty() {
+                        return;
+                    }
+                    received.lock().unwrap().push(request);
+                    match responses.lock().unwrap().next() {
+                        Some(response) => stream.write_all(response.as_bytes()).unwrap(),
+                        None => return,
+                    }
+                }
+            });
+        }
+    });
+    (url, connections, requests)
+}
+
+/// an HTTP response with a status, extra header lines and a body
+#[cfg(test)]
+fn test_response(status: &str, headers: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Length: {}\r\n{}\r\n{}",
+        status,
+        body.len(),
+        headers,
+        body
+    )
+}
+
+#[test]
+fn connection_reuse() {
+    let responses = (0..3)
+        .map(|i| test_response("200 OK", "", &format!("{{\"n\": {}}}", i)))
+        .collect();
+    let (server, connections, _) = test_server(responses);
+    let openml = OpenML::builder().retry_policy(RetryPolicy::none()).build();
+
+    for i in 0..3 {
+        let url = format!("{}/api/v1/json/flow/{}", server, i);
+        assert_eq!(
+            get_uncached(&openml, &url).unwrap(),
+            format!("{{\"n\": {}}}", i)
+        );
+    }
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn unsuccessful_streamed_downloads() {
+    let dir = env::temp_dir().join(format!("openml-test-{}-streamed", std::process::id()));
+    let (server, _, _) = test_server(vec![
+        test_response("404 Not Found", "", "no such file"),
+        test_response("200 OK", "", "@RELATION r"),
+    ]);
+    let openml = OpenML::builder()
+        .cache_dir(&dir)
+        .retry_policy(RetryPolicy::none())
+        .build();
+    let url = format!("{}/api_splits/get/1/Task_1_splits.arff", server);
+
+    match open_cached(&openml, &url) {
+        Err(Error::NotFound { body, .. }) => assert_eq!(body, "no such file"),
+        other => panic!("unexpected {:?}", other.map(|(_, size)| size)),
+    }
+    assert!(!cache_path(&openml, &url).unwrap().exists());
+
+    let (_, size) = open_cached(&openml, &url).unwrap();
+    assert_eq!(size, 11);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn revalidation() {
+    let dir = env::temp_dir().join(format!("openml-test-{}-revalidate", std::process::id()));
+    let (server, _, requests) = test_server(vec![
+        test_response("304 Not Modified", "", ""),
+        test_response("200 OK", "ETag: \"v2\"\r\n", "{\"changed\": true}"),
+    ]);
+    let openml = OpenML::builder()
+        .cache_dir(&dir)
+        .retry_policy(RetryPolicy::none())
+        .build();
+    let cache = |url: &str| {
+        let path = cache_path(&openml, url).unwrap();
+        let validators = Validators {
+            etag: Some("\"v1\"".to_owned()),
+            last_modified: None,
+        };
+        write_atomically(url, &path, validators, |file| {
+            file.write_all(b"{}")?;
+            Ok(md5::compute(b"{}"))
+        })
+        .unwrap();
+        path
+    };
+
+    // 304: the file did not change and is kept
+    let url = format!("{}/api/v1/json/flow/1", server);
+    let path = cache(&url);
+    revalidate(&openml, &path, &url).unwrap();
+    assert!(path.exists());
+    assert!(requests.lock().unwrap()[0]
+        .to_lowercase()
+        .contains("if-none-match: \"v1\""));
+
+    // 200: the file changed and is removed, so that it is downloaded again
+    revalidate(&openml, &path, &url).unwrap();
+    assert!(!path.exists());
+
+    // the server cannot be reached: the file is kept
+    let closed = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    };
+    let url = format!("{}/api/v1/json/flow/1", closed);
+    let path = cache(&url);
+    revalidate(&openml, &path, &url).unwrap();
+    assert!(path.exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn corrupt_shared_files() {
+    let base = env::temp_dir().join(format!("openml-test-{}-corrupt", std::process::id()));
+    let (server, _, _) = test_server(vec![test_response("200 OK", "", "@RELATION good")]);
+    let url = format!("{}/data/v1/download/1/good.arff", server);
+    let md5 = format!("{:x}", md5::compute("@RELATION good"));
+
+    let shared = path_in(&base.join("shared"), &url);
+    fs::create_dir_all(shared.parent().unwrap()).unwrap();
+    fs::write(&shared, "@RELATION trunc").unwrap();
+
+    let openml = OpenML::builder()
+        .cache_dir(base.join("writable"))
+        .shared_cache(base.join("shared"), true)
+        .retry_policy(RetryPolicy::none())
+        .build();
+    let data = get_cached_verified(&openml, &url, &md5);
+
+    assert_eq!(data.unwrap(), "@RELATION good");
+    assert_eq!(fs::read_to_string(&shared).unwrap(), "@RELATION trunc");
+    // unverified queries of this client still read the shared copy first
+    assert_eq!(get_cached(&openml, &url).unwrap(), "@RELATION trunc");
+    let writable = cache_path(&openml, &url).unwrap();
+    assert_eq!(
+        read_text(File::open(writable).unwrap()).unwrap(),
+        "@RELATION good"
+    );
+    fs::remove_dir_all(&base).unwrap();
+}
@@ -1 +1,11 @@
-pub use crate::measure_accumulator::MeasureAccumulator;
+//! The prelude re-exports the stable surface of the crate.
+//!
+//! Everything reachable through `use openml::prelude::*` follows semantic versioning: items are
+//! only removed or changed in a breaking way together with a minor version bump (while the crate
+//! is below 1.0), and every change to this list is recorded in `CHANGELOG.md`.
+
+pub use crate::error::Error;
+pub use crate::measures::MeasureAccumulator;
+pub use crate::openml_api::OpenML;
+pub use crate::runs::RunResult;
+pub use crate::tasks::{Flow, Task};
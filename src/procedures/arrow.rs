@@ -0,0 +1,88 @@
+//! Conversion of folds into Arrow arrays, enabled by the `arrow` feature
+
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, RecordBatch, StringArray, UInt32Array};
+
+use super::{Fold, Procedure};
+
+impl Fold {
+    /// row indices of the training set as an Arrow array
+    ///
+    /// # Panics
+    /// if a row index does not fit into 32 bits
+    pub fn trainset_arrow(&self) -> UInt32Array {
+        to_arrow(&self.trainset)
+    }
+
+    /// row indices of the testing set as an Arrow array
+    ///
+    /// # Panics
+    /// if a row index does not fit into 32 bits
+    pub fn testset_arrow(&self) -> UInt32Array {
+        to_arrow(&self.testset)
+    }
+}
+
+fn to_arrow(rows: &[usize]) -> UInt32Array {
+    rows.iter()
+        .map(|&row| u32::try_from(row).expect("row index exceeds 32 bits"))
+        .collect::<Vec<_>>()
+        .into()
+}
+
+/// all folds as a table with the columns `repeat`, `fold`, `rowid` and `type` (`"TRAIN"` or
+/// `"TEST"`), in the layout of OpenML's split files
+pub(crate) fn to_record_batch(splits: &dyn Procedure) -> RecordBatch {
+    let mut repeats = vec![];
+    let mut folds = vec![];
+    let mut rows = vec![];
+    let mut types = vec![];
+
+    for (repeat, fold_index, fold) in splits.iter() {
+        for (set, purpose) in [(&fold.trainset, "TRAIN"), (&fold.testset, "TEST")] {
+            for &row in set {
+                repeats.push(repeat as u32);
+                folds.push(fold_index as u32);
+                rows.push(row);
+                types.push(purpose);
+            }
+        }
+    }
+
+    RecordBatch::try_from_iter(vec![
+        ("repeat", Arc::new(UInt32Array::from(repeats)) as ArrayRef),
+        ("fold", Arc::new(UInt32Array::from(folds)) as ArrayRef),
+        ("rowid", Arc::new(to_arrow(&rows)) as ArrayRef),
+        ("type", Arc::new(StringArray::from(types)) as ArrayRef),
+    ])
+    .expect("columns have equal length")
+}
+
+#[test]
+fn record_batch() {
+    use arrow_array::Array;
+
+    use super::FrozenSets;
+
+    let fold = Fold {
+        trainset: vec![0, 2],
+        testset: vec![1],
+    };
+    assert_eq!(fold.testset_arrow().values().to_vec(), vec![1]);
+
+    let batch = to_record_batch(&FrozenSets {
+        folds: vec![vec![fold]],
+    });
+    assert_eq!(batch.num_rows(), 3);
+    assert_eq!(batch.num_columns(), 4);
+
+    let types = batch
+        .column(3)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(types.value(2), "TEST");
+    assert_eq!(types.len(), 3);
+}
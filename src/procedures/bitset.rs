@@ -0,0 +1,48 @@
+//! A minimal growable bit set
+
+const BITS: usize = 64;
+
+/// Set of `usize` values, stored as one bit per possible value
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BitSet {
+    blocks: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new() -> Self {
+        BitSet { blocks: Vec::new() }
+    }
+
+    /// add a value to the set, growing the storage if necessary. Returns whether the value was
+    /// not in the set yet.
+    pub fn insert(&mut self, i: usize) -> bool {
+        let block = i / BITS;
+        if block >= self.blocks.len() {
+            self.blocks.resize(block + 1, 0);
+        }
+        let bit = 1 << (i % BITS);
+        let new = self.blocks[block] & bit == 0;
+        self.blocks[block] |= bit;
+        new
+    }
+
+    /// iterate over the values in ascending order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.blocks.iter().enumerate().flat_map(|(k, &block)| {
+            (0..BITS)
+                .filter(move |bit| block & (1 << bit) != 0)
+                .map(move |bit| k * BITS + bit)
+        })
+    }
+}
+
+#[test]
+fn bitset() {
+    let mut set = BitSet::new();
+    assert!(set.insert(3));
+    assert!(set.insert(130));
+    assert!(set.insert(64));
+    assert!(!set.insert(3));
+
+    assert_eq!(set.iter().collect::<Vec<_>>(), vec![3, 64, 130]);
+}
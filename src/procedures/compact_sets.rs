@@ -0,0 +1,48 @@
+use std::borrow::Cow;
+
+use super::{BitSet, Fold, Procedure};
+
+/// pre-defined cross-validation, stored as one pair of bit sets per fold
+///
+/// This representation needs one bit per row and fold, instead of one `usize` per row and fold.
+/// The index vectors of a fold are only materialized while the fold is being evaluated.
+#[derive(Debug)]
+pub(crate) struct CompactSets {
+    pub(crate) folds: Vec<Vec<CompactFold>>,
+}
+
+/// Training and testing set of a single fold
+#[derive(Debug, Clone)]
+pub(crate) struct CompactFold {
+    pub(crate) trainset: BitSet,
+    pub(crate) testset: BitSet,
+}
+
+impl CompactFold {
+    pub fn new() -> Self {
+        CompactFold {
+            trainset: BitSet::new(),
+            testset: BitSet::new(),
+        }
+    }
+
+    /// expand the bit sets into index vectors (in ascending row order)
+    pub(crate) fn to_fold(&self) -> Fold {
+        Fold {
+            trainset: self.trainset.iter().collect(),
+            testset: self.testset.iter().collect(),
+        }
+    }
+}
+
+impl Procedure for CompactSets {
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (usize, usize, Cow<'a, Fold>)> + 'a> {
+        let iter = self.folds.iter().enumerate().flat_map(|(r, inner)| {
+            inner
+                .iter()
+                .enumerate()
+                .map(move |(f, fold)| (r, f, Cow::Owned(fold.to_fold())))
+        });
+        Box::new(iter)
+    }
+}
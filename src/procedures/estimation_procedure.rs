@@ -0,0 +1,80 @@
+/// Kind of validation procedure, as named by OpenML
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProcedureType {
+    CrossValidation,
+    Holdout,
+    LeaveOneOut,
+    /// train on the first rows and test on the remaining ones; see `splits::time_ordered`
+    TimeOrdered,
+    /// any procedure type not known to this crate
+    Other(String),
+}
+
+/// Description of the evaluation scheme used by a task
+#[derive(Debug, Clone, PartialEq)]
+pub struct EstimationProcedure {
+    pub(crate) id: Option<u32>,
+    pub(crate) kind: ProcedureType,
+    pub(crate) n_repeats: usize,
+    pub(crate) n_folds: usize,
+    pub(crate) percentage: Option<f64>,
+    pub(crate) stratified: bool,
+}
+
+impl EstimationProcedure {
+    /// get the OpenML ID of the procedure, if it was loaded from OpenML
+    pub fn id(&self) -> Option<u32> {
+        self.id
+    }
+
+    /// get the procedure type
+    pub fn kind(&self) -> &ProcedureType {
+        &self.kind
+    }
+
+    /// get the number of repeats
+    pub fn n_repeats(&self) -> usize {
+        self.n_repeats
+    }
+
+    /// get the number of folds per repeat
+    pub fn n_folds(&self) -> usize {
+        self.n_folds
+    }
+
+    /// get the percentage of rows used for testing, if the procedure is a holdout or time-ordered
+    pub fn percentage(&self) -> Option<f64> {
+        self.percentage
+    }
+
+    /// return `true` if the splits preserve class proportions
+    pub fn is_stratified(&self) -> bool {
+        self.stratified
+    }
+}
+
+impl EstimationProcedure {
+    /// describe a time-ordered split of `n_rows` rows with the testing set starting at
+    /// `test_start`
+    pub(crate) fn time_ordered(n_rows: usize, test_start: usize) -> Self {
+        EstimationProcedure {
+            id: None,
+            kind: ProcedureType::TimeOrdered,
+            n_repeats: 1,
+            n_folds: 1,
+            percentage: Some(100.0 * (n_rows - test_start) as f64 / n_rows as f64),
+            stratified: false,
+        }
+    }
+}
+
+impl From<&str> for ProcedureType {
+    fn from(name: &str) -> Self {
+        match name {
+            "crossvalidation" => ProcedureType::CrossValidation,
+            "holdout" => ProcedureType::Holdout,
+            "leaveoneout" => ProcedureType::LeaveOneOut,
+            _ => ProcedureType::Other(name.to_owned()),
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use super::{Fold, Procedure};
 
 /// pre-defined cross-validation
@@ -7,8 +9,13 @@ pub(crate) struct FrozenSets {
 }
 
 impl Procedure for FrozenSets {
-    fn iter<'a>(&'a self) -> Box<'a + Iterator<Item = &'a Fold>> {
-        let iter = self.folds.iter().flat_map(|inner| inner.iter());
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (usize, usize, Cow<'a, Fold>)> + 'a> {
+        let iter = self.folds.iter().enumerate().flat_map(|(r, inner)| {
+            inner
+                .iter()
+                .enumerate()
+                .map(move |(f, fold)| (r, f, Cow::Borrowed(fold)))
+        });
         Box::new(iter)
     }
 }
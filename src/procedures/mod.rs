@@ -1,26 +1,47 @@
 //! Validation procedures
 
+#[cfg(feature = "arrow")]
+pub(crate) mod arrow;
+mod bitset;
+mod compact_sets;
+mod estimation_procedure;
 mod frozen_sets;
 
+use std::borrow::Cow;
+
+pub(crate) use self::bitset::BitSet;
+pub(crate) use self::compact_sets::{CompactFold, CompactSets};
+pub use self::estimation_procedure::{EstimationProcedure, ProcedureType};
 pub(crate) use self::frozen_sets::FrozenSets;
 
 /// Validation procedures support iteration over cross-validation folds
 pub(crate) trait Procedure {
-    fn iter<'a>(&'a self) -> Box<'a + Iterator<Item = &'a Fold>>;
+    /// iterate over all folds, together with their repeat and fold index
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (usize, usize, Cow<'a, Fold>)> + 'a>;
 }
 
 /// A single cross-validation fold, consisting of a training set and a testing set
-#[derive(Debug, Clone)]
-pub(crate) struct Fold {
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fold {
     pub(crate) trainset: Vec<usize>,
     pub(crate) testset: Vec<usize>,
 }
 
 impl Fold {
-    pub fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Fold {
             trainset: Vec::new(),
             testset: Vec::new(),
         }
     }
+
+    /// row indices of the training set
+    pub fn trainset(&self) -> &[usize] {
+        &self.trainset
+    }
+
+    /// row indices of the testing set
+    pub fn testset(&self) -> &[usize] {
+        &self.testset
+    }
 }
@@ -0,0 +1,262 @@
+//! Descriptions of flows
+//!
+//! OpenML identifies a flow by its name and external version. Runs can only be uploaded for flows
+//! that are known to the server, so a flow is uploaded together with its first run.
+
+use std::collections::HashSet;
+use std::fmt::Display;
+
+use crate::error::{Error, Result};
+
+use super::upload::UploadOptions;
+use super::xml::{escape, OML_NAMESPACE};
+
+/// What OpenML needs to know about a flow to store runs of it
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowMetadata {
+    /// name of the flow, e.g. `"rust.linfa.DecisionTree"`
+    pub name: String,
+    /// version of the implementation, e.g. `"linfa_0.7.0"`; a flow with changed behaviour needs a
+    /// new external version
+    pub external_version: String,
+    /// human-readable description of what the flow does
+    pub description: String,
+    /// libraries the flow depends on, e.g. `"linfa 0.7.0, ndarray 0.15"`
+    pub dependencies: Option<String>,
+    /// hyperparameters of the flow
+    pub parameters: Vec<FlowParameter>,
+}
+
+/// A hyperparameter of a flow
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowParameter {
+    pub name: String,
+    /// type of the values, e.g. `"int"`, `"float"`, `"bool"` or `"string"`
+    pub data_type: String,
+    /// value that is used unless a run sets another one
+    pub default_value: Option<String>,
+}
+
+impl FlowMetadata {
+    /// describe a flow without dependencies or parameters; see `FlowBuilder` for describing
+    /// them
+    pub fn new<S, V, D>(name: S, external_version: V, description: D) -> Self
+    where
+        S: Into<String>,
+        V: Into<String>,
+        D: Into<String>,
+    {
+        FlowMetadata {
+            name: name.into(),
+            external_version: external_version.into(),
+            description: description.into(),
+            dependencies: None,
+            parameters: Vec::new(),
+        }
+    }
+
+    /// write the XML description that is uploaded to create the flow
+    pub(crate) fn to_xml(&self, options: &UploadOptions) -> String {
+        let mut xml = format!("<oml:flow xmlns:oml=\"{}\">\n", OML_NAMESPACE);
+        xml += &format!("  <oml:name>{}</oml:name>\n", escape(&self.name));
+        xml += &format!(
+            "  <oml:external_version>{}</oml:external_version>\n",
+            escape(&self.external_version)
+        );
+        xml += &format!(
+            "  <oml:description>{}</oml:description>\n",
+            escape(&self.description)
+        );
+        if let Some(dependencies) = &self.dependencies {
+            xml += &format!(
+                "  <oml:dependencies>{}</oml:dependencies>\n",
+                escape(dependencies)
+            );
+        }
+        for parameter in &self.parameters {
+            xml += "  <oml:parameter>\n";
+            xml += &format!("    <oml:name>{}</oml:name>\n", escape(&parameter.name));
+            xml += &format!(
+                "    <oml:data_type>{}</oml:data_type>\n",
+                escape(&parameter.data_type)
+            );
+            if let Some(default) = &parameter.default_value {
+                xml += &format!(
+                    "    <oml:default_value>{}</oml:default_value>\n",
+                    escape(default)
+                );
+            }
+            xml += "  </oml:parameter>\n";
+        }
+        xml += &options.to_xml();
+        xml += "</oml:flow>\n";
+        xml
+    }
+}
+
+/// Builds the description of a flow step by step
+///
+/// The external version should change whenever the behaviour of the flow changes, e.g. by
+/// combining the version of the crate that implements it with the commit it was built from:
+///
+/// ```
+/// use openml::FlowBuilder;
+///
+/// let flow = FlowBuilder::new("rust.knn", concat!(env!("CARGO_PKG_VERSION"), "+3f2a9c1"))
+///     .description("k-nearest neighbours with Euclidean distance")
+///     .parameter("k", "int", 5)
+///     .parameter("weighted", "bool", false)
+///     .dependency("openml 0.1")
+///     .build()
+///     .unwrap();
+/// assert_eq!(flow.parameters[0].default_value.as_deref(), Some("5"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct FlowBuilder {
+    flow: FlowMetadata,
+    dependencies: Vec<String>,
+}
+
+impl FlowBuilder {
+    /// start describing the flow with the given name and external version. Its description
+    /// defaults to the name.
+    pub fn new<S: Into<String>, V: Into<String>>(name: S, external_version: V) -> Self {
+        let name = name.into();
+        FlowBuilder {
+            flow: FlowMetadata::new(name.clone(), external_version, name),
+            dependencies: Vec::new(),
+        }
+    }
+
+    /// set the human-readable description of what the flow does
+    pub fn description<D: Into<String>>(mut self, description: D) -> Self {
+        self.flow.description = description.into();
+        self
+    }
+
+    /// add a hyperparameter with the type of its values and its default value
+    pub fn parameter<S, T, D>(mut self, name: S, data_type: T, default_value: D) -> Self
+    where
+        S: Into<String>,
+        T: Into<String>,
+        D: Display,
+    {
+        self.flow.parameters.push(FlowParameter {
+            name: name.into(),
+            data_type: data_type.into(),
+            default_value: Some(default_value.to_string()),
+        });
+        self
+    }
+
+    /// add a hyperparameter without a default value
+    pub fn required_parameter<S: Into<String>, T: Into<String>>(
+        mut self,
+        name: S,
+        data_type: T,
+    ) -> Self {
+        self.flow.parameters.push(FlowParameter {
+            name: name.into(),
+            data_type: data_type.into(),
+            default_value: None,
+        });
+        self
+    }
+
+    /// add a library the flow depends on, e.g. `"ndarray 0.15"`
+    pub fn dependency<S: Into<String>>(mut self, dependency: S) -> Self {
+        self.dependencies.push(dependency.into());
+        self
+    }
+
+    /// finish the description. Fails with `Error::InvalidFlow` if the name or external version
+    /// is empty, or if two parameters have the same name.
+    pub fn build(self) -> Result<FlowMetadata> {
+        let mut flow = self.flow;
+        let invalid = |message: String| Error::InvalidFlow {
+            name: flow.name.clone(),
+            message,
+        };
+        if flow.name.trim().is_empty() {
+            return Err(invalid("the name is empty".to_owned()));
+        }
+        if flow.external_version.trim().is_empty() {
+            return Err(invalid("the external version is empty".to_owned()));
+        }
+        let mut names = HashSet::new();
+        for parameter in &flow.parameters {
+            if !names.insert(parameter.name.as_str()) {
+                return Err(invalid(format!(
+                    "parameter {:?} is declared twice",
+                    parameter.name
+                )));
+            }
+        }
+        if !self.dependencies.is_empty() {
+            flow.dependencies = Some(self.dependencies.join(", "));
+        }
+        Ok(flow)
+    }
+}
+
+#[test]
+fn flow_xml() {
+    let mut flow = FlowMetadata::new("rust.knn", "0.1", "k < 5 & distance-weighted");
+    assert_eq!(
+        flow.to_xml(&UploadOptions::new()),
+        "<oml:flow xmlns:oml=\"http://openml.org/openml\">\n  \
+         <oml:name>rust.knn</oml:name>\n  \
+         <oml:external_version>0.1</oml:external_version>\n  \
+         <oml:description>k &lt; 5 &amp; distance-weighted</oml:description>\n\
+         </oml:flow>\n"
+    );
+    flow.dependencies = Some("openml 0.1".to_owned());
+    assert!(flow
+        .to_xml(&UploadOptions::new())
+        .contains("<oml:dependencies>openml 0.1</oml:dependencies>\n</oml:flow>"));
+
+    use super::upload::Visibility;
+    let private = UploadOptions::new()
+        .with_tag("study_42")
+        .with_visibility(Visibility::Private);
+    assert!(flow.to_xml(&private).ends_with(
+        "  <oml:tag>study_42</oml:tag>\n  \
+         <oml:visibility>private</oml:visibility>\n\
+         </oml:flow>\n"
+    ));
+}
+
+#[test]
+fn flow_builder() {
+    let flow = FlowBuilder::new("rust.knn", "0.1")
+        .parameter("k", "int", 5)
+        .required_parameter("metric", "string")
+        .dependency("openml 0.1")
+        .dependency("ndarray 0.15")
+        .build()
+        .unwrap();
+    assert_eq!(flow.description, "rust.knn");
+    assert_eq!(
+        flow.dependencies.as_deref(),
+        Some("openml 0.1, ndarray 0.15")
+    );
+    assert!(flow.to_xml(&UploadOptions::new()).ends_with(
+        "  <oml:parameter>\n    \
+         <oml:name>k</oml:name>\n    \
+         <oml:data_type>int</oml:data_type>\n    \
+         <oml:default_value>5</oml:default_value>\n  \
+         </oml:parameter>\n  \
+         <oml:parameter>\n    \
+         <oml:name>metric</oml:name>\n    \
+         <oml:data_type>string</oml:data_type>\n  \
+         </oml:parameter>\n\
+         </oml:flow>\n"
+    ));
+
+    let twice = FlowBuilder::new("rust.knn", "0.1")
+        .parameter("k", "int", 5)
+        .parameter("k", "int", 3)
+        .build();
+    assert!(matches!(twice, Err(Error::InvalidFlow { .. })));
+    assert!(FlowBuilder::new("rust.knn", " ").build().is_err());
+}
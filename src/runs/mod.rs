@@ -0,0 +1,22 @@
+//! Runs: the predictions of a flow on every fold of a task, in the form OpenML stores them
+
+mod flow;
+mod parameters;
+mod predictions;
+mod result;
+mod trace;
+mod upload;
+mod verification;
+mod xml;
+
+pub use self::flow::{FlowBuilder, FlowMetadata, FlowParameter};
+pub use self::parameters::ParameterSettings;
+pub use self::predictions::{PredictionRow, RunPrediction, RunPredictions};
+pub use self::result::RunResult;
+pub use self::trace::{TraceIteration, TraceRecorder};
+pub use self::upload::{UploadOptions, UploadedRun, Visibility};
+pub use self::verification::{MeasureComparison, RunVerification};
+
+pub(crate) use self::result::evaluate_predictions;
+pub(crate) use self::upload::run_xml;
+pub(crate) use self::verification::verify_predictions;
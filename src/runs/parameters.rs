@@ -0,0 +1,105 @@
+//! Hyperparameter settings of runs
+
+use std::fmt;
+
+use crate::error::{Error, Result};
+
+use super::flow::FlowMetadata;
+
+/// The values a run sets for the hyperparameters of its flow, in the order in which they were
+/// set. Parameters that are not set keep the default value declared by the flow.
+///
+/// ```
+/// use openml::ParameterSettings;
+///
+/// let settings = ParameterSettings::new().set("k", 7).set("weighted", true);
+/// assert_eq!(settings.get("k"), Some("7"));
+/// assert_eq!(settings.to_string(), "k = 7\nweighted = true\n");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParameterSettings {
+    values: Vec<(String, String)>,
+}
+
+impl ParameterSettings {
+    /// create settings that leave every parameter at its default value
+    pub fn new() -> Self {
+        ParameterSettings::default()
+    }
+
+    /// set the value of a parameter, replacing the value it was set to before
+    pub fn set<S: Into<String>, V: fmt::Display>(mut self, name: S, value: V) -> Self {
+        let name = name.into();
+        let value = value.to_string();
+        match self.values.iter_mut().find(|(n, _)| *n == name) {
+            Some(setting) => setting.1 = value,
+            None => self.values.push((name, value)),
+        }
+        self
+    }
+
+    /// get the value a parameter is set to
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// iterate over the names and values of the parameters that are set
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// get the number of parameters that are set
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// return `true` if every parameter keeps its default value
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// check that every parameter that is set is declared by the flow
+    pub(crate) fn check(&self, flow: &FlowMetadata) -> Result<()> {
+        for (name, _) in &self.values {
+            if !flow.parameters.iter().any(|p| p.name == *name) {
+                return Err(Error::InvalidFlow {
+                    name: flow.name.clone(),
+                    message: format!("the flow has no parameter {:?}", name),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One `name = value` line per parameter
+impl fmt::Display for ParameterSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, value) in &self.values {
+            writeln!(f, "{} = {}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn parameter_settings() {
+    use super::FlowBuilder;
+
+    let flow = FlowBuilder::new("rust.knn", "0.1")
+        .parameter("k", "int", 5)
+        .build()
+        .unwrap();
+    let settings = ParameterSettings::new().set("k", 3).set("k", 7);
+    assert_eq!(settings.iter().collect::<Vec<_>>(), vec![("k", "7")]);
+    assert!(settings.check(&flow).is_ok());
+    assert!(matches!(
+        settings.set("metric", "cosine").check(&flow),
+        Err(Error::InvalidFlow { .. })
+    ));
+}
@@ -0,0 +1,266 @@
+//! The predictions file of a run
+//!
+//! OpenML stores the predictions of a run as an ARFF file with one row per prediction: the repeat,
+//! fold and row id it belongs to, the prediction and the correct value. Files of classification
+//! tasks also hold the confidence in every class. The file is uploaded together with the
+//! description of the run.
+
+use std::fmt::Write;
+
+use crate::anonymize::quote;
+use crate::error::{Error, Result};
+use crate::tasks::ClassLabels;
+
+/// Predictions that can be stored in a predictions file
+pub trait RunPrediction {
+    /// get the predicted class code of a classification task, or the predicted value of a
+    /// regression task
+    fn value(&self) -> f64;
+
+    /// get the confidence in every class, if the flow predicted them. Predictions without
+    /// confidences are stored with a confidence of 1 in the predicted class.
+    fn confidences(&self) -> Option<Vec<f64>> {
+        None
+    }
+}
+
+macro_rules! run_prediction_number {
+    ($($t:ty),*) => {
+        $(
+            impl RunPrediction for $t {
+                fn value(&self) -> f64 {
+                    *self as f64
+                }
+            }
+        )*
+    };
+}
+
+run_prediction_number!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64);
+
+/// Class probabilities; the class with the highest probability is predicted
+impl RunPrediction for Vec<f64> {
+    fn value(&self) -> f64 {
+        let mut best = 0;
+        for (code, p) in self.iter().enumerate() {
+            if *p > self[best] {
+                best = code;
+            }
+        }
+        best as f64
+    }
+
+    fn confidences(&self) -> Option<Vec<f64>> {
+        Some(self.clone())
+    }
+}
+
+/// A single prediction of a run
+#[derive(Debug, Clone, PartialEq)]
+pub struct PredictionRow {
+    pub repeat: usize,
+    pub fold: usize,
+    /// index of the predicted row in the data set
+    pub row_id: usize,
+    pub value: f64,
+    pub confidences: Option<Vec<f64>>,
+}
+
+/// The predictions of a flow on every fold of a task, in the order in which they were made
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunPredictions {
+    rows: Vec<PredictionRow>,
+}
+
+impl RunPredictions {
+    /// create an empty list of predictions
+    pub fn new() -> Self {
+        RunPredictions::default()
+    }
+
+    /// add the prediction of a row in the testing set of a fold
+    pub fn record<P: RunPrediction + ?Sized>(
+        &mut self,
+        repeat: usize,
+        fold: usize,
+        row_id: usize,
+        prediction: &P,
+    ) {
+        self.rows.push(PredictionRow {
+            repeat,
+            fold,
+            row_id,
+            value: prediction.value(),
+            confidences: prediction.confidences(),
+        });
+    }
+
+    /// get all predictions
+    pub fn rows(&self) -> &[PredictionRow] {
+        &self.rows
+    }
+
+    /// get the number of predictions
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// return `true` if nothing was predicted
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// write the predictions of a classification task. `truth` holds the correct class code of
+    /// every row of the data set.
+    pub(crate) fn classification_arff(
+        &self,
+        task_id: &str,
+        labels: &ClassLabels,
+        truth: &[Option<usize>],
+    ) -> Result<String> {
+        let n_classes = labels.len();
+        let classes: Vec<String> = (0..n_classes)
+            .map(|code| quote(labels.label(code).unwrap_or_default()))
+            .collect();
+        let nominal = format!("{{{}}}", classes.join(","));
+
+        let mut out = header(task_id);
+        for code in 0..n_classes {
+            let name = format!("confidence.{}", labels.label(code).unwrap_or_default());
+            writeln!(out, "@ATTRIBUTE {} NUMERIC", quote(&name)).unwrap();
+        }
+        writeln!(out, "@ATTRIBUTE prediction {}", nominal).unwrap();
+        writeln!(out, "@ATTRIBUTE correct {}", nominal).unwrap();
+        writeln!(out, "@DATA").unwrap();
+
+        for row in &self.rows {
+            let code = row.value.round();
+            if !(code >= 0.0 && code < n_classes as f64) {
+                return Err(invalid(
+                    row,
+                    format!(
+                        "class code {} is outside of the {} classes",
+                        row.value, n_classes
+                    ),
+                ));
+            }
+            let code = code as usize;
+            let confidences = match &row.confidences {
+                Some(confidences) if confidences.len() != n_classes => {
+                    return Err(invalid(
+                        row,
+                        format!(
+                            "expected confidences of {} classes but found {}",
+                            n_classes,
+                            confidences.len()
+                        ),
+                    ))
+                }
+                Some(confidences) => confidences.clone(),
+                None => (0..n_classes)
+                    .map(|c| if c == code { 1.0 } else { 0.0 })
+                    .collect(),
+            };
+            let correct = truth
+                .get(row.row_id)
+                .cloned()
+                .flatten()
+                .and_then(|c| classes.get(c))
+                .map_or("?", String::as_str);
+
+            write!(out, "{},{},{}", row.repeat, row.fold, row.row_id).unwrap();
+            for confidence in confidences {
+                write!(out, ",{}", confidence).unwrap();
+            }
+            writeln!(out, ",{},{}", classes[code], correct).unwrap();
+        }
+        Ok(out)
+    }
+
+    /// write the predictions of a regression task. `truth` holds the correct value of every row of
+    /// the data set.
+    pub(crate) fn regression_arff(&self, task_id: &str, truth: &[Option<f64>]) -> Result<String> {
+        let mut out = header(task_id);
+        writeln!(out, "@ATTRIBUTE prediction NUMERIC").unwrap();
+        writeln!(out, "@ATTRIBUTE truth NUMERIC").unwrap();
+        writeln!(out, "@DATA").unwrap();
+
+        for row in &self.rows {
+            if !row.value.is_finite() {
+                return Err(invalid(
+                    row,
+                    format!("prediction {} is not finite", row.value),
+                ));
+            }
+            let correct = truth
+                .get(row.row_id)
+                .cloned()
+                .flatten()
+                .map_or("?".to_owned(), |y| y.to_string());
+            writeln!(
+                out,
+                "{},{},{},{},{}",
+                row.repeat, row.fold, row.row_id, row.value, correct
+            )
+            .unwrap();
+        }
+        Ok(out)
+    }
+}
+
+/// write the relation and the attributes that locate a prediction
+fn header(task_id: &str) -> String {
+    let mut out = String::new();
+    writeln!(out, "@RELATION openml_task_{}_predictions", task_id).unwrap();
+    writeln!(out, "@ATTRIBUTE repeat NUMERIC").unwrap();
+    writeln!(out, "@ATTRIBUTE fold NUMERIC").unwrap();
+    writeln!(out, "@ATTRIBUTE row_id NUMERIC").unwrap();
+    out
+}
+
+fn invalid(row: &PredictionRow, message: String) -> Error {
+    Error::InvalidPredictions {
+        repeat: row.repeat,
+        fold: row.fold,
+        row: Some(row.row_id),
+        message,
+    }
+}
+
+#[test]
+fn predictions_arff() {
+    use arff::dynamic::DataSet;
+
+    let labels = ClassLabels::new(vec!["no".to_owned(), "yes".to_owned()]);
+    let mut predictions = RunPredictions::new();
+    predictions.record(0, 0, 3, &1u8);
+    predictions.record(0, 1, 0, &vec![0.75, 0.25]);
+
+    let arff = predictions
+        .classification_arff("59", &labels, &[Some(0), None, None, Some(1)])
+        .unwrap();
+    assert!(arff.starts_with("@RELATION openml_task_59_predictions\n"));
+    assert!(arff.contains("@ATTRIBUTE confidence.yes NUMERIC\n"));
+    assert!(arff.ends_with("@DATA\n0,0,3,0,1,yes,yes\n0,1,0,0.75,0.25,no,no\n"));
+    let data = DataSet::from_str(&arff).unwrap();
+    assert_eq!((data.n_rows(), data.n_cols()), (2, 7));
+
+    predictions.record(1, 0, 2, &vec![0.5]);
+    assert!(matches!(
+        predictions.classification_arff("59", &labels, &[]),
+        Err(Error::InvalidPredictions {
+            repeat: 1,
+            row: Some(2),
+            ..
+        })
+    ));
+
+    let mut predictions = RunPredictions::new();
+    predictions.record(0, 0, 1, &2.5);
+    let arff = predictions
+        .regression_arff("2280", &[None, Some(3.0)])
+        .unwrap();
+    assert!(arff.ends_with("@ATTRIBUTE truth NUMERIC\n@DATA\n0,0,1,2.5,3\n"));
+    predictions.record(0, 0, 0, &f64::NAN);
+    assert!(predictions.regression_arff("2280", &[]).is_err());
+}
@@ -0,0 +1,176 @@
+//! Results of runs that are kept locally
+//!
+//! A run is exported as a directory with two files: `run.json` describes the task, the flow, its
+//! hyperparameter settings, the seed of generated splits and the computed measures, and
+//! `predictions.arff` holds every prediction in OpenML's format, together with the correct value.
+//! The measures can thus be recomputed from the export alone.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::error::Result;
+use crate::measures::{evaluate, Measure};
+
+use super::flow::FlowMetadata;
+use super::parameters::ParameterSettings;
+use super::predictions::RunPredictions;
+
+/// The predictions of a flow on a task, with everything that is needed to archive and re-verify
+/// them
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunResult {
+    pub task_id: String,
+    pub flow: FlowMetadata,
+    pub parameter_settings: ParameterSettings,
+    /// seed in effect for generated splits, which tasks without pre-defined splits use; see
+    /// `set_random_seed`
+    pub seed: u64,
+    pub predictions: RunPredictions,
+    /// measures computed from the predictions
+    pub measures: Vec<(Measure, f64)>,
+    pub(crate) predictions_arff: String,
+}
+
+impl RunResult {
+    /// get the value of a single measure, if it was computed
+    pub fn measure(&self, measure: Measure) -> Option<f64> {
+        self.measures
+            .iter()
+            .find(|(m, _)| *m == measure)
+            .map(|&(_, value)| value)
+    }
+
+    /// get the predictions in the ARFF format of OpenML's run files
+    pub fn predictions_arff(&self) -> &str {
+        &self.predictions_arff
+    }
+
+    /// write `run.json` and `predictions.arff` to a directory, which is created if it does not
+    /// exist. Files of an earlier export to the same directory are replaced.
+    pub fn export<P: AsRef<Path>>(&self, dir: P) -> Result<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        fs::write(dir.join("run.json"), self.to_json()?)?;
+        fs::write(dir.join("predictions.arff"), &self.predictions_arff)?;
+        Ok(())
+    }
+
+    fn to_json(&self) -> Result<String> {
+        let flow = &self.flow;
+        let run = RunFile {
+            task_id: &self.task_id,
+            flow: FlowFile {
+                name: &flow.name,
+                external_version: &flow.external_version,
+                description: &flow.description,
+                dependencies: flow.dependencies.as_deref(),
+                parameters: flow
+                    .parameters
+                    .iter()
+                    .map(|p| ParameterFile {
+                        name: &p.name,
+                        data_type: &p.data_type,
+                        default_value: p.default_value.as_deref(),
+                    })
+                    .collect(),
+            },
+            parameter_settings: self.parameter_settings.iter().collect(),
+            seed: self.seed,
+            n_predictions: self.predictions.len(),
+            measures: self
+                .measures
+                .iter()
+                .map(|&(m, value)| (m.to_openml_name(), value))
+                .collect(),
+        };
+        Ok(serde_json::to_string_pretty(&run)?)
+    }
+}
+
+/// compute measures from recorded predictions and the correct value of every row of the data
+/// set. Predictions of rows without a correct value are not scored.
+pub(crate) fn evaluate_predictions(
+    predictions: &RunPredictions,
+    truth: &[Option<f64>],
+    measures: &[Measure],
+) -> Vec<(Measure, f64)> {
+    let (known, predicted): (Vec<f64>, Vec<f64>) = predictions
+        .rows()
+        .iter()
+        .filter_map(|row| Some((truth.get(row.row_id).cloned().flatten()?, row.value)))
+        .unzip();
+    measures
+        .iter()
+        .map(|&m| (m, evaluate(m, &known, &predicted)))
+        .collect()
+}
+
+/// Contents of `run.json`
+#[derive(Serialize)]
+struct RunFile<'a> {
+    task_id: &'a str,
+    flow: FlowFile<'a>,
+    parameter_settings: BTreeMap<&'a str, &'a str>,
+    seed: u64,
+    n_predictions: usize,
+    measures: BTreeMap<&'static str, f64>,
+}
+
+#[derive(Serialize)]
+struct FlowFile<'a> {
+    name: &'a str,
+    external_version: &'a str,
+    description: &'a str,
+    dependencies: Option<&'a str>,
+    parameters: Vec<ParameterFile<'a>>,
+}
+
+#[derive(Serialize)]
+struct ParameterFile<'a> {
+    name: &'a str,
+    data_type: &'a str,
+    default_value: Option<&'a str>,
+}
+
+#[test]
+fn export_run() {
+    use super::FlowBuilder;
+
+    let mut predictions = RunPredictions::new();
+    predictions.record(0, 0, 0, &1.0);
+    predictions.record(0, 0, 1, &4.0);
+    predictions.record(0, 1, 2, &0.0);
+    let truth = [Some(1.0), Some(2.0), None];
+    let measures = evaluate_predictions(&predictions, &truth, &[Measure::Mae]);
+    assert_eq!(measures, vec![(Measure::Mae, 1.0)]);
+
+    let run = RunResult {
+        task_id: "2280".to_owned(),
+        flow: FlowBuilder::new("rust.mean", "0.1")
+            .parameter("shrinkage", "float", 0.5)
+            .build()
+            .unwrap(),
+        parameter_settings: ParameterSettings::new().set("shrinkage", 0.25),
+        seed: 7,
+        predictions: predictions.clone(),
+        measures,
+        predictions_arff: predictions.regression_arff("2280", &truth).unwrap(),
+    };
+    assert_eq!(run.measure(Measure::Mae), Some(1.0));
+
+    let dir = std::env::temp_dir().join(format!("openml-test-run-{}", std::process::id()));
+    run.export(&dir).unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.join("run.json")).unwrap()).unwrap();
+    assert_eq!(json["task_id"], "2280");
+    assert_eq!(json["flow"]["parameters"][0]["default_value"], "0.5");
+    assert_eq!(json["parameter_settings"]["shrinkage"], "0.25");
+    assert_eq!(json["seed"], 7);
+    assert_eq!(json["measures"]["mean_absolute_error"], 1.0);
+    assert_eq!(
+        fs::read_to_string(dir.join("predictions.arff")).unwrap(),
+        run.predictions_arff()
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
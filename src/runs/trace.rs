@@ -0,0 +1,193 @@
+//! Traces of hyperparameter searches
+//!
+//! Flows that tune their own hyperparameters, e.g. with a grid search on the training set of
+//! every fold, can record every setting they tried. OpenML stores these as the trace of a run: an
+//! ARFF file with one row per repeat, fold and iteration, the evaluation of the setting and
+//! whether it was selected for predicting the test set.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+use crate::anonymize::quote;
+use crate::error::{Error, Result};
+use crate::tasks::FoldContext;
+
+use super::flow::FlowMetadata;
+use super::parameters::ParameterSettings;
+
+/// A hyperparameter setting that was tried on the training set of a fold
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceIteration {
+    pub repeat: usize,
+    pub fold: usize,
+    /// index of the setting within the search on this fold
+    pub iteration: usize,
+    pub parameters: ParameterSettings,
+    /// score of the setting, e.g. its accuracy on an inner validation set
+    pub evaluation: f64,
+    /// whether the setting was used to predict the testing set of the fold
+    pub selected: bool,
+}
+
+/// Records the settings a flow tried during a hyperparameter search. Flows record into it through
+/// a shared reference: the recorder is created before the task is run, captured by the flow, and
+/// passed to `run_and_upload_traced` together with the flow.
+#[derive(Debug, Default)]
+pub struct TraceRecorder {
+    iterations: Mutex<Vec<TraceIteration>>,
+}
+
+impl TraceRecorder {
+    /// create an empty trace
+    pub fn new() -> Self {
+        TraceRecorder::default()
+    }
+
+    /// record a setting that was tried on the training set of the fold in `context`. Exactly
+    /// one setting per fold must be `selected`.
+    pub fn record(
+        &self,
+        context: &FoldContext,
+        iteration: usize,
+        parameters: &ParameterSettings,
+        evaluation: f64,
+        selected: bool,
+    ) {
+        self.push(TraceIteration {
+            repeat: context.repeat(),
+            fold: context.fold(),
+            iteration,
+            parameters: parameters.clone(),
+            evaluation,
+            selected,
+        });
+    }
+
+    /// add an iteration whose repeat and fold are given explicitly
+    pub fn push(&self, iteration: TraceIteration) {
+        self.iterations.lock().unwrap().push(iteration);
+    }
+
+    /// get all iterations, in the order in which they were recorded
+    pub fn iterations(&self) -> Vec<TraceIteration> {
+        self.iterations.lock().unwrap().clone()
+    }
+
+    /// return `true` if nothing was recorded
+    pub fn is_empty(&self) -> bool {
+        self.iterations.lock().unwrap().is_empty()
+    }
+
+    /// check that every recorded parameter is declared by the flow, and that exactly one setting
+    /// was selected on every fold with iterations
+    pub(crate) fn check(&self, flow: &FlowMetadata) -> Result<()> {
+        let iterations = self.iterations.lock().unwrap();
+        let mut selected = BTreeMap::new();
+        for iteration in iterations.iter() {
+            iteration.parameters.check(flow)?;
+            *selected
+                .entry((iteration.repeat, iteration.fold))
+                .or_insert(0) += iteration.selected as usize;
+        }
+        match selected.into_iter().find(|&(_, n)| n != 1) {
+            Some(((repeat, fold), n)) => Err(Error::InvalidTrace {
+                repeat,
+                fold,
+                message: format!("{} settings are selected instead of one", n),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// write the trace in the ARFF format of OpenML's trace files. Every parameter that was set
+    /// in any iteration gets a column; iterations that did not set it hold a missing value.
+    pub(crate) fn trace_arff(&self, task_id: &str) -> String {
+        let iterations = self.iterations.lock().unwrap();
+        let mut names: Vec<&str> = vec![];
+        for iteration in iterations.iter() {
+            for (name, _) in iteration.parameters.iter() {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+
+        let mut out = String::new();
+        writeln!(out, "@RELATION openml_task_{}_trace", task_id).unwrap();
+        writeln!(out, "@ATTRIBUTE repeat NUMERIC").unwrap();
+        writeln!(out, "@ATTRIBUTE fold NUMERIC").unwrap();
+        writeln!(out, "@ATTRIBUTE iteration NUMERIC").unwrap();
+        writeln!(out, "@ATTRIBUTE evaluation NUMERIC").unwrap();
+        writeln!(out, "@ATTRIBUTE selected {{true,false}}").unwrap();
+        for name in &names {
+            let column = format!("parameter_{}", name);
+            writeln!(out, "@ATTRIBUTE {} STRING", quote(&column)).unwrap();
+        }
+        writeln!(out, "@DATA").unwrap();
+
+        for iteration in iterations.iter() {
+            write!(
+                out,
+                "{},{},{},{},{}",
+                iteration.repeat,
+                iteration.fold,
+                iteration.iteration,
+                iteration.evaluation,
+                iteration.selected
+            )
+            .unwrap();
+            for name in &names {
+                match iteration.parameters.get(name) {
+                    Some(value) => write!(out, ",{}", quote(value)).unwrap(),
+                    None => write!(out, ",?").unwrap(),
+                }
+            }
+            writeln!(out).unwrap();
+        }
+        out
+    }
+}
+
+#[test]
+fn trace_arff() {
+    use super::FlowBuilder;
+
+    let iteration = |fold, iteration, k: Option<u32>, selected| TraceIteration {
+        repeat: 0,
+        fold,
+        iteration,
+        parameters: match k {
+            Some(k) => ParameterSettings::new().set("k", k),
+            None => ParameterSettings::new(),
+        },
+        evaluation: 0.5,
+        selected,
+    };
+    let trace = TraceRecorder::new();
+    trace.push(iteration(0, 0, Some(1), false));
+    trace.push(iteration(0, 1, None, true));
+    trace.push(iteration(1, 0, Some(3), true));
+
+    let arff = trace.trace_arff("59");
+    assert!(arff.starts_with("@RELATION openml_task_59_trace\n"));
+    assert!(arff.ends_with(
+        "@ATTRIBUTE parameter_k STRING\n@DATA\n\
+         0,0,0,0.5,false,1\n\
+         0,0,1,0.5,true,?\n\
+         0,1,0,0.5,true,3\n"
+    ));
+    let data = arff::dynamic::DataSet::from_str(&arff).unwrap();
+    assert_eq!((data.n_rows(), data.n_cols()), (3, 6));
+
+    let flow = FlowBuilder::new("rust.knn", "0.1")
+        .parameter("k", "int", 5)
+        .build()
+        .unwrap();
+    assert!(trace.check(&flow).is_ok());
+    trace.push(iteration(1, 1, Some(5), true));
+    assert!(matches!(
+        trace.check(&flow),
+        Err(Error::InvalidTrace { fold: 1, .. })
+    ));
+}
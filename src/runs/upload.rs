@@ -0,0 +1,208 @@
+//! Runs uploaded to OpenML
+
+use std::fmt;
+
+use crate::error::{Error, Result};
+use crate::openml_api::FlowId;
+
+use super::parameters::ParameterSettings;
+use super::xml::{escape, OML_NAMESPACE};
+
+/// A run that was stored on the OpenML server
+#[derive(Debug, Clone, PartialEq)]
+pub struct UploadedRun {
+    /// id of the run on the server
+    pub run_id: u32,
+    /// id of the task the run belongs to
+    pub task_id: String,
+    /// id of the flow the run belongs to; it was created if the server did not know the flow
+    pub flow_id: FlowId,
+    /// values of the flow's hyperparameters in the run
+    pub parameter_settings: ParameterSettings,
+    /// address of the run's page on the OpenML website
+    pub url: String,
+}
+
+/// A summary of the run with the values of its hyperparameters
+impl fmt::Display for UploadedRun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "run {} of flow {} on task {}: {}",
+            self.run_id, self.flow_id, self.task_id, self.url
+        )?;
+        for (name, value) in self.parameter_settings.iter() {
+            writeln!(f, "  {} = {}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Who can see an uploaded flow or run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Visibility {
+    /// everyone; the default
+    #[default]
+    Public,
+    /// only the owner of the API key and the people they share it with
+    Private,
+}
+
+/// Labels and the visibility of every flow and run a client uploads; see
+/// `OpenMLBuilder::upload_options`
+///
+/// ```
+/// use openml::{OpenML, UploadOptions, Visibility};
+///
+/// let options = UploadOptions::new()
+///     .with_tag("study_42")
+///     .with_tag("rust-bench")
+///     .with_visibility(Visibility::Private);
+/// let openml = OpenML::builder().upload_options(options)?.build();
+/// # Ok::<(), openml::Error>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UploadOptions {
+    tags: Vec<String>,
+    visibility: Visibility,
+}
+
+impl UploadOptions {
+    /// create options for public uploads without tags
+    pub fn new() -> Self {
+        UploadOptions::default()
+    }
+
+    /// add a tag. Tags consist of letters, digits, `_`, `-` and `.`, and are at most 64
+    /// characters long.
+    pub fn with_tag<S: Into<String>>(mut self, tag: S) -> Self {
+        let tag = tag.into();
+        if !self.tags.contains(&tag) {
+            self.tags.push(tag);
+        }
+        self
+    }
+
+    /// set who can see uploads. Servers that do not support private uploads reject them, and the
+    /// upload fails with the error of the server instead of making the flow or run public.
+    pub fn with_visibility(mut self, visibility: Visibility) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
+    /// get the tags
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// get the visibility
+    pub fn visibility(&self) -> Visibility {
+        self.visibility
+    }
+
+    /// check that every tag is accepted by the server; fails with `Error::InvalidTag` otherwise
+    pub(crate) fn check(&self) -> Result<()> {
+        let valid = |c: char| c.is_ascii_alphanumeric() || "_-.".contains(c);
+        match self
+            .tags
+            .iter()
+            .find(|tag| tag.is_empty() || tag.len() > 64 || !tag.chars().all(valid))
+        {
+            Some(tag) => Err(Error::InvalidTag { tag: tag.clone() }),
+            None => Ok(()),
+        }
+    }
+
+    /// write the elements that are appended to the XML description of a flow or run
+    pub(crate) fn to_xml(&self) -> String {
+        let mut xml = String::new();
+        for tag in &self.tags {
+            xml += &format!("  <oml:tag>{}</oml:tag>\n", escape(tag));
+        }
+        if self.visibility == Visibility::Private {
+            xml += "  <oml:visibility>private</oml:visibility>\n";
+        }
+        xml
+    }
+}
+
+/// write the XML description that is uploaded together with the predictions of a run
+pub(crate) fn run_xml(
+    task_id: &str,
+    flow_id: FlowId,
+    parameters: &ParameterSettings,
+    options: &UploadOptions,
+) -> String {
+    let mut xml = format!("<oml:run xmlns:oml=\"{}\">\n", OML_NAMESPACE);
+    xml += &format!("  <oml:task_id>{}</oml:task_id>\n", escape(task_id));
+    xml += &format!("  <oml:flow_id>{}</oml:flow_id>\n", flow_id);
+    for (name, value) in parameters.iter() {
+        xml += "  <oml:parameter_setting>\n";
+        xml += &format!("    <oml:name>{}</oml:name>\n", escape(name));
+        xml += &format!("    <oml:value>{}</oml:value>\n", escape(value));
+        xml += "  </oml:parameter_setting>\n";
+    }
+    xml += &options.to_xml();
+    xml += "</oml:run>\n";
+    xml
+}
+
+#[test]
+fn run_description() {
+    let parameters = ParameterSettings::new();
+    let options = UploadOptions::new();
+    assert_eq!(
+        run_xml("59", FlowId(8353), &parameters, &options),
+        "<oml:run xmlns:oml=\"http://openml.org/openml\">\n  \
+         <oml:task_id>59</oml:task_id>\n  \
+         <oml:flow_id>8353</oml:flow_id>\n\
+         </oml:run>\n"
+    );
+
+    let parameters = parameters.set("metric", "l<2>");
+    assert!(
+        run_xml("59", FlowId(8353), &parameters, &options).ends_with(
+            "  <oml:parameter_setting>\n    \
+         <oml:name>metric</oml:name>\n    \
+         <oml:value>l&lt;2&gt;</oml:value>\n  \
+         </oml:parameter_setting>\n\
+         </oml:run>\n"
+        )
+    );
+
+    let run = UploadedRun {
+        run_id: 10437,
+        task_id: "59".to_owned(),
+        flow_id: FlowId(8353),
+        parameter_settings: parameters,
+        url: "https://www.openml.org/r/10437".to_owned(),
+    };
+    assert_eq!(
+        run.to_string(),
+        "run 10437 of flow 8353 on task 59: https://www.openml.org/r/10437\n  metric = l<2>\n"
+    );
+}
+
+#[test]
+fn upload_options() {
+    let options = UploadOptions::new()
+        .with_tag("study_42")
+        .with_tag("rust-bench")
+        .with_tag("study_42")
+        .with_visibility(Visibility::Private);
+    assert!(options.check().is_ok());
+    assert_eq!(
+        run_xml("59", FlowId(8353), &ParameterSettings::new(), &options),
+        "<oml:run xmlns:oml=\"http://openml.org/openml\">\n  \
+         <oml:task_id>59</oml:task_id>\n  \
+         <oml:flow_id>8353</oml:flow_id>\n  \
+         <oml:tag>study_42</oml:tag>\n  \
+         <oml:tag>rust-bench</oml:tag>\n  \
+         <oml:visibility>private</oml:visibility>\n\
+         </oml:run>\n"
+    );
+    match UploadOptions::new().with_tag("no spaces").check() {
+        Err(Error::InvalidTag { tag }) => assert_eq!(tag, "no spaces"),
+        other => panic!("unexpected {:?}", other),
+    }
+}
@@ -0,0 +1,260 @@
+//! Verification of the evaluations of runs
+//!
+//! The server evaluates the predictions of every run it stores. Recomputing these measures from
+//! the predictions file with this crate's measures, and comparing the results, validates both:
+//! a discrepancy points to a measure that is defined differently, or to a bug.
+
+use std::fmt;
+
+use crate::data_frame::{Column, DataFrame};
+use crate::error::{Error, Result};
+use crate::measures::{evaluate, Measure};
+
+/// Measures that are verified on classification runs
+const CLASSIFICATION_MEASURES: &[Measure] = &[
+    Measure::Accuracy,
+    Measure::Precision,
+    Measure::Recall,
+    Measure::F1,
+    Measure::Kappa,
+    Measure::AreaUnderRocCurve,
+    Measure::MeanClassComplexity,
+    Measure::MatthewsCorrelation,
+];
+
+/// Measures that are verified on regression runs
+const REGRESSION_MEASURES: &[Measure] = &[
+    Measure::Rmse,
+    Measure::Mae,
+    Measure::RelativeAbsoluteError,
+    Measure::RootRelativeSquaredError,
+    Measure::RSquared,
+];
+
+/// A measure as computed by the server and by this crate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeasureComparison {
+    pub measure: Measure,
+    pub server: f64,
+    pub local: f64,
+}
+
+impl MeasureComparison {
+    /// get the absolute difference between the two values
+    pub fn difference(&self) -> f64 {
+        (self.local - self.server).abs()
+    }
+
+    /// check whether the values differ by more than `tolerance`. Two NaN values agree.
+    pub fn differs(&self, tolerance: f64) -> bool {
+        !(self.difference() <= tolerance || self.local.is_nan() && self.server.is_nan())
+    }
+}
+
+/// The measures of a run that were recomputed from its predictions
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunVerification {
+    pub run_id: u32,
+    pub task_id: String,
+    /// every measure the server computed for the whole run that this crate can compute from the
+    /// predictions file
+    pub comparisons: Vec<MeasureComparison>,
+}
+
+impl RunVerification {
+    /// get the measures whose values differ by more than `tolerance`
+    pub fn discrepancies(&self, tolerance: f64) -> Vec<&MeasureComparison> {
+        self.comparisons
+            .iter()
+            .filter(|c| c.differs(tolerance))
+            .collect()
+    }
+}
+
+/// A report with one line per measure; measures that differ by more than `1e-6` are marked
+impl fmt::Display for RunVerification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "run {} of task {}", self.run_id, self.task_id)?;
+        for c in &self.comparisons {
+            writeln!(
+                f,
+                "  {} {}: server {}, local {}",
+                if c.differs(1e-6) { "!" } else { " " },
+                c.measure.to_openml_name(),
+                c.server,
+                c.local
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// recompute the measures in `server` from a predictions file and compare them
+pub(crate) fn verify_predictions(
+    run_id: u32,
+    task_id: String,
+    predictions_arff: &str,
+    server: &[(Measure, f64)],
+) -> Result<RunVerification> {
+    let frame = DataFrame::parse_arff(predictions_arff)?;
+    let prediction = column(&frame, "prediction")?;
+
+    let mut comparisons = vec![];
+    if let Some(correct) = frame.column("correct") {
+        let (known, predicted, probabilities) = classification_values(&frame, prediction, correct)?;
+        for &(measure, server) in server {
+            if !CLASSIFICATION_MEASURES.contains(&measure) {
+                continue;
+            }
+            let local = match measure {
+                Measure::AreaUnderRocCurve | Measure::MeanClassComplexity => match &probabilities {
+                    Some(probabilities) => evaluate(measure, &known, probabilities),
+                    None => continue,
+                },
+                _ => evaluate(measure, &known, &predicted),
+            };
+            comparisons.push(MeasureComparison {
+                measure,
+                server,
+                local,
+            });
+        }
+    } else {
+        let truth = numeric(column(&frame, "truth")?)?;
+        let prediction = numeric(prediction)?;
+        let (known, predicted): (Vec<f64>, Vec<f64>) = truth
+            .iter()
+            .zip(prediction)
+            .filter_map(|(&known, &predicted)| Some((known?, predicted?)))
+            .unzip();
+        for &(measure, server) in server {
+            if REGRESSION_MEASURES.contains(&measure) {
+                comparisons.push(MeasureComparison {
+                    measure,
+                    server,
+                    local: evaluate(measure, &known, &predicted),
+                });
+            }
+        }
+    }
+
+    Ok(RunVerification {
+        run_id,
+        task_id,
+        comparisons,
+    })
+}
+
+/// get the correct and predicted class codes of the rows with both, and for binary tasks the
+/// confidence in the second class if the file has it. Codes follow the classes of the
+/// `prediction` column.
+#[allow(clippy::type_complexity)]
+fn classification_values(
+    frame: &DataFrame,
+    prediction: &Column,
+    correct: &Column,
+) -> Result<(Vec<f64>, Vec<f64>, Option<Vec<f64>>)> {
+    let (levels, predicted_codes) = nominal(prediction)?;
+    let (correct_levels, correct_codes) = nominal(correct)?;
+    // classes that are never predicted get codes after those of the predicted classes
+    let correct_code = |code: usize| {
+        levels
+            .iter()
+            .position(|label| *label == correct_levels[code])
+            .unwrap_or(levels.len() + code)
+    };
+    let confidences = match levels {
+        [_, positive] => frame
+            .column(&format!("confidence.{}", positive))
+            .and_then(Column::as_numeric),
+        _ => None,
+    };
+
+    let mut known = vec![];
+    let mut predicted = vec![];
+    let mut probabilities = vec![];
+    for (row, (&c, &p)) in correct_codes.iter().zip(predicted_codes).enumerate() {
+        if let (Some(c), Some(p)) = (c, p) {
+            known.push(correct_code(c) as f64);
+            predicted.push(p as f64);
+            if let Some(confidences) = confidences {
+                probabilities.push(confidences[row].unwrap_or(f64::NAN));
+            }
+        }
+    }
+    Ok((known, predicted, confidences.map(|_| probabilities)))
+}
+
+fn column<'a>(frame: &'a DataFrame, name: &str) -> Result<&'a Column> {
+    frame.column(name).ok_or_else(|| Error::ColumnError {
+        column: name.to_owned(),
+        message: "missing from the predictions file".to_owned(),
+    })
+}
+
+fn nominal(column: &Column) -> Result<(&[String], &[Option<usize>])> {
+    column.as_nominal().ok_or_else(|| Error::ColumnError {
+        column: column.name().to_owned(),
+        message: "expected nominal values".to_owned(),
+    })
+}
+
+fn numeric(column: &Column) -> Result<&[Option<f64>]> {
+    column.as_numeric().ok_or_else(|| Error::ColumnError {
+        column: column.name().to_owned(),
+        message: "expected numeric values".to_owned(),
+    })
+}
+
+#[test]
+fn verify_run_predictions() {
+    let arff = "@RELATION openml_task_59_predictions\n\
+                @ATTRIBUTE repeat NUMERIC\n\
+                @ATTRIBUTE fold NUMERIC\n\
+                @ATTRIBUTE row_id NUMERIC\n\
+                @ATTRIBUTE confidence.no NUMERIC\n\
+                @ATTRIBUTE confidence.yes NUMERIC\n\
+                @ATTRIBUTE prediction {no,yes}\n\
+                @ATTRIBUTE correct {yes,no}\n\
+                @DATA\n\
+                0,0,0,0.9,0.1,no,no\n\
+                0,0,1,0.2,0.8,yes,yes\n\
+                0,1,2,0.6,0.4,no,yes\n\
+                0,1,3,0.3,0.7,yes,?\n";
+    let server = [
+        (Measure::Accuracy, 2.0 / 3.0),
+        (Measure::AreaUnderRocCurve, 0.5),
+        (Measure::Rmse, 1.0),
+    ];
+    let verification = verify_predictions(10437, "59".to_owned(), arff, &server).unwrap();
+    let measures: Vec<_> = verification.comparisons.iter().map(|c| c.measure).collect();
+    assert_eq!(
+        measures,
+        vec![Measure::Accuracy, Measure::AreaUnderRocCurve]
+    );
+    assert!((verification.comparisons[0].local - 2.0 / 3.0).abs() < 1e-12);
+    assert_eq!(verification.comparisons[1].local, 1.0);
+
+    let discrepancies = verification.discrepancies(1e-6);
+    assert_eq!(discrepancies.len(), 1);
+    assert_eq!(discrepancies[0].measure, Measure::AreaUnderRocCurve);
+    assert!(verification
+        .to_string()
+        .contains("! area_under_roc_curve: server 0.5, local 1\n"));
+
+    let arff = "@RELATION openml_task_2280_predictions\n\
+                @ATTRIBUTE repeat NUMERIC\n\
+                @ATTRIBUTE fold NUMERIC\n\
+                @ATTRIBUTE row_id NUMERIC\n\
+                @ATTRIBUTE prediction NUMERIC\n\
+                @ATTRIBUTE truth NUMERIC\n\
+                @DATA\n\
+                0,0,0,1,2\n\
+                0,0,1,3,3\n";
+    let verification =
+        verify_predictions(1, "2280".to_owned(), arff, &[(Measure::Mae, 0.5)]).unwrap();
+    assert_eq!(
+        verification.discrepancies(0.0),
+        Vec::<&MeasureComparison>::new()
+    );
+}
@@ -0,0 +1,20 @@
+//! Writing the XML descriptions that OpenML expects with uploads
+
+/// Namespace of the elements of OpenML descriptions
+pub(crate) const OML_NAMESPACE: &str = "http://openml.org/openml";
+
+/// escape text for use in an XML element
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
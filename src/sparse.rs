@@ -0,0 +1,435 @@
+//! Sparse ARFF data sets
+//!
+//! In sparse ARFF files every data line lists only the values that differ from zero, e.g.
+//! `{0 1.5, 7 yes}`. Such files typically hold text data with thousands of columns, which would
+//! need far too much memory when expanded into dense columns. They are parsed into a compressed
+//! sparse row matrix instead.
+//!
+//! Nominal values are stored as their codes, so an omitted nominal value stands for the first
+//! level. Missing values (`?`) are stored as NaN.
+
+use crate::data_frame::{AttributeType, Column, ColumnValues, DataFrame, Feature};
+use crate::error::{Error, Result};
+
+/// A matrix of `f64` values in compressed sparse row (CSR) format
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparseMatrix {
+    n_cols: usize,
+    row_offsets: Vec<usize>,
+    indices: Vec<usize>,
+    values: Vec<f64>,
+}
+
+/// The stored entries of one row of a `SparseMatrix`, ordered by column
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SparseRow<'a> {
+    indices: &'a [usize],
+    values: &'a [f64],
+}
+
+impl SparseMatrix {
+    /// create a matrix with `n_cols` columns and no rows
+    pub fn new(n_cols: usize) -> Self {
+        SparseMatrix {
+            n_cols,
+            row_offsets: vec![0],
+            indices: vec![],
+            values: vec![],
+        }
+    }
+
+    /// append a row given as (column, value) pairs. Zeros are not stored. Panics if a column is
+    /// out of range.
+    pub fn push_row<I: IntoIterator<Item = (usize, f64)>>(&mut self, entries: I) {
+        let start = self.indices.len();
+        for (col, value) in entries {
+            assert!(col < self.n_cols, "column {} out of range", col);
+            if value != 0.0 {
+                self.indices.push(col);
+                self.values.push(value);
+            }
+        }
+
+        // sort the new entries by column
+        let mut row: Vec<_> = self.indices[start..]
+            .iter()
+            .cloned()
+            .zip(self.values[start..].iter().cloned())
+            .collect();
+        row.sort_by_key(|&(col, _)| col);
+        for (k, (col, value)) in row.into_iter().enumerate() {
+            self.indices[start + k] = col;
+            self.values[start + k] = value;
+        }
+
+        self.row_offsets.push(self.indices.len());
+    }
+
+    /// get the number of rows
+    pub fn n_rows(&self) -> usize {
+        self.row_offsets.len() - 1
+    }
+
+    /// get the number of columns
+    pub fn n_cols(&self) -> usize {
+        self.n_cols
+    }
+
+    /// get the number of stored (non-zero) entries
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /// get the stored entries of a row
+    pub fn row(&self, row: usize) -> SparseRow<'_> {
+        let range = self.row_offsets[row]..self.row_offsets[row + 1];
+        SparseRow {
+            indices: &self.indices[range.clone()],
+            values: &self.values[range],
+        }
+    }
+
+    /// iterate over all rows
+    pub fn rows(&self) -> impl Iterator<Item = SparseRow<'_>> {
+        (0..self.n_rows()).map(move |row| self.row(row))
+    }
+
+    /// get a single value
+    pub fn get(&self, row: usize, col: usize) -> f64 {
+        self.row(row).get(col)
+    }
+
+    /// get one column as a dense vector
+    pub fn col(&self, col: usize) -> Vec<f64> {
+        self.rows().map(|row| row.get(col)).collect()
+    }
+
+    /// return a copy of the matrix without the given column
+    pub fn without_col(&self, col: usize) -> SparseMatrix {
+        let mut result = SparseMatrix::new(self.n_cols - 1);
+        for row in self.rows() {
+            result.push_row(row.iter().filter(|&(c, _)| c != col).map(|(c, v)| {
+                if c > col {
+                    (c - 1, v)
+                } else {
+                    (c, v)
+                }
+            }));
+        }
+        result
+    }
+}
+
+impl<'a> SparseRow<'a> {
+    /// get the columns of the stored entries
+    pub fn indices(&self) -> &'a [usize] {
+        self.indices
+    }
+
+    /// get the values of the stored entries
+    pub fn values(&self) -> &'a [f64] {
+        self.values
+    }
+
+    /// iterate over (column, value) pairs
+    pub fn iter(&self) -> impl Iterator<Item = (usize, f64)> + 'a {
+        self.indices
+            .iter()
+            .cloned()
+            .zip(self.values.iter().cloned())
+    }
+
+    /// get a single value
+    pub fn get(&self, col: usize) -> f64 {
+        match self.indices.binary_search(&col) {
+            Ok(k) => self.values[k],
+            Err(_) => 0.0,
+        }
+    }
+
+    /// expand the row into a dense vector of length `n_cols`
+    pub fn to_dense(&self, n_cols: usize) -> Vec<f64> {
+        let mut dense = vec![0.0; n_cols];
+        for (col, value) in self.iter() {
+            dense[col] = value;
+        }
+        dense
+    }
+}
+
+/// A data set read from a sparse ARFF file
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparseDataSet {
+    name: String,
+    names: Vec<String>,
+    levels: Vec<Option<Vec<String>>>,
+    matrix: SparseMatrix,
+}
+
+impl SparseDataSet {
+    /// parse a sparse ARFF file. Only numeric and nominal attributes are supported.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(input: &str) -> Result<Self> {
+        let mut name = String::new();
+        let mut names = vec![];
+        let mut levels = vec![];
+        let mut matrix = None;
+
+        for (i, line) in input.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('%') {
+                continue;
+            }
+            let error = |message: &str| Error::SparseArffError {
+                line: line_number,
+                message: message.to_owned(),
+            };
+
+            let matrix = match matrix {
+                Some(ref mut matrix) => matrix,
+                None => {
+                    let (keyword, rest) = split_token(line);
+                    match keyword.to_lowercase().as_str() {
+                        "@relation" => name = unquote(rest.trim()).to_owned(),
+                        "@attribute" => {
+                            let (attr, kind) = split_token(rest.trim());
+                            names.push(unquote(attr).to_owned());
+                            levels.push(parse_type(kind.trim()).map_err(|m| error(&m))?);
+                        }
+                        "@data" => matrix = Some(SparseMatrix::new(names.len())),
+                        _ => return Err(error("unexpected line in header")),
+                    }
+                    continue;
+                }
+            };
+
+            let entries = line
+                .strip_prefix('{')
+                .and_then(|l| l.strip_suffix('}'))
+                .ok_or_else(|| error("data line is not sparse"))?;
+
+            let mut row = vec![];
+            for entry in split_values(entries).into_iter().filter(|e| !e.is_empty()) {
+                let (col, value) = split_token(entry);
+                let col: usize = col.parse().map_err(|_| error("invalid column index"))?;
+                if col >= names.len() {
+                    return Err(error("column index out of range"));
+                }
+                let value = unquote(value.trim());
+                let value = match (&levels[col], value) {
+                    (_, "?") => f64::NAN,
+                    (Some(levels), value) => levels
+                        .iter()
+                        .position(|l| l == value)
+                        .ok_or_else(|| error("unknown nominal value"))?
+                        as f64,
+                    (None, value) => value.parse().map_err(|_| error("invalid number"))?,
+                };
+                row.push((col, value));
+            }
+            matrix.push_row(row);
+        }
+
+        Ok(SparseDataSet {
+            name,
+            names,
+            levels,
+            matrix: matrix.unwrap_or_else(|| SparseMatrix::new(0)),
+        })
+    }
+
+    /// get the name of the data set
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// iterate over the column names
+    pub fn column_names(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(String::as_str)
+    }
+
+    /// get the index of a column
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| n == name)
+    }
+
+    /// get the levels of a nominal column, or `None` if the column is numeric
+    pub fn levels(&self, col: usize) -> Option<&[String]> {
+        self.levels[col].as_deref()
+    }
+
+    /// get the names and types of all columns
+    pub fn features(&self) -> Vec<Feature> {
+        self.names
+            .iter()
+            .zip(&self.levels)
+            .map(|(name, levels)| {
+                let attribute_type = match levels {
+                    Some(levels) => AttributeType::Nominal {
+                        levels: levels.clone(),
+                    },
+                    None => AttributeType::Numeric,
+                };
+                Feature::new(name, attribute_type)
+            })
+            .collect()
+    }
+
+    /// get the values as a sparse matrix; nominal values are represented by their codes
+    pub fn matrix(&self) -> &SparseMatrix {
+        &self.matrix
+    }
+
+    /// get one column as a dense, typed column
+    pub fn column(&self, col: usize) -> Column {
+        let values = self.matrix.col(col);
+        let values = match self.levels[col] {
+            Some(ref levels) => ColumnValues::Nominal {
+                levels: levels.clone(),
+                codes: values
+                    .into_iter()
+                    .map(|v| if v.is_nan() { None } else { Some(v as usize) })
+                    .collect(),
+            },
+            None => ColumnValues::Numeric(
+                values
+                    .into_iter()
+                    .map(|v| if v.is_nan() { None } else { Some(v) })
+                    .collect(),
+            ),
+        };
+        Column::new(&self.names[col], values)
+    }
+
+    /// return a copy of the data set without the given column
+    pub fn without_column(&self, col: usize) -> SparseDataSet {
+        let mut result = self.clone();
+        result.names.remove(col);
+        result.levels.remove(col);
+        result.matrix = self.matrix.without_col(col);
+        result
+    }
+
+    /// expand the data set into dense columns. This needs memory for every value, including the
+    /// zeros.
+    pub fn to_data_frame(&self) -> DataFrame {
+        let columns = (0..self.names.len()).map(|col| self.column(col)).collect();
+        DataFrame::new(&self.name, columns)
+    }
+}
+
+/// return `true` if the data section of an ARFF file is in sparse format
+pub(crate) fn is_sparse(input: &str) -> bool {
+    let mut lines = input
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('%'));
+    lines.any(|l| l.to_lowercase().starts_with("@data"));
+    lines.next().map(|l| l.starts_with('{')).unwrap_or(false)
+}
+
+/// parse an attribute type into the list of levels, or `None` for numeric attributes
+fn parse_type(kind: &str) -> std::result::Result<Option<Vec<String>>, String> {
+    if let Some(levels) = kind.strip_prefix('{').and_then(|k| k.strip_suffix('}')) {
+        return Ok(Some(
+            split_values(levels)
+                .into_iter()
+                .map(|l| unquote(l).to_owned())
+                .collect(),
+        ));
+    }
+    match kind.to_lowercase().as_str() {
+        "numeric" | "real" | "integer" => Ok(None),
+        _ => Err(format!(
+            "unsupported attribute type in sparse data: {}",
+            kind
+        )),
+    }
+}
+
+/// split off the first whitespace-separated token, which may be quoted
+pub(crate) fn split_token(s: &str) -> (&str, &str) {
+    let end = match s.chars().next() {
+        Some(q @ '\'') | Some(q @ '"') => s[1..].find(q).map(|i| i + 2),
+        _ => s.find(char::is_whitespace),
+    };
+    match end {
+        Some(end) => (&s[..end], &s[end..]),
+        None => (s, ""),
+    }
+}
+
+/// split a comma-separated list, ignoring commas within quotes
+pub(crate) fn split_values(s: &str) -> Vec<&str> {
+    let mut values = vec![];
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (None, '\'') | (None, '"') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, ',') => {
+                values.push(s[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    values.push(s[start..].trim());
+    values
+}
+
+pub(crate) fn unquote(s: &str) -> &str {
+    for q in &['\'', '"'] {
+        if let Some(inner) = s.strip_prefix(*q).and_then(|s| s.strip_suffix(*q)) {
+            return inner;
+        }
+    }
+    s
+}
+
+#[test]
+fn sparse_arff() {
+    let input = "% text data\n\
+                 @RELATION 'sparse test'\n\
+                 @ATTRIBUTE 'word a' NUMERIC\n\
+                 @ATTRIBUTE b NUMERIC\n\
+                 @ATTRIBUTE class {no,'yes please'}\n\
+                 @DATA\n\
+                 {0 1.5, 2 'yes please'}\n\
+                 {}\n\
+                 {1 ?, 0 2}\n";
+
+    assert!(is_sparse(input));
+    let data = SparseDataSet::from_str(input).unwrap();
+
+    assert_eq!(data.name(), "sparse test");
+    assert_eq!(
+        data.column_names().collect::<Vec<_>>(),
+        vec!["word a", "b", "class"]
+    );
+    assert_eq!(data.levels(2).unwrap().len(), 2);
+
+    let m = data.matrix();
+    assert_eq!((m.n_rows(), m.n_cols(), m.nnz()), (3, 3, 4));
+    assert_eq!(m.row(0).to_dense(3), vec![1.5, 0.0, 1.0]);
+    assert_eq!(m.row(2).indices(), &[0, 1]);
+    assert!(m.get(2, 1).is_nan());
+
+    assert_eq!(
+        data.column(2).values(),
+        &ColumnValues::Nominal {
+            levels: vec!["no".to_owned(), "yes please".to_owned()],
+            codes: vec![Some(1), Some(0), Some(0)],
+        }
+    );
+    assert!(data.column(1).is_missing(2));
+
+    let features = data.without_column(0);
+    assert_eq!(features.matrix().row(0).to_dense(2), vec![0.0, 1.0]);
+    assert_eq!(features.to_data_frame().n_cols(), 2);
+
+    assert!(SparseDataSet::from_str("@DATA\n{0 1}\n").is_err());
+    assert!(!is_sparse("@DATA\n1,2\n"));
+}
@@ -0,0 +1,241 @@
+//! Local generation of cross-validation splits
+//!
+//! Most OpenML tasks come with pre-defined splits. The functions in this module generate
+//! equivalent splits for data sets or tasks that have none.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+pub use crate::procedures::Fold;
+
+/// Generate `n_repeats` times repeated k-fold cross-validation splits of `n_rows` rows.
+///
+/// The rows are shuffled before each repeat, using a random number generator initialized with
+/// `seed`. The result contains `n_repeats` vectors of `n_folds` folds each.
+pub fn k_fold(n_rows: usize, n_folds: usize, n_repeats: usize, seed: u64) -> Vec<Vec<Fold>> {
+    k_fold_with_rng(
+        n_rows,
+        n_folds,
+        n_repeats,
+        &mut ChaCha8Rng::seed_from_u64(seed),
+    )
+}
+
+/// Generate repeated k-fold cross-validation splits like `k_fold`, drawing random numbers from
+/// `rng`.
+///
+/// Sharing one seeded generator between all stochastic steps makes a whole sequence of
+/// experiments reproducible from a single seed.
+pub fn k_fold_with_rng<R>(
+    n_rows: usize,
+    n_folds: usize,
+    n_repeats: usize,
+    rng: &mut R,
+) -> Vec<Vec<Fold>>
+where
+    R: Rng + ?Sized,
+{
+    assert!(
+        n_folds > 1,
+        "k-fold cross-validation requires at least two folds"
+    );
+
+    (0..n_repeats)
+        .map(|_| {
+            let mut rows: Vec<_> = (0..n_rows).collect();
+            rows.shuffle(rng);
+            assign_folds(&rows, n_folds)
+        })
+        .collect()
+}
+
+/// Generate `n_repeats` times repeated, stratified k-fold cross-validation splits.
+///
+/// Stratification follows OpenML's semantics: the rows of each class in `target` are shuffled and
+/// dealt out to the folds in turn, so that every fold has (up to one row per class) the same class
+/// proportions as the whole data set. Classes are processed in order of first appearance, which
+/// makes the result depend on `seed` only.
+pub fn stratified_k_fold<T>(
+    target: &[T],
+    n_folds: usize,
+    n_repeats: usize,
+    seed: u64,
+) -> Vec<Vec<Fold>>
+where
+    T: Eq + Hash,
+{
+    stratified_k_fold_with_rng(
+        target,
+        n_folds,
+        n_repeats,
+        &mut ChaCha8Rng::seed_from_u64(seed),
+    )
+}
+
+/// Generate repeated, stratified k-fold cross-validation splits like `stratified_k_fold`,
+/// drawing random numbers from `rng`.
+pub fn stratified_k_fold_with_rng<T, R>(
+    target: &[T],
+    n_folds: usize,
+    n_repeats: usize,
+    rng: &mut R,
+) -> Vec<Vec<Fold>>
+where
+    T: Eq + Hash,
+    R: Rng + ?Sized,
+{
+    assert!(
+        n_folds > 1,
+        "k-fold cross-validation requires at least two folds"
+    );
+
+    let mut class_index = HashMap::new();
+    let mut classes: Vec<Vec<usize>> = Vec::new();
+    for (row, label) in target.iter().enumerate() {
+        let idx = *class_index.entry(label).or_insert_with(|| {
+            classes.push(Vec::new());
+            classes.len() - 1
+        });
+        classes[idx].push(row);
+    }
+
+    (0..n_repeats)
+        .map(|_| {
+            let mut rows = Vec::with_capacity(target.len());
+            for class in &classes {
+                let mut class = class.clone();
+                class.shuffle(rng);
+                rows.extend(class);
+            }
+            assign_folds(&rows, n_folds)
+        })
+        .collect()
+}
+
+/// Draw a bootstrap sample: the training set consists of `n_rows` rows drawn with replacement
+/// (so it may contain rows several times), and the testing set of the rows that were never drawn.
+pub fn bootstrap<R>(n_rows: usize, rng: &mut R) -> Fold
+where
+    R: Rng + ?Sized,
+{
+    let mut drawn = vec![false; n_rows];
+    let mut trainset: Vec<_> = (0..n_rows).map(|_| rng.gen_range(0..n_rows)).collect();
+    for &row in &trainset {
+        drawn[row] = true;
+    }
+    trainset.sort_unstable();
+
+    Fold {
+        trainset,
+        testset: (0..n_rows).filter(|&row| !drawn[row]).collect(),
+    }
+}
+
+/// Draw a random subsample of `n_samples` distinct rows out of `n_rows`, in ascending order.
+pub fn subsample<R>(n_rows: usize, n_samples: usize, rng: &mut R) -> Vec<usize>
+where
+    R: Rng + ?Sized,
+{
+    let mut rows = rand::seq::index::sample(rng, n_rows, n_samples).into_vec();
+    rows.sort_unstable();
+    rows
+}
+
+/// Generate a single time-ordered split: rows before `test_start` form the training set, and
+/// rows from `test_start` up to `n_rows` form the testing set.
+///
+/// This assumes the rows are sorted by time, so that models are only ever tested on data that
+/// lies in the future of their training data.
+pub fn time_ordered(n_rows: usize, test_start: usize) -> Fold {
+    assert!(
+        test_start <= n_rows,
+        "test set starts at row {} but there are only {} rows",
+        test_start,
+        n_rows
+    );
+
+    Fold {
+        trainset: (0..test_start).collect(),
+        testset: (test_start..n_rows).collect(),
+    }
+}
+
+/// Deal rows out to folds in turn; each row is in the testing set of exactly one fold.
+fn assign_folds(rows: &[usize], n_folds: usize) -> Vec<Fold> {
+    let mut folds = vec![Fold::new(); n_folds];
+
+    for (i, &row) in rows.iter().enumerate() {
+        for (k, fold) in folds.iter_mut().enumerate() {
+            if i % n_folds == k {
+                fold.testset.push(row);
+            } else {
+                fold.trainset.push(row);
+            }
+        }
+    }
+
+    for fold in &mut folds {
+        fold.trainset.sort_unstable();
+        fold.testset.sort_unstable();
+    }
+
+    folds
+}
+
+#[test]
+fn temporal() {
+    let fold = time_ordered(5, 3);
+    assert_eq!(fold.trainset(), &[0, 1, 2]);
+    assert_eq!(fold.testset(), &[3, 4]);
+}
+
+#[test]
+fn stratified() {
+    let target = [0, 0, 0, 0, 0, 0, 1, 1, 1, 2, 2, 2];
+    let splits = stratified_k_fold(&target, 3, 2, 42);
+
+    assert_eq!(splits.len(), 2);
+    for folds in &splits {
+        assert_eq!(folds.len(), 3);
+
+        let mut tested = vec![];
+        for fold in folds {
+            assert_eq!(fold.trainset().len() + fold.testset().len(), target.len());
+
+            let count = |c| fold.testset().iter().filter(|&&i| target[i] == c).count();
+            assert_eq!((count(0), count(1), count(2)), (2, 1, 1));
+
+            tested.extend_from_slice(fold.testset());
+        }
+        tested.sort();
+        assert_eq!(tested, (0..target.len()).collect::<Vec<_>>());
+    }
+
+    assert_eq!(splits, stratified_k_fold(&target, 3, 2, 42));
+}
+
+#[test]
+fn sampling() {
+    let mut rng = ChaCha8Rng::seed_from_u64(7);
+
+    let fold = bootstrap(20, &mut rng);
+    assert_eq!(fold.trainset().len(), 20);
+    assert!(fold
+        .testset()
+        .iter()
+        .all(|row| !fold.trainset().contains(row)));
+
+    let rows = subsample(20, 5, &mut rng);
+    assert_eq!(rows.len(), 5);
+    assert!(rows.windows(2).all(|w| w[0] < w[1]));
+
+    // the seeded and the generator based variants agree
+    assert_eq!(
+        k_fold(10, 3, 2, 1),
+        k_fold_with_rng(10, 3, 2, &mut ChaCha8Rng::seed_from_u64(1))
+    );
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use num_traits::AsPrimitive;
+
+use crate::error::{Error, Result};
+
+/// Mapping between the class labels of a classification task and the numeric codes used for them
+///
+/// The mapping is built once per task from the declaration of the nominal target attribute, so it
+/// covers every class (even if it never occurs in a training set) and the codes are identical in
+/// all folds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassLabels {
+    labels: Vec<String>,
+    codes: HashMap<String, usize>,
+}
+
+impl ClassLabels {
+    /// create the mapping from labels given in order of their codes
+    pub fn new(labels: Vec<String>) -> Self {
+        let codes = labels
+            .iter()
+            .enumerate()
+            .map(|(code, label)| (label.clone(), code))
+            .collect();
+        ClassLabels { labels, codes }
+    }
+
+    /// get the number of classes
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    /// return `true` if there are no classes
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// get the label of a class code
+    pub fn label(&self, code: usize) -> Option<&str> {
+        self.labels.get(code).map(String::as_str)
+    }
+
+    /// get the code of a class label
+    pub fn code(&self, label: &str) -> Option<usize> {
+        self.codes.get(label).cloned()
+    }
+
+    /// decode a predicted class code into its label. Codes given as floating point numbers are
+    /// rounded to the nearest integer.
+    pub fn decode_one<C: AsPrimitive<f64>>(&self, code: C) -> Option<&str> {
+        let code = code.as_().round();
+        if code < 0.0 {
+            return None;
+        }
+        self.label(code as usize)
+    }
+
+    /// decode a sequence of predicted class codes into labels, e.g. to report predictions or to
+    /// write them to a predictions file. Fails at the first code that does not belong to a class.
+    pub fn decode<I, C>(&self, codes: I) -> Result<Vec<&str>>
+    where
+        I: IntoIterator<Item = C>,
+        C: AsPrimitive<f64>,
+    {
+        codes
+            .into_iter()
+            .enumerate()
+            .map(|(row, code)| {
+                self.decode_one(code)
+                    .ok_or_else(|| Error::InvalidPrediction {
+                        row: Some(row),
+                        message: format!("{} is not a class code", code.as_()),
+                    })
+            })
+            .collect()
+    }
+
+    /// encode a sequence of labels into class codes. Fails at the first unknown label.
+    pub fn encode<'a, I>(&self, labels: I) -> Result<Vec<usize>>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        labels
+            .into_iter()
+            .enumerate()
+            .map(|(row, label)| {
+                self.code(label).ok_or_else(|| Error::InvalidPrediction {
+                    row: Some(row),
+                    message: format!("unknown class label {:?}", label),
+                })
+            })
+            .collect()
+    }
+
+    /// iterate over all labels in order of their codes
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.labels.iter().map(String::as_str)
+    }
+}
+
+#[test]
+fn class_labels() {
+    let labels = ClassLabels::new(vec!["setosa".to_owned(), "versicolor".to_owned()]);
+    assert_eq!(labels.len(), 2);
+    assert_eq!(labels.code("versicolor"), Some(1));
+    assert_eq!(labels.code("virginica"), None);
+    assert_eq!(labels.label(0), Some("setosa"));
+    assert_eq!(labels.label(2), None);
+
+    assert_eq!(
+        labels.decode(vec![1u8, 0, 1]).unwrap(),
+        vec!["versicolor", "setosa", "versicolor"]
+    );
+    assert_eq!(labels.decode_one(0.9), Some("versicolor"));
+    assert_eq!(labels.decode_one(-1i32), None);
+    assert!(labels.decode(vec![0, 2]).is_err());
+    assert_eq!(
+        labels.encode(vec!["versicolor", "setosa"]).unwrap(),
+        vec![1, 0]
+    );
+    assert!(labels.encode(vec!["virginica"]).is_err());
+}
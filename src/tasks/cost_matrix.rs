@@ -0,0 +1,27 @@
+/// Misclassification costs of a classification task
+#[derive(Debug, Clone, PartialEq)]
+pub enum CostMatrix {
+    /// all misclassifications have the same cost
+    None,
+
+    /// `costs[actual][predicted]` is the cost of predicting class `predicted` for a row of class
+    /// `actual`, with classes given by their codes
+    Costs(Vec<Vec<f64>>),
+}
+
+impl CostMatrix {
+    /// return `true` if there are no explicit costs
+    pub fn is_none(&self) -> bool {
+        *self == CostMatrix::None
+    }
+
+    /// get the cost of predicting class `predicted` for a row of class `actual`. Without explicit
+    /// costs, correct predictions cost 0 and wrong predictions cost 1.
+    pub fn cost(&self, actual: usize, predicted: usize) -> f64 {
+        match self {
+            CostMatrix::Costs(costs) => costs[actual][predicted],
+            CostMatrix::None if actual == predicted => 0.0,
+            CostMatrix::None => 1.0,
+        }
+    }
+}
@@ -0,0 +1,36 @@
+//! Flows as stateful models with separate training and prediction
+
+use super::FeatureView;
+
+/// A model that is fitted on the training set of every fold and then predicts its testing set
+///
+/// The flow is borrowed mutably for the whole run, so it can keep buffers or other state across
+/// folds. Classification targets are passed as class codes, and predictions of classification
+/// tasks are class codes as well.
+///
+/// ```
+/// use openml::{FeatureView, Flow};
+///
+/// /// predicts the mean target of the training set
+/// struct Mean {
+///     mean: f64,
+/// }
+///
+/// impl Flow for Mean {
+///     fn fit(&mut self, _x: FeatureView<'_, f64>, y: &[f64]) {
+///         self.mean = y.iter().sum::<f64>() / y.len() as f64;
+///     }
+///
+///     fn predict(&self, x: FeatureView<'_, f64>) -> Vec<f64> {
+///         vec![self.mean; x.len()]
+///     }
+/// }
+/// ```
+pub trait Flow {
+    /// train on the feature rows `x` of a training set and their targets `y`. This replaces
+    /// whatever was learned on earlier folds.
+    fn fit(&mut self, x: FeatureView<'_, f64>, y: &[f64]);
+
+    /// predict the target of every feature row, in order
+    fn predict(&self, x: FeatureView<'_, f64>) -> Vec<f64>;
+}
@@ -0,0 +1,184 @@
+//! Iteration over the folds of a task, for flows that drive their own training loop
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+use serde::de::DeserializeOwned;
+
+use crate::data_frame::{from_frame, DataFrame};
+use crate::error::Result;
+use crate::procedures::Fold;
+
+use super::missing_values::{Features, MissingValues};
+
+/// The training and testing data of a single fold, copied out of the data set
+///
+/// Feature rows are passed to the flow in the same order as by the `run` methods, and with the
+/// same handling of missing values. Folds that a `SingleClassPolicy` skips are left out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FoldData<X, Y> {
+    pub repeat: usize,
+    pub fold: usize,
+    pub x_train: Vec<Vec<X>>,
+    pub y_train: Vec<Y>,
+    pub x_test: Vec<Vec<X>>,
+    pub y_test: Vec<Y>,
+    /// indices of the testing rows in the data set, e.g. to record predictions with
+    /// `RunPredictions::record`
+    pub test_rows: Vec<usize>,
+}
+
+/// Iterator over the folds of a task; see `folds` on the task types. Features that are imputed
+/// per fold are deserialized by `next`, which yields an error if that fails.
+pub struct Folds<'a, X, Y> {
+    splits: Box<dyn Iterator<Item = (usize, usize, Cow<'a, Fold>)> + 'a>,
+    skip: HashSet<(usize, usize)>,
+    frame: DataFrame,
+    policy: MissingValues,
+    /// features that are the same in every fold; `None` if they are imputed per fold
+    x: Option<Vec<X>>,
+    y: Vec<Y>,
+}
+
+impl<'a, X, Y> Folds<'a, X, Y>
+where
+    X: DeserializeOwned + Clone,
+    Y: DeserializeOwned + Clone,
+{
+    pub(crate) fn new(
+        splits: Box<dyn Iterator<Item = (usize, usize, Cow<'a, Fold>)> + 'a>,
+        skip: HashSet<(usize, usize)>,
+        frame: DataFrame,
+        target: &DataFrame,
+        policy: MissingValues,
+    ) -> Result<Self> {
+        let features = Features::new(policy, &frame);
+        let x = if features.per_fold() {
+            None
+        } else {
+            Some(features.deserialize(&[])?)
+        };
+        let y = from_frame(target)?;
+        Ok(Folds {
+            splits,
+            skip,
+            frame,
+            policy,
+            x,
+            y,
+        })
+    }
+}
+
+impl<'a, X, Y> Iterator for Folds<'a, X, Y>
+where
+    X: DeserializeOwned + Clone,
+    Y: DeserializeOwned + Clone,
+{
+    type Item = Result<FoldData<X, Y>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (repeat, fold, split) = loop {
+            let (repeat, fold, split) = self.splits.next()?;
+            if !self.skip.contains(&(repeat, fold)) {
+                break (repeat, fold, split);
+            }
+        };
+
+        let features = Features::new(self.policy, &self.frame);
+        let fold_x;
+        let x = match &self.x {
+            Some(x) => x,
+            None => {
+                fold_x = match features.deserialize::<X>(&split.trainset) {
+                    Ok(x) => x,
+                    Err(e) => return Some(Err(e)),
+                };
+                &fold_x
+            }
+        };
+        let n_cols = self.frame.n_cols();
+        let rows = |rows: &[usize]| -> Vec<Vec<X>> {
+            rows.iter()
+                .map(|&i| x[i * n_cols..(i + 1) * n_cols].to_vec())
+                .collect()
+        };
+        let targets =
+            |rows: &[usize]| -> Vec<Y> { rows.iter().map(|&i| self.y[i].clone()).collect() };
+
+        let trainset = features.keep(&split.trainset);
+        let testset = features.keep(&split.testset);
+        Some(Ok(FoldData {
+            repeat,
+            fold,
+            x_train: rows(&trainset),
+            y_train: targets(&trainset),
+            x_test: rows(&testset),
+            y_test: targets(&testset),
+            test_rows: testset,
+        }))
+    }
+}
+
+#[test]
+fn folds() {
+    use crate::procedures::{FrozenSets, Procedure};
+
+    let frame = DataFrame::parse_arff(
+        "@RELATION test\n\
+         @ATTRIBUTE a NUMERIC\n\
+         @ATTRIBUTE b NUMERIC\n\
+         @DATA\n\
+         1,2\n\
+         3,?\n\
+         5,6\n\
+         7,8\n",
+    )
+    .unwrap();
+    let target = DataFrame::parse_arff(
+        "@RELATION test\n\
+         @ATTRIBUTE y {no,yes}\n\
+         @DATA\n\
+         no\nyes\nyes\nno\n",
+    )
+    .unwrap();
+    let splits = FrozenSets {
+        folds: vec![vec![
+            Fold {
+                trainset: vec![0, 1],
+                testset: vec![2, 3],
+            },
+            Fold {
+                trainset: vec![2, 3],
+                testset: vec![0, 1],
+            },
+        ]],
+    };
+
+    let folds: Vec<FoldData<f64, String>> = Folds::new(
+        splits.iter(),
+        HashSet::new(),
+        frame.clone(),
+        &target,
+        MissingValues::DropRows,
+    )
+    .unwrap()
+    .collect::<Result<_>>()
+    .unwrap();
+    assert_eq!(folds.len(), 2);
+    assert_eq!(folds[0].x_train, vec![vec![1.0, 2.0]]);
+    assert_eq!(folds[0].y_train, vec!["no"]);
+    assert_eq!(folds[0].x_test, vec![vec![5.0, 6.0], vec![7.0, 8.0]]);
+    assert_eq!(folds[0].y_test, vec!["yes", "no"]);
+    assert_eq!(folds[1].test_rows, vec![0]);
+
+    let skip = [(0, 0)].iter().cloned().collect();
+    let folds: Vec<FoldData<f64, String>> =
+        Folds::new(splits.iter(), skip, frame, &target, MissingValues::Impute)
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+    assert_eq!(folds.len(), 1);
+    assert_eq!(folds[0].fold, 1);
+    assert_eq!(folds[0].x_test[1], vec![3.0, 7.0]);
+}
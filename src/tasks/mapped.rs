@@ -0,0 +1,190 @@
+//! Memory-mapped feature matrices
+//!
+//! The features of a data set are written once to the cache directory as a row-major matrix of
+//! `f64` values, and then mapped into memory. Processes on one machine that map the same file
+//! share its pages, so a multi-GB data set is only held in memory once.
+
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::data_frame::DataFrame;
+use crate::dataset::DataSet;
+use crate::error::{Error, Result};
+use crate::openml_api::{cache_path, OpenML};
+
+use super::views::FeatureView;
+
+/// Identifies feature matrix files and the version of their format
+const MAGIC: &[u8; 8] = b"OMLF64\0\x01";
+
+/// Size of the header: magic, number of rows and number of columns. It is a multiple of 8, so
+/// that the values are aligned.
+const HEADER_LEN: usize = 24;
+
+/// A feature matrix of `f64` values that is mapped from the cache directory. Nominal values are
+/// their codes, dates are epoch seconds and missing values are NaN.
+#[derive(Debug)]
+pub struct MappedFeatures {
+    map: Mmap,
+    n_rows: usize,
+    n_cols: usize,
+}
+
+impl MappedFeatures {
+    /// map the features of a data set, writing them to the cache first if necessary
+    pub(crate) fn from_data_set(data: &DataSet, openml: &OpenML) -> Result<Self> {
+        let names: Vec<String> = data
+            .features()
+            .iter()
+            .map(|f| f.name().to_owned())
+            .collect();
+        let key = format!(
+            "features_{}_{}_{:08x}.f64",
+            data.id,
+            data.version.unwrap_or(0),
+            crc32fast::hash(names.join("\n").as_bytes())
+        );
+        let path = cache_path(openml, &key)?;
+
+        if let Some(features) = MappedFeatures::open(&path) {
+            info!("Mapping features {:?}", path);
+            return Ok(features);
+        }
+
+        let (x, _) = data
+            .clone_split()
+            .expect("Mapped features require a target column");
+        write(&path, &x)?;
+        MappedFeatures::open(&path).ok_or_else(|| Error::ColumnError {
+            column: key,
+            message: "could not map the feature matrix".to_owned(),
+        })
+    }
+
+    /// map a feature matrix file; returns `None` if it does not exist or is not valid
+    fn open(path: &Path) -> Option<Self> {
+        let file = File::open(path).ok()?;
+        // SAFETY: the file is only ever written under a temporary name and then renamed, so
+        // mapped files are never modified.
+        let map = unsafe { Mmap::map(&file) }.ok()?;
+        if map.len() < HEADER_LEN || &map[..8] != MAGIC {
+            return None;
+        }
+        let read_u64 = |at: usize| {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(&map[at..at + 8]);
+            u64::from_ne_bytes(bytes) as usize
+        };
+        let (n_rows, n_cols) = (read_u64(8), read_u64(16));
+        if map.len() != HEADER_LEN + n_rows * n_cols * 8 {
+            return None;
+        }
+        Some(MappedFeatures {
+            map,
+            n_rows,
+            n_cols,
+        })
+    }
+
+    /// get the number of rows
+    pub fn n_rows(&self) -> usize {
+        self.n_rows
+    }
+
+    /// get the number of features per row
+    pub fn n_cols(&self) -> usize {
+        self.n_cols
+    }
+
+    /// get all values in row-major order
+    pub fn as_slice(&self) -> &[f64] {
+        let values = &self.map[HEADER_LEN..];
+        // SAFETY: maps are page-aligned and the header length is a multiple of 8, so the values
+        // are aligned; `open` checked that the file holds exactly `n_rows * n_cols` values.
+        unsafe {
+            std::slice::from_raw_parts(values.as_ptr() as *const f64, self.n_rows * self.n_cols)
+        }
+    }
+
+    /// get the `i`th row
+    pub fn row(&self, i: usize) -> &[f64] {
+        &self.as_slice()[i * self.n_cols..(i + 1) * self.n_cols]
+    }
+
+    /// get a view of the rows with the given indices
+    pub fn view<'a>(&'a self, rows: &'a [usize]) -> FeatureView<'a, f64> {
+        FeatureView::new(self.as_slice(), self.n_cols, rows)
+    }
+}
+
+/// write a feature matrix file; it is written under a temporary name first, so that mapped files
+/// never change
+fn write(path: &Path, x: &DataFrame) -> Result<()> {
+    let columns = x
+        .columns()
+        .iter()
+        .map(|c| {
+            c.to_f64().ok_or_else(|| Error::ColumnError {
+                column: c.name().to_owned(),
+                message: "string column cannot be mapped".to_owned(),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".{}.tmp", std::process::id()));
+
+    let result = File::create(&tmp)
+        .and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            writer.write_all(MAGIC)?;
+            writer.write_all(&(x.n_rows() as u64).to_ne_bytes())?;
+            writer.write_all(&(x.n_cols() as u64).to_ne_bytes())?;
+            for row in 0..x.n_rows() {
+                for column in &columns {
+                    writer.write_all(&column[row].to_ne_bytes())?;
+                }
+            }
+            writer.into_inner().map_err(|e| e.into_error())?;
+            fs::rename(&tmp, path)
+        })
+        .map_err(Error::from);
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+#[test]
+fn mapped_features() {
+    let x = DataFrame::parse_arff(
+        "@RELATION test\n\
+         @ATTRIBUTE a NUMERIC\n\
+         @ATTRIBUTE b {u,v}\n\
+         @DATA\n\
+         1.5,v\n\
+         ?,u\n\
+         3,?\n",
+    )
+    .unwrap();
+    let path = std::env::temp_dir().join(format!("openml-test-{}.f64", std::process::id()));
+
+    write(&path, &x).unwrap();
+    let features = MappedFeatures::open(&path).unwrap();
+    assert_eq!((features.n_rows(), features.n_cols()), (3, 2));
+    assert_eq!(features.row(0), &[1.5, 1.0]);
+    assert!(features.row(1)[0].is_nan());
+    let view = features.view(&[2, 0]);
+    assert_eq!(view.get(0)[0], 3.0);
+    assert!(view.get(0)[1].is_nan());
+    assert_eq!(view.get(1), &[1.5, 1.0]);
+    drop(features);
+
+    fs::write(&path, b"OMLF64").unwrap();
+    assert!(MappedFeatures::open(&path).is_none());
+    fs::remove_file(&path).unwrap();
+}
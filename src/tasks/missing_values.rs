@@ -0,0 +1,135 @@
+use serde::de::DeserializeOwned;
+
+use crate::data_frame::{from_frame, DataFrame};
+use crate::error::Result;
+
+/// How missing feature values are handled before the features are passed to a flow
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MissingValues {
+    /// pass missing values to the flow unchanged; features must be deserialized into `Option`s to
+    /// receive them
+    #[default]
+    PassThrough,
+    /// replace missing numeric values by NaN; missing nominal and string values are passed
+    /// through
+    NanMarkers,
+    /// leave rows with missing features out of the training and testing sets. These rows are not
+    /// evaluated.
+    DropRows,
+    /// replace missing values by the mean (numeric features) or the most frequent value (nominal
+    /// and string features) of the current fold's training set
+    Impute,
+}
+
+/// The features of a task, prepared for flows according to a `MissingValues` policy
+pub(crate) struct Features<'a> {
+    policy: MissingValues,
+    frame: &'a DataFrame,
+    complete: Vec<bool>,
+}
+
+impl<'a> Features<'a> {
+    pub(crate) fn new(policy: MissingValues, frame: &'a DataFrame) -> Self {
+        let complete = match policy {
+            MissingValues::DropRows => frame.complete_rows(),
+            _ => vec![],
+        };
+        Features {
+            policy,
+            frame,
+            complete,
+        }
+    }
+
+    /// return `true` if the features depend on the training set, so that they must be
+    /// deserialized for every fold
+    pub(crate) fn per_fold(&self) -> bool {
+        self.policy == MissingValues::Impute
+    }
+
+    /// deserialize the features as seen by a flow that is trained on `trainset`
+    pub(crate) fn deserialize<X: DeserializeOwned>(&self, trainset: &[usize]) -> Result<Vec<X>> {
+        let frame = match self.policy {
+            MissingValues::PassThrough => return Ok(from_frame(self.frame)?),
+            MissingValues::NanMarkers => self.frame.with_nan_markers(),
+            MissingValues::Impute => self.frame.impute(trainset),
+            // incomplete rows are never passed to the flow, but must still deserialize
+            MissingValues::DropRows => self.frame.impute(&[]),
+        };
+        Ok(from_frame(&frame)?)
+    }
+
+    /// select the rows of a training or testing set that are passed to the flow
+    pub(crate) fn keep(&self, rows: &[usize]) -> Vec<usize> {
+        match self.policy {
+            MissingValues::DropRows => rows.iter().cloned().filter(|&i| self.complete[i]).collect(),
+            _ => rows.to_vec(),
+        }
+    }
+}
+
+#[test]
+fn policies() {
+    let frame = DataFrame::parse_arff(
+        "@RELATION test\n\
+         @ATTRIBUTE x NUMERIC\n\
+         @DATA\n\
+         1\n\
+         ?\n\
+         5\n",
+    )
+    .unwrap();
+
+    let features = Features::new(MissingValues::PassThrough, &frame);
+    let x: Vec<Option<f64>> = features.deserialize(&[]).unwrap();
+    assert_eq!(x, vec![Some(1.0), None, Some(5.0)]);
+    assert!(features.deserialize::<f64>(&[]).is_err());
+
+    let features = Features::new(MissingValues::NanMarkers, &frame);
+    assert!(features.deserialize::<f64>(&[]).unwrap()[1].is_nan());
+    assert!(features.deserialize::<f32>(&[]).unwrap()[1].is_nan());
+
+    let features = Features::new(MissingValues::DropRows, &frame);
+    assert_eq!(features.keep(&[0, 1, 2]), vec![0, 2]);
+
+    let features = Features::new(MissingValues::Impute, &frame);
+    assert!(features.per_fold());
+    let x: Vec<f64> = features.deserialize(&[0, 1]).unwrap();
+    assert_eq!(x, vec![1.0, 1.0, 5.0]);
+}
+
+#[test]
+fn string_features() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Row {
+        text: Option<String>,
+        length: f64,
+    }
+
+    let frame = DataFrame::parse_arff(
+        "@RELATION text\n\
+         @ATTRIBUTE text STRING\n\
+         @ATTRIBUTE length NUMERIC\n\
+         @DATA\n\
+         'hello world',11\n\
+         ?,?\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        frame.column("text").unwrap().as_strings().unwrap()[0].as_deref(),
+        Some("hello world")
+    );
+
+    // raw strings reach the flow, also after imputation of the numeric feature
+    let features = Features::new(MissingValues::Impute, &frame);
+    let rows: Vec<Row> = features.deserialize(&[0]).unwrap();
+    assert_eq!(
+        rows[0],
+        Row {
+            text: Some("hello world".to_owned()),
+            length: 11.0
+        }
+    );
+    assert_eq!(rows[1].text.as_deref(), Some("hello world"));
+}
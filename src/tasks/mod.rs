@@ -1,14 +1,83 @@
 //! Implementations of specific OpenML task types
 
+mod class_labels;
+mod cost_matrix;
+mod flow;
+mod folds;
+#[cfg(feature = "mmap")]
+mod mapped;
+mod missing_values;
+mod prediction_files;
+mod progress;
+mod runner;
+mod single_class;
 mod supervised_classification;
 mod supervised_regression;
+mod task_kind;
+mod validation;
+mod views;
 
 use serde::de::DeserializeOwned;
 
+pub use self::class_labels::ClassLabels;
+pub use self::cost_matrix::CostMatrix;
+pub use self::flow::Flow;
+pub use self::folds::{FoldData, Folds};
+#[cfg(feature = "mmap")]
+pub use self::mapped::MappedFeatures;
+pub use self::missing_values::MissingValues;
+pub use self::prediction_files::PredictionFileEvaluation;
+pub use self::progress::FoldProgress;
+pub use self::single_class::SingleClassPolicy;
 pub use self::supervised_classification::SupervisedClassification;
 pub use self::supervised_regression::SupervisedRegression;
+pub use self::task_kind::TaskKind;
+pub use self::validation::CheckPrediction;
+pub use self::views::{FeatureView, TrainingView};
 
-use crate::measure_accumulator::MeasureAccumulator;
+use crate::data_frame::Feature;
+use crate::error::Result;
+use crate::measures::MeasureAccumulator;
+use crate::procedures::EstimationProcedure;
+
+/// Information about the fold a flow is currently trained and tested on
+#[derive(Debug)]
+pub struct FoldContext<'a> {
+    pub(crate) repeat: usize,
+    pub(crate) fold: usize,
+    pub(crate) cost_matrix: &'a CostMatrix,
+    pub(crate) class_labels: Option<&'a ClassLabels>,
+    pub(crate) features: &'a [Feature],
+}
+
+impl<'a> FoldContext<'a> {
+    /// get the index of the current repeat
+    pub fn repeat(&self) -> usize {
+        self.repeat
+    }
+
+    /// get the index of the current fold within the repeat
+    pub fn fold(&self) -> usize {
+        self.fold
+    }
+
+    /// get the misclassification costs; cost-sensitive learners may use them during training
+    pub fn cost_matrix(&self) -> &'a CostMatrix {
+        self.cost_matrix
+    }
+
+    /// get the names and types of the features, in the order in which they are passed to the
+    /// flow
+    pub fn features(&self) -> &'a [Feature] {
+        self.features
+    }
+
+    /// get the class labels of a classification task. They are the same in every fold, even if
+    /// some classes are missing from the current training set.
+    pub fn class_labels(&self) -> Option<&'a ClassLabels> {
+        self.class_labels
+    }
+}
 
 pub trait Task {
     /// get task ID
@@ -17,25 +86,94 @@ pub trait Task {
     /// get task name
     fn name(&self) -> &str;
 
+    /// get the name OpenML gives the type of the task, e.g. "Supervised Classification"
+    fn task_type(&self) -> &'static str;
+
+    /// get the ID of the task's data set
+    fn dataset_id(&self) -> &str;
+
+    /// get the name of the target column
+    fn target_feature(&self) -> Option<&str>;
+
+    /// get the evaluation scheme of the task
+    fn estimation_procedure(&self) -> &EstimationProcedure;
+
+    /// get the OpenML names of the measures the task is evaluated with; see
+    /// `Measure::from_openml_name`
+    fn evaluation_measures(&self) -> &[String];
+
+    /// iterate over the folds of the task, copying the training and testing data of each fold
+    fn folds<X, Y>(&self) -> Result<Folds<'_, X, Y>>
+    where
+        X: DeserializeOwned + Clone,
+        Y: DeserializeOwned + Clone;
+
     /// run task, specifying the type of an entire feature column in `X`. This allows to run
     /// machine learning models that take features of different types, or named features in form
     /// of structs.
-    fn run_static<X, Y, F, M>(&self, flow: F) -> M
+    fn run_static<X, Y, P, F, M>(&self, flow: F) -> M
     where
-        F: Fn(&mut Iterator<Item = (&X, &Y)>, &mut Iterator<Item = &X>) -> Box<Iterator<Item = Y>>,
+        F: FnMut(
+            &mut dyn Iterator<Item = (&X, &Y)>,
+            &mut dyn Iterator<Item = &X>,
+        ) -> Box<dyn Iterator<Item = P>>,
         X: DeserializeOwned,
         Y: DeserializeOwned,
-        M: MeasureAccumulator<Y>;
+        M: MeasureAccumulator<Y, P>;
+
+    /// run task like `run_static`, additionally passing information about the current fold to
+    /// the flow.
+    fn run_static_with_context<X, Y, P, F, M>(&self, flow: F) -> M
+    where
+        F: FnMut(
+            &FoldContext,
+            &mut dyn Iterator<Item = (&X, &Y)>,
+            &mut dyn Iterator<Item = &X>,
+        ) -> Box<dyn Iterator<Item = P>>,
+        X: DeserializeOwned,
+        Y: DeserializeOwned,
+        M: MeasureAccumulator<Y, P>;
 
     /// run task, specifying the feature type in `X`. This allows to run machine learning models
-    /// that expect every feature to have the same type.
-    fn run<X, Y, F, M>(&self, flow: F) -> M
+    /// that expect every feature to have the same type. Any numeric type works; flows over `f32`
+    /// need half the memory for the features of flows over `f64`.
+    fn run<X, Y, P, F, M>(&self, flow: F) -> M
+    where
+        F: FnMut(
+            &mut dyn Iterator<Item = (&[X], &Y)>,
+            &mut dyn Iterator<Item = &[X]>,
+        ) -> Box<dyn Iterator<Item = P>>,
+        X: DeserializeOwned,
+        Y: DeserializeOwned,
+        M: MeasureAccumulator<Y, P>;
+
+    /// run task like `run`, updating a measure that was created by the caller.
+    fn run_with_measure<X, Y, P, F, M>(&self, flow: F, measure: &mut M)
+    where
+        F: FnMut(
+            &mut dyn Iterator<Item = (&[X], &Y)>,
+            &mut dyn Iterator<Item = &[X]>,
+        ) -> Box<dyn Iterator<Item = P>>,
+        X: DeserializeOwned,
+        Y: DeserializeOwned,
+        M: MeasureAccumulator<Y, P> + ?Sized;
+
+    /// run task with a `Flow` that is fitted on the training set of every fold and then predicts
+    /// its testing set
+    fn run_flow<F, M>(&self, flow: &mut F) -> M
+    where
+        F: Flow + ?Sized,
+        M: MeasureAccumulator<f64, f64>;
+
+    /// run task like `run`, additionally passing information about the current fold to the flow.
+    fn run_with_context<X, Y, P, F, M>(&self, flow: F) -> M
     where
-        F: Fn(
-            &mut Iterator<Item = (&[X], &Y)>,
-            &mut Iterator<Item = &[X]>,
-        ) -> Box<Iterator<Item = Y>>,
+        F: FnMut(
+            &FoldContext,
+            &mut dyn Iterator<Item = (&[X], &Y)>,
+            &mut dyn Iterator<Item = &[X]>,
+        ) -> Box<dyn Iterator<Item = P>>,
         X: DeserializeOwned,
         Y: DeserializeOwned,
-        M: MeasureAccumulator<Y>;
+        M: MeasureAccumulator<Y, P>;
 }
@@ -0,0 +1,225 @@
+//! Evaluation of prediction files produced by tools outside of this crate
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::measures::{evaluate, Measure};
+use crate::procedures::Procedure;
+
+/// Measures computed from one prediction file
+#[derive(Debug, Clone, PartialEq)]
+pub struct PredictionFileEvaluation {
+    path: PathBuf,
+    results: Vec<(Measure, f64)>,
+}
+
+impl PredictionFileEvaluation {
+    /// get the path of the evaluated file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// get all computed measures
+    pub fn results(&self) -> &[(Measure, f64)] {
+        &self.results
+    }
+
+    /// get the value of a single measure, if it was computed
+    pub fn result(&self, measure: Measure) -> Option<f64> {
+        self.results
+            .iter()
+            .find(|(m, _)| *m == measure)
+            .map(|&(_, value)| value)
+    }
+}
+
+/// Evaluate every `.csv` file in `dir`, in order of their paths.
+///
+/// Each file needs a header naming the columns `repeat`, `fold`, `row_id` and `prediction` (in any
+/// order, further columns are ignored) and must predict every row of every testing set exactly
+/// once. `parse` converts a prediction into the numeric representation used by `truth`. Rows
+/// whose known value is missing are not scored.
+pub(crate) fn evaluate_directory<F>(
+    dir: &Path,
+    splits: &dyn Procedure,
+    truth: &[Option<f64>],
+    measures: &[Measure],
+    parse: F,
+) -> Result<Vec<PredictionFileEvaluation>>
+where
+    F: Fn(&str) -> Option<f64>,
+{
+    let mut paths = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "csv") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let raw = fs::read_to_string(&path)?;
+            let (known, predicted) =
+                read_predictions(&raw, splits, truth, &parse).map_err(|(line, message)| {
+                    Error::PredictionFileError {
+                        path: path.clone(),
+                        line,
+                        message,
+                    }
+                })?;
+
+            let results = measures
+                .iter()
+                .map(|&m| (m, evaluate(m, &known, &predicted)))
+                .collect();
+
+            Ok(PredictionFileEvaluation { path, results })
+        })
+        .collect()
+}
+
+/// Line number (if the problem is tied to a line) and description of a problem in a file
+type Problem = (Option<usize>, String);
+
+/// Read the (known, predicted) pairs of a prediction file, or return the first problem.
+fn read_predictions<F>(
+    raw: &str,
+    splits: &dyn Procedure,
+    truth: &[Option<f64>],
+    parse: F,
+) -> std::result::Result<(Vec<f64>, Vec<f64>), Problem>
+where
+    F: Fn(&str) -> Option<f64>,
+{
+    let mut expected: HashMap<(usize, usize, usize), bool> = HashMap::new();
+    for (repeat, fold, f) in splits.iter() {
+        for &row in f.testset() {
+            expected.insert((repeat, fold, row), false);
+        }
+    }
+
+    let mut lines = raw
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty());
+
+    let header: Vec<_> = match lines.next() {
+        Some((_, header)) => split_csv(header).collect(),
+        None => return Err((None, "file is empty".to_owned())),
+    };
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|&h| h == name)
+            .ok_or_else(|| (Some(1), format!("missing column '{}'", name)))
+    };
+    let repeat_col = column("repeat")?;
+    let fold_col = column("fold")?;
+    let row_col = column("row_id")?;
+    let prediction_col = column("prediction")?;
+
+    let mut known = vec![];
+    let mut predicted = vec![];
+
+    for (lineno, line) in lines {
+        let values: Vec<_> = split_csv(line).collect();
+        if values.len() != header.len() {
+            return Err((
+                Some(lineno),
+                format!(
+                    "expected {} values but found {}",
+                    header.len(),
+                    values.len()
+                ),
+            ));
+        }
+
+        let index = |col: usize| {
+            values[col]
+                .parse::<usize>()
+                .map_err(|_| (Some(lineno), format!("invalid integer '{}'", values[col])))
+        };
+        let key = (index(repeat_col)?, index(fold_col)?, index(row_col)?);
+
+        match expected.get_mut(&key) {
+            None => {
+                return Err((
+                    Some(lineno),
+                    format!(
+                        "row {} is not in the testing set of repeat {}, fold {}",
+                        key.2, key.0, key.1
+                    ),
+                ))
+            }
+            Some(true) => return Err((Some(lineno), format!("row {} predicted twice", key.2))),
+            Some(seen) => *seen = true,
+        }
+
+        let prediction = parse(values[prediction_col]).ok_or_else(|| {
+            (
+                Some(lineno),
+                format!("invalid prediction '{}'", values[prediction_col]),
+            )
+        })?;
+
+        if let Some(k) = truth[key.2] {
+            known.push(k);
+            predicted.push(prediction);
+        }
+    }
+
+    let n_missing = expected.values().filter(|&&seen| !seen).count();
+    if n_missing > 0 {
+        return Err((None, format!("{} test rows have no prediction", n_missing)));
+    }
+
+    Ok((known, predicted))
+}
+
+fn split_csv(line: &str) -> impl Iterator<Item = &str> {
+    line.split(',')
+        .map(|v| v.trim().trim_matches(|c| c == '\'' || c == '"'))
+}
+
+#[test]
+fn prediction_file() {
+    use crate::procedures::{Fold, FrozenSets};
+
+    let splits = FrozenSets {
+        folds: vec![vec![
+            Fold {
+                trainset: vec![1, 2],
+                testset: vec![0],
+            },
+            Fold {
+                trainset: vec![0],
+                testset: vec![1, 2],
+            },
+        ]],
+    };
+    let truth = [Some(1.0), Some(0.0), None];
+    let parse = |s: &str| s.parse().ok();
+
+    let raw = "repeat,fold,row_id,prediction\n0,0,0,1\n0,1,1,1\n0,1,2,\"0\"\n";
+    let (known, predicted) = read_predictions(raw, &splits, &truth, parse).unwrap();
+    assert_eq!(known, vec![1.0, 0.0]);
+    assert_eq!(predicted, vec![1.0, 1.0]);
+
+    let raw = "repeat,fold,row_id,prediction\n0,0,0,1\n0,0,1,1\n";
+    assert_eq!(
+        read_predictions(raw, &splits, &truth, parse).unwrap_err().0,
+        Some(3)
+    );
+
+    let raw = "repeat,fold,row_id,prediction\n0,0,0,1\n";
+    assert_eq!(
+        read_predictions(raw, &splits, &truth, parse).unwrap_err().0,
+        None
+    );
+}
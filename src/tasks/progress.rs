@@ -0,0 +1,102 @@
+//! Progress reports while a task is run
+
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+/// A fold that a run started or finished; see `with_progress` on the task types
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FoldProgress {
+    /// the features of the fold are about to be prepared and passed to the flow
+    Started {
+        repeat: usize,
+        fold: usize,
+        /// time since the run started
+        elapsed: Duration,
+    },
+    /// the predictions of the fold were evaluated
+    Finished {
+        repeat: usize,
+        fold: usize,
+        /// time since the run started
+        elapsed: Duration,
+    },
+}
+
+/// The callback of a task; runs only borrow the task, so the callback is mutated through a cell
+pub(crate) type ProgressCallback = RefCell<Box<dyn FnMut(&FoldProgress)>>;
+
+/// Reports the folds of a single run to the callback of a task, if it has one
+pub(crate) struct Progress<'a> {
+    callback: Option<&'a ProgressCallback>,
+    start: Instant,
+}
+
+impl<'a> Progress<'a> {
+    pub(crate) fn new(callback: Option<&'a ProgressCallback>) -> Self {
+        Progress {
+            callback,
+            start: Instant::now(),
+        }
+    }
+
+    pub(crate) fn started(&self, repeat: usize, fold: usize) {
+        if let Some(callback) = self.callback {
+            (callback.borrow_mut())(&FoldProgress::Started {
+                repeat,
+                fold,
+                elapsed: self.start.elapsed(),
+            });
+        }
+    }
+
+    pub(crate) fn finished(&self, repeat: usize, fold: usize) {
+        if let Some(callback) = self.callback {
+            (callback.borrow_mut())(&FoldProgress::Finished {
+                repeat,
+                fold,
+                elapsed: self.start.elapsed(),
+            });
+        }
+    }
+}
+
+#[test]
+fn progress() {
+    use std::rc::Rc;
+
+    let events = Rc::new(RefCell::new(vec![]));
+    let recorded = events.clone();
+    let mut calls = 0;
+    let callback: ProgressCallback = RefCell::new(Box::new(move |event| {
+        calls += 1;
+        recorded.borrow_mut().push((calls, *event));
+    }));
+    let progress = Progress::new(Some(&callback));
+    progress.started(0, 1);
+    progress.finished(0, 1);
+    Progress::new(None).started(0, 2);
+
+    let events = events.borrow();
+    assert_eq!(events.len(), 2);
+    match (events[0], events[1]) {
+        (
+            (
+                1,
+                FoldProgress::Started {
+                    fold: 1,
+                    elapsed: a,
+                    ..
+                },
+            ),
+            (
+                2,
+                FoldProgress::Finished {
+                    fold: 1,
+                    elapsed: b,
+                    ..
+                },
+            ),
+        ) => assert!(a <= b),
+        other => panic!("unexpected events {:?}", other),
+    }
+}
@@ -0,0 +1,190 @@
+//! The loop over the folds of a task that all `run` methods share
+
+use std::collections::HashSet;
+
+use serde::de::DeserializeOwned;
+
+use crate::data_frame::{from_frame, DataFrame};
+use crate::dataset::DataSet;
+use crate::error::Result;
+use crate::measures::MeasureAccumulator;
+use crate::procedures::Procedure;
+
+use super::missing_values::{Features, MissingValues};
+use super::progress::{Progress, ProgressCallback};
+use super::validation;
+use super::{ClassLabels, CostMatrix, FeatureView, FoldContext, TrainingView};
+
+/// The parts of a task that a run reads
+pub(crate) struct Runner<'a> {
+    pub(crate) data: &'a DataSet,
+    pub(crate) splits: &'a dyn Procedure,
+    pub(crate) missing_values: MissingValues,
+    pub(crate) class_labels: Option<&'a ClassLabels>,
+    pub(crate) cost_matrix: &'a CostMatrix,
+    /// folds whose training set contains a single class; they are reported with a warning
+    pub(crate) single_class: HashSet<(usize, usize)>,
+    /// whether the folds in `single_class` are skipped
+    pub(crate) skip_single_class: bool,
+    pub(crate) progress: Option<&'a ProgressCallback>,
+}
+
+/// How the features are passed to the flow
+pub(crate) enum Layout<'a, X> {
+    /// one `X` per feature value
+    Values,
+    /// one `X` per row, e.g. a struct of named features
+    Rows,
+    /// a row-major feature matrix and its number of columns, which is used as it is
+    #[cfg_attr(not(feature = "mmap"), allow(dead_code))]
+    Mapped(&'a [X], usize),
+}
+
+impl<'a> Runner<'a> {
+    /// run the flow on views of every fold and add its predictions to `measure`. Fails if the
+    /// features or targets cannot be deserialized, if the flow does not predict every row of a
+    /// fold exactly once, or if `check` rejects a prediction.
+    pub(crate) fn run_views<X, Y, P, F, M, C>(
+        &self,
+        layout: Layout<'_, X>,
+        mut flow: F,
+        measure: &mut M,
+        mut check: C,
+    ) -> Result<()>
+    where
+        F: FnMut(
+            &FoldContext,
+            TrainingView<'_, X, Y>,
+            FeatureView<'_, X>,
+        ) -> Box<dyn Iterator<Item = P>>,
+        X: DeserializeOwned,
+        Y: DeserializeOwned,
+        M: MeasureAccumulator<Y, P> + ?Sized,
+        C: FnMut((usize, usize), usize, &P) -> std::result::Result<(), String>,
+    {
+        let (dx, policy, mapped) = match layout {
+            Layout::Mapped(x, n_cols) => (
+                DataFrame::new("", vec![]),
+                MissingValues::PassThrough,
+                Some((x, n_cols)),
+            ),
+            _ => (self.data.split_target()?.0, self.missing_values, None),
+        };
+        let n_cols = match layout {
+            Layout::Values => dx.n_cols(),
+            Layout::Rows => 1,
+            Layout::Mapped(_, n_cols) => n_cols,
+        };
+
+        let features = Features::new(policy, &dx);
+        let owned_x: Option<Vec<X>> = if mapped.is_some() || features.per_fold() {
+            None
+        } else {
+            Some(features.deserialize(&[])?)
+        };
+        let shared_x = mapped.map(|(x, _)| x).or(owned_x.as_deref());
+        let y: Vec<Y> = from_frame(&self.data.target_frame()?)?;
+        let feature_schema = self.data.features();
+
+        let progress = Progress::new(self.progress);
+        for (repeat, fold_index, fold) in self.splits.iter() {
+            if self.single_class.contains(&(repeat, fold_index)) {
+                warn!(
+                    "Training set of repeat {}, fold {} contains a single class",
+                    repeat, fold_index
+                );
+                if self.skip_single_class {
+                    continue;
+                }
+            }
+
+            progress.started(repeat, fold_index);
+            let fold_x;
+            let x = match shared_x {
+                Some(x) => x,
+                None => {
+                    fold_x = features.deserialize::<X>(&fold.trainset)?;
+                    &fold_x
+                }
+            };
+            let trainset = features.keep(&fold.trainset);
+            let testset = features.keep(&fold.testset);
+
+            let context = FoldContext {
+                repeat,
+                fold: fold_index,
+                class_labels: self.class_labels,
+                cost_matrix: self.cost_matrix,
+                features: &feature_schema,
+            };
+
+            let train = TrainingView::new(FeatureView::new(x, n_cols, &trainset), &y);
+            let test = FeatureView::new(x, n_cols, &testset);
+
+            let predictit = flow(&context, train, test);
+
+            measure.start_fold(repeat, fold_index);
+            measure.training_targets(&mut trainset.iter().map(|&i| &y[i]));
+            validation::measure_fold(
+                measure,
+                (repeat, fold_index),
+                &y,
+                &testset,
+                predictit,
+                &mut check,
+            )?;
+            progress.finished(repeat, fold_index);
+        }
+        Ok(())
+    }
+
+    /// run the flow on every fold like `run_views`, passing the rows of every fold as iterators
+    /// over whole rows
+    pub(crate) fn run_rows<X, Y, P, F, M>(&self, mut flow: F, measure: &mut M) -> Result<()>
+    where
+        F: FnMut(
+            &FoldContext,
+            &mut dyn Iterator<Item = (&X, &Y)>,
+            &mut dyn Iterator<Item = &X>,
+        ) -> Box<dyn Iterator<Item = P>>,
+        X: DeserializeOwned,
+        Y: DeserializeOwned,
+        M: MeasureAccumulator<Y, P> + ?Sized,
+    {
+        self.run_views(
+            Layout::Rows,
+            |context, train: TrainingView<'_, X, Y>, test: FeatureView<'_, X>| {
+                flow(
+                    context,
+                    &mut train.iter().map(|(x, y)| (&x[0], y)),
+                    &mut test.iter().map(|x| &x[0]),
+                )
+            },
+            measure,
+            |_, _, _| Ok(()),
+        )
+    }
+
+    /// run the flow on every fold like `run_views`, passing the rows of every fold as iterators
+    /// over feature slices
+    pub(crate) fn run_slices<X, Y, P, F, M>(&self, mut flow: F, measure: &mut M) -> Result<()>
+    where
+        F: FnMut(
+            &FoldContext,
+            &mut dyn Iterator<Item = (&[X], &Y)>,
+            &mut dyn Iterator<Item = &[X]>,
+        ) -> Box<dyn Iterator<Item = P>>,
+        X: DeserializeOwned,
+        Y: DeserializeOwned,
+        M: MeasureAccumulator<Y, P> + ?Sized,
+    {
+        self.run_views(
+            Layout::Values,
+            |context, train: TrainingView<'_, X, Y>, test: FeatureView<'_, X>| {
+                flow(context, &mut train.iter(), &mut test.iter())
+            },
+            measure,
+            |_, _, _| Ok(()),
+        )
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::HashSet;
+
+use crate::procedures::{Fold, FrozenSets, Procedure};
+
+/// What to do with folds whose training set contains only a single class
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SingleClassPolicy {
+    /// run the flow on such folds anyway, but log a warning
+    #[default]
+    Warn,
+    /// do not run the flow on such folds; they are listed by `single_class_folds()`
+    Skip,
+    /// merge the testing rows of classes that are missing from the training set into the
+    /// training set; these rows are not evaluated in that fold
+    Merge,
+    /// refuse to run the task if there are such folds
+    Error,
+}
+
+/// find all (repeat, fold) pairs whose training set contains less than two classes
+pub(crate) fn single_class_folds(
+    splits: &dyn Procedure,
+    target: &[Option<usize>],
+) -> Vec<(usize, usize)> {
+    splits
+        .iter()
+        .filter(|(_, _, fold)| is_single_class(fold, target))
+        .map(|(repeat, fold, _)| (repeat, fold))
+        .collect()
+}
+
+/// move the testing rows of classes that are missing from the training set of a single-class fold
+/// into its training set
+pub(crate) fn merge_single_class_folds(
+    splits: &dyn Procedure,
+    target: &[Option<usize>],
+) -> FrozenSets {
+    let mut folds: Vec<Vec<Fold>> = vec![];
+    for (repeat, _, fold) in splits.iter() {
+        if repeat >= folds.len() {
+            folds.resize(repeat + 1, vec![]);
+        }
+
+        let mut fold = fold.into_owned();
+        if is_single_class(&fold, target) {
+            let classes: HashSet<_> = fold.trainset.iter().filter_map(|&i| target[i]).collect();
+            let (keep, moved): (Vec<usize>, Vec<usize>) = fold
+                .testset
+                .iter()
+                .partition(|&&i| target[i].is_none_or(|c| classes.contains(&c)));
+            fold.testset = keep;
+            fold.trainset.extend(moved);
+            fold.trainset.sort_unstable();
+        }
+        folds[repeat].push(fold);
+    }
+
+    FrozenSets { folds }
+}
+
+fn is_single_class(fold: &Fold, target: &[Option<usize>]) -> bool {
+    let mut classes = fold.trainset.iter().filter_map(|&i| target[i]);
+    match classes.next() {
+        Some(first) => classes.all(|c| c == first),
+        None => true,
+    }
+}
+
+#[test]
+fn merge_folds() {
+    let target = [Some(0), Some(0), Some(1), Some(1), Some(0), Some(0)];
+    let fold = |test: &[usize]| Fold {
+        trainset: (0..6).filter(|i| !test.contains(i)).collect(),
+        testset: test.to_vec(),
+    };
+    let splits = FrozenSets {
+        folds: vec![vec![fold(&[0, 1]), fold(&[2, 3]), fold(&[4, 5])]],
+    };
+
+    assert_eq!(single_class_folds(&splits, &target), vec![(0, 1)]);
+
+    let merged = merge_single_class_folds(&splits, &target);
+    assert_eq!(merged.folds[0][0], fold(&[0, 1]));
+    assert_eq!(merged.folds[0][1].testset, Vec::<usize>::new());
+    assert_eq!(merged.folds[0][1].trainset, vec![0, 1, 2, 3, 4, 5]);
+    assert_eq!(merged.folds[0][2], fold(&[4, 5]));
+    assert!(single_class_folds(&merged, &target).is_empty());
+}
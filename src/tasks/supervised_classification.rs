@@ -1,17 +1,80 @@
-use arff::dynamic::de::from_dataset;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
 use serde::de::DeserializeOwned;
 
+use crate::data_frame::{from_frame, DataFrame, Feature};
 use crate::dataset::DataSet;
-use crate::measure_accumulator::MeasureAccumulator;
-use crate::procedures::Procedure;
+use crate::error::{Error, Result};
+use crate::measures::{Measure, MeasureAccumulator};
+use crate::openml_api::{random_seed, upload_run, OpenML};
+use crate::procedures::{EstimationProcedure, FrozenSets, Procedure};
+use crate::runs::{
+    evaluate_predictions, FlowMetadata, ParameterSettings, RunPrediction, RunPredictions,
+    RunResult, TraceRecorder, UploadedRun,
+};
+use crate::sparse::{SparseDataSet, SparseMatrix};
+use crate::splits;
+
+#[cfg(feature = "mmap")]
+use super::mapped::MappedFeatures;
+use super::missing_values::{Features, MissingValues};
+use super::prediction_files::{self, PredictionFileEvaluation};
+use super::progress::{FoldProgress, ProgressCallback};
+use super::runner::{Layout, Runner};
+use super::single_class::{self, SingleClassPolicy};
+use super::validation::{self, CheckPrediction};
+use super::{ClassLabels, CostMatrix, FeatureView, Flow, FoldContext, Folds, TrainingView};
 
 /// Classification task
 pub struct SupervisedClassification {
     pub(crate) id: String,
     pub(crate) name: String,
+    pub(crate) tags: Vec<String>,
     pub(crate) source_data: DataSet,
-    pub(crate) estimation_procedure: Box<Procedure>,
-    //pub(crate) cost_matrix: CostMatrix,
+    pub(crate) estimation_procedure: EstimationProcedure,
+    pub(crate) evaluation_measures: Vec<String>,
+    pub(crate) splits: Box<dyn Procedure>,
+    pub(crate) missing_values: MissingValues,
+    pub(crate) progress: Option<ProgressCallback>,
+    /// the client the task was loaded with, which its runs are uploaded with
+    pub(crate) openml: OpenML,
+    pub(crate) cost_matrix: CostMatrix,
+    pub(crate) class_labels: Option<ClassLabels>,
+    pub(crate) single_class_policy: SingleClassPolicy,
+}
+
+/// The identity of the task; the data set and splits are left out
+impl fmt::Debug for SupervisedClassification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SupervisedClassification")
+            .field("id", &self.id)
+            .field("name", &self.name)
+            .field("dataset_id", &self.source_data.id)
+            .field("dataset_version", &self.source_data.version)
+            .field("target_feature", &self.source_data.target)
+            .field("estimation_procedure", &self.estimation_procedure)
+            .finish_non_exhaustive()
+    }
+}
+
+/// e.g. `task 59 (Supervised Classification) on data set 61, target "class"`
+impl fmt::Display for SupervisedClassification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "task {} ({}) on data set {}",
+            self.id,
+            self.task_type(),
+            self.source_data.id
+        )?;
+        if let Some(target) = &self.source_data.target {
+            write!(f, ", target {:?}", target)?;
+        }
+        Ok(())
+    }
 }
 
 impl SupervisedClassification {
@@ -25,81 +88,604 @@ impl SupervisedClassification {
         &self.name
     }
 
+    /// get the tags the task is labeled with on OpenML
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// get the name OpenML gives the type of the task, "Supervised Classification"
+    pub fn task_type(&self) -> &'static str {
+        "Supervised Classification"
+    }
+
+    /// get the ID of the task's data set
+    pub fn dataset_id(&self) -> &str {
+        &self.source_data.id
+    }
+
+    /// get the name of the target column
+    pub fn target_feature(&self) -> Option<&str> {
+        self.source_data.target.as_deref()
+    }
+
+    /// get the version of the task's data set, if the server reported it
+    pub fn dataset_version(&self) -> Option<u32> {
+        self.source_data.version
+    }
+
+    /// get the task's data set, features and target, with typed columns
+    pub fn data_frame(&self) -> DataFrame {
+        self.source_data.data_frame()
+    }
+
+    /// get the names and types of the task's features, in the order in which they are passed to
+    /// flows. Flows receive them in `FoldContext::features`, too.
+    pub fn features(&self) -> Vec<Feature> {
+        self.source_data.features()
+    }
+
+    /// get the name of the column that identifies rows, if the data set has one. It is not passed
+    /// to flows as a feature.
+    pub fn row_id_attribute(&self) -> Option<&str> {
+        self.source_data.row_id.as_deref()
+    }
+
+    /// get the names of the columns that the data set marks to be ignored. They are not passed to
+    /// flows as features.
+    pub fn ignored_attributes(&self) -> &[String] {
+        &self.source_data.ignored
+    }
+
+    /// get the row ID and ignored columns, which are not passed to flows
+    pub fn excluded_columns(&self) -> DataFrame {
+        self.source_data.excluded_columns()
+    }
+
+    /// get the features of the task as a sparse matrix, if the data set is stored in sparse
+    /// format. `run` and `run_static` expand sparse features into dense rows for every run;
+    /// flows that can work on sparse data can use this matrix and the row indices instead. The
+    /// matrix keeps the features removed by `select_features` or `drop_features`.
+    pub fn sparse_features(&self) -> Option<&SparseMatrix> {
+        self.source_data.sparse.as_ref().map(SparseDataSet::matrix)
+    }
+
+    /// get the evaluation scheme of the task
+    pub fn estimation_procedure(&self) -> &EstimationProcedure {
+        &self.estimation_procedure
+    }
+
+    /// get the OpenML names of the measures the task is evaluated with; see
+    /// `Measure::from_openml_name`
+    pub fn evaluation_measures(&self) -> &[String] {
+        &self.evaluation_measures
+    }
+
+    /// get the mapping between class labels and class codes, if the target is nominal
+    pub fn class_labels(&self) -> Option<&ClassLabels> {
+        self.class_labels.as_ref()
+    }
+
+    /// get the misclassification costs of the task
+    pub fn cost_matrix(&self) -> &CostMatrix {
+        &self.cost_matrix
+    }
+
+    /// list the (repeat, fold) pairs whose training set contains only a single class. This can
+    /// happen on small or imbalanced data sets with unstratified splits.
+    pub fn single_class_folds(&self) -> Vec<(usize, usize)> {
+        match self.source_data.nominal_target() {
+            Some(target) => single_class::single_class_folds(&*self.splits, &target),
+            None => vec![],
+        }
+    }
+
+    /// choose how folds whose training set contains only a single class are handled. With
+    /// `SingleClassPolicy::Error` this fails if there are any such folds.
+    pub fn with_single_class_policy(mut self, policy: SingleClassPolicy) -> Result<Self> {
+        match policy {
+            SingleClassPolicy::Error => {
+                if let Some(&(repeat, fold)) = self.single_class_folds().first() {
+                    return Err(Error::SingleClassFold { repeat, fold });
+                }
+            }
+            SingleClassPolicy::Merge => {
+                if let Some(target) = self.source_data.nominal_target() {
+                    let merged = single_class::merge_single_class_folds(&*self.splits, &target);
+                    self.splits = Box::new(merged);
+                }
+            }
+            SingleClassPolicy::Warn | SingleClassPolicy::Skip => {}
+        }
+        self.single_class_policy = policy;
+        Ok(self)
+    }
+
+    /// report every fold a run starts and finishes to `callback`, e.g. to show the progress of
+    /// long runs. The callback is called on the thread that runs the task, before the features of
+    /// the fold are prepared and after its predictions are evaluated; skipped folds are not
+    /// reported. The callback may keep state, e.g. a progress bar.
+    pub fn with_progress<C: FnMut(&FoldProgress) + 'static>(mut self, callback: C) -> Self {
+        self.progress = Some(RefCell::new(Box::new(callback)));
+        self
+    }
+
+    /// choose how missing feature values are handled before the features are passed to the flow
+    pub fn with_missing_values(mut self, policy: MissingValues) -> Self {
+        self.missing_values = policy;
+        self
+    }
+
+    /// pass only the features with the given names to the flow, e.g. for ablation studies. Fails
+    /// if a name is not a feature of the task.
+    pub fn select_features(mut self, names: &[&str]) -> Result<Self> {
+        self.source_data.select_features(names)?;
+        Ok(self)
+    }
+
+    /// do not pass the features with the given names to the flow. Fails if a name is not a
+    /// feature of the task.
+    pub fn drop_features(mut self, names: &[&str]) -> Result<Self> {
+        self.source_data.drop_features(names)?;
+        Ok(self)
+    }
+
+    /// evaluate every `.csv` prediction file in a directory with accuracy, precision, recall, F1
+    /// and kappa.
+    ///
+    /// The files need the columns `repeat`, `fold`, `row_id` and `prediction`, and must predict
+    /// every row of every testing set of the task exactly once. Predictions are class labels or
+    /// class codes. Fails if the target is neither
+    /// nominal nor numeric.
+    pub fn evaluate_prediction_files<P: AsRef<Path>>(
+        &self,
+        dir: P,
+    ) -> Result<Vec<PredictionFileEvaluation>> {
+        let truth = self.source_data.numeric_target()?;
+        let labels = self.class_labels.as_ref();
+
+        prediction_files::evaluate_directory(
+            dir.as_ref(),
+            &*self.splits,
+            &truth,
+            &[
+                Measure::Accuracy,
+                Measure::Precision,
+                Measure::Recall,
+                Measure::F1,
+                Measure::Kappa,
+            ],
+            |p| match labels.and_then(|labels| labels.code(p)) {
+                Some(code) => Some(code as f64),
+                None => p.parse().ok(),
+            },
+        )
+    }
+
+    /// get all folds of the task as an Arrow table with the columns `repeat`, `fold`, `rowid` and
+    /// `type` (`"TRAIN"` or `"TEST"`), e.g. to join them against the data set in a query engine.
+    #[cfg(feature = "arrow")]
+    pub fn splits_arrow(&self) -> arrow_array::RecordBatch {
+        crate::procedures::arrow::to_record_batch(&*self.splits)
+    }
+
+    /// replace the task's splits with a single time-ordered split: the model is trained on all
+    /// rows before `test_start` and tested on all rows from `test_start` on.
+    pub fn with_time_ordered_split(mut self, test_start: usize) -> Self {
+        let n_rows = self.source_data.frame.n_rows();
+        let fold = splits::time_ordered(n_rows, test_start);
+        self.splits = Box::new(FrozenSets {
+            folds: vec![vec![fold]],
+        });
+        self.estimation_procedure = EstimationProcedure::time_ordered(n_rows, test_start);
+        self
+    }
+
+    /// check a flow quickly before running the whole task: the flow is trained on at most 100 rows
+    /// of the first fold's training set and predicts at most 20 rows of its testing set. Fails if
+    /// the task has no folds, if the flow does not predict every row exactly once, or if a
+    /// prediction is implausible (see `CheckPrediction`).
+    pub fn validate<X, Y, P, F>(&self, mut flow: F) -> Result<()>
+    where
+        F: FnMut(
+            &mut dyn Iterator<Item = (&[X], &Y)>,
+            &mut dyn Iterator<Item = &[X]>,
+        ) -> Box<dyn Iterator<Item = P>>,
+        X: DeserializeOwned,
+        Y: DeserializeOwned,
+        P: CheckPrediction,
+    {
+        let (dx, dy) = self.source_data.split_target()?;
+
+        let features = Features::new(self.missing_values, &dx);
+        let fold = validation::first_fold(&*self.splits)?;
+        let trainset = validation::spread(&features.keep(&fold.trainset), validation::TRAIN_ROWS);
+        let testset = validation::spread(&features.keep(&fold.testset), validation::TEST_ROWS);
+
+        let x: Vec<X> = features.deserialize(&fold.trainset)?;
+        let y: Vec<Y> = from_frame(&dy)?;
+        let n = dx.n_cols();
+
+        let mut train = trainset.iter().map(|&i| (&x[i * n..(i + 1) * n], &y[i]));
+        let mut test = testset.iter().map(|&i| &x[i * n..(i + 1) * n]);
+
+        let predictions = flow(&mut train, &mut test);
+        validation::check_predictions(
+            predictions,
+            &testset,
+            self.class_labels.as_ref().map(ClassLabels::len),
+        )
+    }
+
+    /// iterate over the folds of the task, copying the training and testing data of each fold,
+    /// for flows that drive their own training loop, e.g. with early stopping. Unlike the `run`
+    /// methods, nothing is evaluated: predictions can be scored with `Measure`s or recorded in
+    /// `RunPredictions` together with `test_rows`.
+    pub fn folds<X, Y>(&self) -> Result<Folds<'_, X, Y>>
+    where
+        X: DeserializeOwned + Clone,
+        Y: DeserializeOwned + Clone,
+    {
+        let (x, y) = self.source_data.split_target()?;
+        let skip = match self.single_class_policy {
+            SingleClassPolicy::Skip => self.single_class_folds().into_iter().collect(),
+            _ => HashSet::new(),
+        };
+        Folds::new(self.splits.iter(), skip, x, &y, self.missing_values)
+    }
+
     /// run task, specifying the type of an entire feature column in `X`. This allows to run
     /// machine learning models that take features of different types, or named features in form
     /// of structs.
-    pub fn run_static<X, Y, F, M>(&self, flow: F) -> M
+    ///
+    /// Like all `run` methods, this panics if the features or targets cannot be deserialized, or
+    /// if the flow does not predict every row of a fold exactly once; use `try_run_views` to get
+    /// an `Err` instead. The flow
+    /// is called once per fold, in order, and may keep state across calls, e.g. a random number
+    /// generator or a warm-started model.
+    pub fn run_static<X, Y, P, F, M>(&self, mut flow: F) -> M
     where
-        F: Fn(&mut Iterator<Item = (&X, &Y)>, &mut Iterator<Item = &X>) -> Box<Iterator<Item = Y>>,
+        F: FnMut(
+            &mut dyn Iterator<Item = (&X, &Y)>,
+            &mut dyn Iterator<Item = &X>,
+        ) -> Box<dyn Iterator<Item = P>>,
         X: DeserializeOwned,
         Y: DeserializeOwned,
-        M: MeasureAccumulator<Y>,
+        M: MeasureAccumulator<Y, P>,
     {
-        let (dx, dy) = self
-            .source_data
-            .clone_split()
-            .expect("Supervised Classification requires a target column");
-
-        let x: Vec<X> = from_dataset(&dx).unwrap();
-        let y: Vec<Y> = from_dataset(&dy).unwrap();
+        self.run_static_with_context(|_, train, test| flow(train, test))
+    }
 
+    /// run task like `run_static`, additionally passing information about the current fold to
+    /// the flow.
+    pub fn run_static_with_context<X, Y, P, F, M>(&self, flow: F) -> M
+    where
+        F: FnMut(
+            &FoldContext,
+            &mut dyn Iterator<Item = (&X, &Y)>,
+            &mut dyn Iterator<Item = &X>,
+        ) -> Box<dyn Iterator<Item = P>>,
+        X: DeserializeOwned,
+        Y: DeserializeOwned,
+        M: MeasureAccumulator<Y, P>,
+    {
         let mut measure = M::new();
+        self.runner()
+            .run_rows(flow, &mut measure)
+            .expect("failed to run the task");
+        measure
+    }
 
-        for fold in self.estimation_procedure.iter() {
-            let mut train = fold.trainset.iter().map(|&i| (&x[i], &y[i]));
-
-            let mut test = fold.testset.iter().map(|&i| &x[i]);
+    /// run task, specifying the feature type in `X`. This allows to run machine learning models
+    /// that expect every feature to have the same type. Any numeric type works; flows over `f32`
+    /// need half the memory for the features of flows over `f64`.
+    pub fn run<X, Y, P, F, M>(&self, mut flow: F) -> M
+    where
+        F: FnMut(
+            &mut dyn Iterator<Item = (&[X], &Y)>,
+            &mut dyn Iterator<Item = &[X]>,
+        ) -> Box<dyn Iterator<Item = P>>,
+        X: DeserializeOwned,
+        Y: DeserializeOwned,
+        M: MeasureAccumulator<Y, P>,
+    {
+        self.run_with_context(|_, train, test| flow(train, test))
+    }
 
-            let predictit = flow(&mut train, &mut test);
+    /// run task like `run`, additionally passing information about the current fold to the flow.
+    pub fn run_with_context<X, Y, P, F, M>(&self, flow: F) -> M
+    where
+        F: FnMut(
+            &FoldContext,
+            &mut dyn Iterator<Item = (&[X], &Y)>,
+            &mut dyn Iterator<Item = &[X]>,
+        ) -> Box<dyn Iterator<Item = P>>,
+        X: DeserializeOwned,
+        Y: DeserializeOwned,
+        M: MeasureAccumulator<Y, P>,
+    {
+        let mut measure = M::new();
+        self.runner()
+            .run_slices(flow, &mut measure)
+            .expect("failed to run the task");
+        measure
+    }
 
-            for (known, pred) in fold.testset.iter().map(|&i| &y[i]).zip(predictit) {
-                measure.update_one(known, &pred);
-            }
-        }
+    /// run task like `run`, updating a measure that was created by the caller. This allows to use
+    /// measures that need to be configured, or measures chosen at runtime as trait objects
+    /// (`Box<dyn MeasureAccumulator<Y, P>>`).
+    pub fn run_with_measure<X, Y, P, F, M>(&self, mut flow: F, measure: &mut M)
+    where
+        F: FnMut(
+            &mut dyn Iterator<Item = (&[X], &Y)>,
+            &mut dyn Iterator<Item = &[X]>,
+        ) -> Box<dyn Iterator<Item = P>>,
+        X: DeserializeOwned,
+        Y: DeserializeOwned,
+        M: MeasureAccumulator<Y, P> + ?Sized,
+    {
+        self.runner()
+            .run_slices(|_, train, test| flow(train, test), measure)
+            .expect("failed to run the task")
+    }
 
+    /// run task like `run_with_context`, passing the training and testing rows of every fold as
+    /// views. The views index into a feature matrix that all folds share: they can be iterated
+    /// any number of times, and rows are only copied if the flow calls `to_vec`.
+    pub fn run_views<X, Y, P, F, M>(&self, flow: F) -> M
+    where
+        F: FnMut(
+            &FoldContext,
+            TrainingView<'_, X, Y>,
+            FeatureView<'_, X>,
+        ) -> Box<dyn Iterator<Item = P>>,
+        X: DeserializeOwned,
+        Y: DeserializeOwned,
+        M: MeasureAccumulator<Y, P>,
+    {
+        let mut measure = M::new();
+        self.runner()
+            .run_views(Layout::Values, flow, &mut measure, |_, _, _| Ok(()))
+            .expect("failed to run the task");
         measure
     }
 
-    /// run task, specifying the feature type in `X`. This allows to run machine learning models
-    /// that expect every feature to have the same type.
-    pub fn run<X, Y, F, M>(&self, flow: F) -> M
+    /// run task like `run_views`, but fail instead of panicking if the flow does not predict
+    /// every row of a fold exactly once, or if a prediction is implausible (see
+    /// `CheckPrediction`).
+    pub fn try_run_views<X, Y, P, F, M>(&self, flow: F) -> Result<M>
     where
-        F: Fn(
-            &mut Iterator<Item = (&[X], &Y)>,
-            &mut Iterator<Item = &[X]>,
-        ) -> Box<Iterator<Item = Y>>,
+        F: FnMut(
+            &FoldContext,
+            TrainingView<'_, X, Y>,
+            FeatureView<'_, X>,
+        ) -> Box<dyn Iterator<Item = P>>,
         X: DeserializeOwned,
         Y: DeserializeOwned,
-        M: MeasureAccumulator<Y>,
+        P: CheckPrediction,
+        M: MeasureAccumulator<Y, P>,
     {
-        let (dx, dy) = self
-            .source_data
-            .clone_split()
-            .expect("Supervised Classification requires a target column");
+        let n_classes = self.class_labels.as_ref().map(ClassLabels::len);
+        let mut measure = M::new();
+        self.runner()
+            .run_views(Layout::Values, flow, &mut measure, |_, _, p: &P| {
+                p.check(n_classes)
+            })?;
+        Ok(measure)
+    }
 
-        let x: Vec<X> = from_dataset(&dx).unwrap();
-        let y: Vec<Y> = from_dataset(&dy).unwrap();
+    /// run task with a `Flow` that is fitted on the training set of every fold and then predicts
+    /// its testing set. The flow is borrowed mutably for the whole run, so it may keep state
+    /// across folds.
+    pub fn run_flow<F, M>(&self, flow: &mut F) -> M
+    where
+        F: Flow + ?Sized,
+        M: MeasureAccumulator<f64, f64>,
+    {
+        self.run_views(|_, train: TrainingView<'_, f64, f64>, test| {
+            let y: Vec<f64> = train.targets().cloned().collect();
+            flow.fit(train.features(), &y);
+            Box::new(flow.predict(test).into_iter())
+        })
+    }
 
+    /// run task like `try_run_views`, and also record every prediction together with the repeat,
+    /// fold and row it belongs to, e.g. to write them to a predictions file with
+    /// `predictions_arff`
+    pub fn run_views_recorded<X, Y, P, F, M>(&self, flow: F) -> Result<(M, RunPredictions)>
+    where
+        F: FnMut(
+            &FoldContext,
+            TrainingView<'_, X, Y>,
+            FeatureView<'_, X>,
+        ) -> Box<dyn Iterator<Item = P>>,
+        X: DeserializeOwned,
+        Y: DeserializeOwned,
+        P: CheckPrediction + RunPrediction,
+        M: MeasureAccumulator<Y, P>,
+    {
+        let n_classes = self.class_labels.as_ref().map(ClassLabels::len);
         let mut measure = M::new();
+        let mut predictions = RunPredictions::new();
+        self.runner().run_views(
+            Layout::Values,
+            flow,
+            &mut measure,
+            |(repeat, fold), row, p: &P| {
+                p.check(n_classes)?;
+                predictions.record(repeat, fold, row, p);
+                Ok(())
+            },
+        )?;
+        Ok((measure, predictions))
+    }
 
-        for fold in self.estimation_procedure.iter() {
-            let mut train = fold
-                .trainset
-                .iter()
-                .map(|&i| (&x[i * dx.n_cols()..(i + 1) * dx.n_cols()], &y[i]));
+    /// write predictions of this task in the ARFF format of OpenML's run files, with the
+    /// confidence in every class and the correct class of every row
+    pub fn predictions_arff(&self, predictions: &RunPredictions) -> Result<String> {
+        let labels = self
+            .class_labels
+            .as_ref()
+            .ok_or_else(|| Error::ColumnError {
+                column: self.source_data.target.clone().unwrap_or_default(),
+                message: "predictions files need a nominal target".to_owned(),
+            })?;
+        let truth = self.source_data.nominal_target().unwrap_or_default();
+        predictions.classification_arff(&self.id, labels, &truth)
+    }
 
-            let mut test = fold
-                .testset
-                .iter()
-                .map(|&i| &x[i * dx.n_cols()..(i + 1) * dx.n_cols()]);
+    /// run task like `run_views_recorded`, and keep the predictions together with the flow, its
+    /// hyperparameter values and the accuracy, precision, recall, F1 and kappa they score, e.g. to
+    /// archive them with `RunResult::export`
+    pub fn run_result<X, Y, P, F, M>(
+        &self,
+        flow: &FlowMetadata,
+        parameters: &ParameterSettings,
+        flow_fn: F,
+    ) -> Result<(M, RunResult)>
+    where
+        F: FnMut(
+            &FoldContext,
+            TrainingView<'_, X, Y>,
+            FeatureView<'_, X>,
+        ) -> Box<dyn Iterator<Item = P>>,
+        X: DeserializeOwned,
+        Y: DeserializeOwned,
+        P: CheckPrediction + RunPrediction,
+        M: MeasureAccumulator<Y, P>,
+    {
+        parameters.check(flow)?;
+        let (measure, predictions) = self.run_views_recorded(flow_fn)?;
+        let truth = self.source_data.numeric_target().unwrap_or_default();
+        let measures = evaluate_predictions(
+            &predictions,
+            &truth,
+            &[
+                Measure::Accuracy,
+                Measure::Precision,
+                Measure::Recall,
+                Measure::F1,
+                Measure::Kappa,
+            ],
+        );
+        let result = RunResult {
+            task_id: self.id.clone(),
+            flow: flow.clone(),
+            parameter_settings: parameters.clone(),
+            seed: random_seed(),
+            predictions_arff: self.predictions_arff(&predictions)?,
+            predictions,
+            measures,
+        };
+        Ok((measure, result))
+    }
 
-            let predictit = flow(&mut train, &mut test);
+    /// run task like `run_views_recorded`, and upload the predictions to the OpenML server as a
+    /// run of `flow` with the hyperparameter values in `parameters`. The flow is created on the
+    /// server if it does not exist yet. Uploads go to the server of the client the task was loaded
+    /// with, authenticated with its API key; a missing key, and parameters the flow does not
+    /// declare, are reported before the flow is run.
+    pub fn run_and_upload<X, Y, P, F, M>(
+        &self,
+        flow: &FlowMetadata,
+        parameters: &ParameterSettings,
+        flow_fn: F,
+    ) -> Result<(M, UploadedRun)>
+    where
+        F: FnMut(
+            &FoldContext,
+            TrainingView<'_, X, Y>,
+            FeatureView<'_, X>,
+        ) -> Box<dyn Iterator<Item = P>>,
+        X: DeserializeOwned,
+        Y: DeserializeOwned,
+        P: CheckPrediction + RunPrediction,
+        M: MeasureAccumulator<Y, P>,
+    {
+        self.run_and_upload_traced(flow, parameters, &TraceRecorder::new(), flow_fn)
+    }
 
-            for (known, pred) in fold.testset.iter().map(|&i| &y[i]).zip(predictit) {
-                measure.update_one(known, &pred);
-            }
-        }
+    /// run task like `run_and_upload` with a flow that searches its hyperparameters and records
+    /// the settings it tried in `trace`. The trace is uploaded with the run unless it is empty.
+    pub fn run_and_upload_traced<X, Y, P, F, M>(
+        &self,
+        flow: &FlowMetadata,
+        parameters: &ParameterSettings,
+        trace: &TraceRecorder,
+        flow_fn: F,
+    ) -> Result<(M, UploadedRun)>
+    where
+        F: FnMut(
+            &FoldContext,
+            TrainingView<'_, X, Y>,
+            FeatureView<'_, X>,
+        ) -> Box<dyn Iterator<Item = P>>,
+        X: DeserializeOwned,
+        Y: DeserializeOwned,
+        P: CheckPrediction + RunPrediction,
+        M: MeasureAccumulator<Y, P>,
+    {
+        self.openml.require_api_key()?;
+        parameters.check(flow)?;
+        let (measure, predictions) = self.run_views_recorded(flow_fn)?;
+        let arff = self.predictions_arff(&predictions)?;
+        let trace = if trace.is_empty() {
+            None
+        } else {
+            trace.check(flow)?;
+            Some(trace.trace_arff(&self.id))
+        };
+        let run = upload_run(&self.openml, &self.id, flow, parameters, arff, trace)?;
+        Ok((measure, run))
+    }
 
+    /// map the features into memory from the cache directory, writing them there first if
+    /// necessary. Processes that map the features of the same data set share the memory.
+    #[cfg(feature = "mmap")]
+    pub fn mapped_features(&self) -> Result<MappedFeatures> {
+        MappedFeatures::from_data_set(&self.source_data, &self.openml)
+    }
+
+    /// run task like `run_views`, with views into memory-mapped features. The features are not
+    /// deserialized, and the missing value policy does not apply: missing values are NaN.
+    #[cfg(feature = "mmap")]
+    pub fn run_views_mapped<Y, P, F, M>(&self, features: &MappedFeatures, flow: F) -> M
+    where
+        F: FnMut(
+            &FoldContext,
+            TrainingView<'_, f64, Y>,
+            FeatureView<'_, f64>,
+        ) -> Box<dyn Iterator<Item = P>>,
+        Y: DeserializeOwned,
+        M: MeasureAccumulator<Y, P>,
+    {
+        assert_eq!(
+            features.n_cols(),
+            self.source_data.features().len(),
+            "mapped features do not belong to this task"
+        );
+        let mut measure = M::new();
+        self.runner()
+            .run_views(
+                Layout::Mapped(features.as_slice(), features.n_cols()),
+                flow,
+                &mut measure,
+                |_, _, _| Ok(()),
+            )
+            .expect("failed to run the task");
         measure
     }
+
+    /// get the parts of the task that a run reads
+    fn runner(&self) -> Runner<'_> {
+        Runner {
+            data: &self.source_data,
+            splits: &*self.splits,
+            missing_values: self.missing_values,
+            class_labels: self.class_labels.as_ref(),
+            cost_matrix: &self.cost_matrix,
+            single_class: self.single_class_folds().into_iter().collect(),
+            skip_single_class: self.single_class_policy == SingleClassPolicy::Skip,
+            progress: self.progress.as_ref(),
+        }
+    }
 }
@@ -0,0 +1,102 @@
+//! Tasks of any supported type
+
+use std::fmt;
+
+use crate::procedures::EstimationProcedure;
+
+use super::{ClassLabels, SupervisedClassification, SupervisedRegression};
+
+/// A task of one of the supported task types, e.g. as loaded with `TaskKind::from_openml` when the
+/// type of the task is not known in advance
+#[derive(Debug)]
+pub enum TaskKind {
+    Classification(SupervisedClassification),
+    Regression(SupervisedRegression),
+}
+
+impl TaskKind {
+    /// get task ID
+    pub fn id(&self) -> &str {
+        match self {
+            TaskKind::Classification(task) => task.id(),
+            TaskKind::Regression(task) => task.id(),
+        }
+    }
+
+    /// get task name
+    pub fn name(&self) -> &str {
+        match self {
+            TaskKind::Classification(task) => task.name(),
+            TaskKind::Regression(task) => task.name(),
+        }
+    }
+
+    /// get the name OpenML gives the type of the task, e.g. "Supervised Classification"
+    pub fn task_type(&self) -> &'static str {
+        match self {
+            TaskKind::Classification(task) => task.task_type(),
+            TaskKind::Regression(task) => task.task_type(),
+        }
+    }
+
+    /// get the ID of the task's data set
+    pub fn dataset_id(&self) -> &str {
+        match self {
+            TaskKind::Classification(task) => task.dataset_id(),
+            TaskKind::Regression(task) => task.dataset_id(),
+        }
+    }
+
+    /// get the name of the target column
+    pub fn target_feature(&self) -> Option<&str> {
+        match self {
+            TaskKind::Classification(task) => task.target_feature(),
+            TaskKind::Regression(task) => task.target_feature(),
+        }
+    }
+
+    /// get the evaluation scheme of the task
+    pub fn estimation_procedure(&self) -> &EstimationProcedure {
+        match self {
+            TaskKind::Classification(task) => task.estimation_procedure(),
+            TaskKind::Regression(task) => task.estimation_procedure(),
+        }
+    }
+
+    /// get the OpenML names of the measures the task is evaluated with
+    pub fn evaluation_measures(&self) -> &[String] {
+        match self {
+            TaskKind::Classification(task) => task.evaluation_measures(),
+            TaskKind::Regression(task) => task.evaluation_measures(),
+        }
+    }
+
+    /// get the class labels of a classification task
+    pub fn class_labels(&self) -> Option<&ClassLabels> {
+        match self {
+            TaskKind::Classification(task) => task.class_labels(),
+            TaskKind::Regression(_) => None,
+        }
+    }
+}
+
+impl From<SupervisedClassification> for TaskKind {
+    fn from(task: SupervisedClassification) -> Self {
+        TaskKind::Classification(task)
+    }
+}
+
+impl From<SupervisedRegression> for TaskKind {
+    fn from(task: SupervisedRegression) -> Self {
+        TaskKind::Regression(task)
+    }
+}
+
+impl fmt::Display for TaskKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskKind::Classification(task) => fmt::Display::fmt(task, f),
+            TaskKind::Regression(task) => fmt::Display::fmt(task, f),
+        }
+    }
+}
@@ -0,0 +1,270 @@
+//! Quick plausibility checks of flows on a small part of a task
+
+use std::borrow::Cow;
+
+use crate::error::{Error, Result};
+use crate::measures::MeasureAccumulator;
+use crate::procedures::{Fold, Procedure};
+
+/// Number of training rows a flow is validated with
+pub(crate) const TRAIN_ROWS: usize = 100;
+
+/// Number of testing rows a flow is validated with
+pub(crate) const TEST_ROWS: usize = 20;
+
+/// Predictions whose plausibility can be checked by `validate`
+pub trait CheckPrediction {
+    /// describe why the prediction is implausible, given the number of classes of a
+    /// classification task (`None` for regression tasks)
+    fn check(&self, n_classes: Option<usize>) -> std::result::Result<(), String>;
+}
+
+macro_rules! check_class_code {
+    ($($t:ty),*) => {
+        $(
+            impl CheckPrediction for $t {
+                #[allow(unused_comparisons)]
+                fn check(&self, n_classes: Option<usize>) -> std::result::Result<(), String> {
+                    match n_classes {
+                        Some(n) if *self < 0 || *self as u64 >= n as u64 => Err(format!(
+                            "class code {} is outside of the {} classes",
+                            self, n
+                        )),
+                        _ => Ok(()),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+check_class_code!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+macro_rules! check_float {
+    ($($t:ty),*) => {
+        $(
+            impl CheckPrediction for $t {
+                fn check(&self, n_classes: Option<usize>) -> std::result::Result<(), String> {
+                    if !self.is_finite() {
+                        return Err(format!("prediction {} is not finite", self));
+                    }
+                    match n_classes {
+                        Some(n) if self.round() < 0.0 || self.round() >= n as $t => Err(format!(
+                            "class code {} is outside of the {} classes",
+                            self, n
+                        )),
+                        _ => Ok(()),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+check_float!(f32, f64);
+
+/// Class probabilities
+impl CheckPrediction for Vec<f64> {
+    fn check(&self, n_classes: Option<usize>) -> std::result::Result<(), String> {
+        if let Some(n) = n_classes {
+            if self.len() != n {
+                return Err(format!(
+                    "expected probabilities of {} classes but found {}",
+                    n,
+                    self.len()
+                ));
+            }
+        }
+        if let Some(p) = self.iter().find(|p| !(0.0..=1.0).contains(*p)) {
+            return Err(format!("probability {} is outside of [0, 1]", p));
+        }
+        let sum: f64 = self.iter().sum();
+        if (sum - 1.0).abs() > 1e-6 {
+            return Err(format!("probabilities sum to {} instead of 1", sum));
+        }
+        Ok(())
+    }
+}
+
+/// get the first fold of the splits, which flows are validated on. Fails with
+/// `Error::SplitsFormatError` if there are no folds.
+pub(crate) fn first_fold(splits: &dyn Procedure) -> Result<Cow<'_, Fold>> {
+    match splits.iter().next() {
+        Some((_, _, fold)) => Ok(fold),
+        None => Err(Error::SplitsFormatError {
+            line: 0,
+            repeat: None,
+            fold: None,
+            message: "the task has no folds".to_owned(),
+        }),
+    }
+}
+
+/// pick at most `n` of `rows`, evenly spread over all of them
+pub(crate) fn spread(rows: &[usize], n: usize) -> Vec<usize> {
+    if rows.len() <= n {
+        return rows.to_vec();
+    }
+    (0..n).map(|k| rows[k * rows.len() / n]).collect()
+}
+
+/// check that there is exactly one plausible prediction for every row of `testset`
+pub(crate) fn check_predictions<P, I>(
+    predictions: I,
+    testset: &[usize],
+    n_classes: Option<usize>,
+) -> Result<()>
+where
+    P: CheckPrediction,
+    I: Iterator<Item = P>,
+{
+    let mut n = 0;
+    for pred in predictions {
+        let row = *testset.get(n).ok_or_else(|| {
+            invalid_prediction(
+                None,
+                format!("flow predicted more than {} rows", testset.len()),
+            )
+        })?;
+        pred.check(n_classes)
+            .map_err(|message| invalid_prediction(Some(row), message))?;
+        n += 1;
+    }
+
+    if n < testset.len() {
+        return Err(invalid_prediction(
+            None,
+            format!("flow predicted {} of {} rows", n, testset.len()),
+        ));
+    }
+    Ok(())
+}
+
+fn invalid_prediction(row: Option<usize>, message: String) -> Error {
+    Error::InvalidPrediction { row, message }
+}
+
+/// add the predictions of a fold to `measure`. Fails with `Error::InvalidPredictions` unless there
+/// is exactly one prediction for every row of `testset` and every prediction passes `check`, which
+/// is called with the repeat and fold, the row and the prediction.
+pub(crate) fn measure_fold<Y, P, I, M, C>(
+    measure: &mut M,
+    (repeat, fold): (usize, usize),
+    y: &[Y],
+    testset: &[usize],
+    predictions: I,
+    mut check: C,
+) -> Result<()>
+where
+    I: Iterator<Item = P>,
+    M: MeasureAccumulator<Y, P> + ?Sized,
+    C: FnMut((usize, usize), usize, &P) -> std::result::Result<(), String>,
+{
+    let invalid = |row, message| Error::InvalidPredictions {
+        repeat,
+        fold,
+        row,
+        message,
+    };
+
+    let mut n = 0;
+    for pred in predictions {
+        let row = *testset.get(n).ok_or_else(|| {
+            invalid(
+                None,
+                format!("flow predicted more than {} rows", testset.len()),
+            )
+        })?;
+        check((repeat, fold), row, &pred).map_err(|message| invalid(Some(row), message))?;
+        measure.update_one(&y[row], &pred);
+        n += 1;
+    }
+
+    if n < testset.len() {
+        return Err(invalid(
+            None,
+            format!("flow predicted {} of {} rows", n, testset.len()),
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn check() {
+    assert!(check_predictions([0u8, 1].iter().cloned(), &[4, 7], Some(2)).is_ok());
+    assert!(check_predictions([0u8, 2].iter().cloned(), &[4, 7], Some(2)).is_err());
+    assert!(check_predictions([0u8].iter().cloned(), &[4, 7], Some(2)).is_err());
+    assert!(check_predictions([0u8, 1, 1].iter().cloned(), &[4, 7], Some(2)).is_err());
+    assert!(check_predictions([1.5, f64::NAN].iter().cloned(), &[4, 7], None).is_err());
+    assert!(check_predictions(vec![vec![0.2, 0.8]].into_iter(), &[4], Some(2)).is_ok());
+    assert!(check_predictions(vec![vec![0.2, 0.7]].into_iter(), &[4], Some(2)).is_err());
+
+    assert_eq!(spread(&[0, 1, 2, 3, 4, 5], 3), vec![0, 2, 4]);
+}
+
+#[test]
+fn no_folds() {
+    use crate::procedures::FrozenSets;
+
+    let splits = FrozenSets { folds: vec![] };
+    match first_fold(&splits) {
+        Err(Error::SplitsFormatError { message, .. }) => {
+            assert_eq!(message, "the task has no folds")
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn measured_folds() {
+    use crate::measures::RootMeanSquaredError;
+
+    let y = [1.0, 2.0, 3.0];
+    let mut measure = RootMeanSquaredError::new();
+    let no_check = |_, _, _: &f64| Ok(());
+    let finite = |_, _, p: &f64| p.check(None);
+
+    assert!(measure_fold(
+        &mut measure,
+        (0, 1),
+        &y,
+        &[0, 2],
+        vec![1.0, 3.0].into_iter(),
+        no_check
+    )
+    .is_ok());
+    assert_eq!(measure.result(), 0.0);
+    match measure_fold(
+        &mut measure,
+        (0, 1),
+        &y,
+        &[0, 2],
+        vec![1.0].into_iter(),
+        no_check,
+    ) {
+        Err(Error::InvalidPredictions { fold, row, .. }) => assert_eq!((fold, row), (1, None)),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(measure_fold(
+        &mut measure,
+        (0, 1),
+        &y,
+        &[0],
+        vec![1.0, 2.0].into_iter(),
+        no_check
+    )
+    .is_err());
+    match measure_fold(
+        &mut measure,
+        (2, 0),
+        &y,
+        &[0, 2],
+        vec![1.0, f64::NAN].into_iter(),
+        finite,
+    ) {
+        Err(Error::InvalidPredictions { repeat, row, .. }) => {
+            assert_eq!((repeat, row), (2, Some(2)))
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
@@ -0,0 +1,146 @@
+//! Views of the rows of a fold, without copying the features
+
+/// The feature rows of a fold: a list of row indices over the feature matrix that all folds
+/// share. Rows are copied only if the flow asks for it with `to_vec`.
+#[derive(Debug)]
+pub struct FeatureView<'a, X> {
+    x: &'a [X],
+    n_cols: usize,
+    rows: &'a [usize],
+}
+
+// implemented by hand, because derived impls would require `X: Clone`
+impl<'a, X> Clone for FeatureView<'a, X> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, X> Copy for FeatureView<'a, X> {}
+
+impl<'a, X> FeatureView<'a, X> {
+    /// create a view of the rows with the given indices in a row-major matrix with `n_cols`
+    /// columns
+    pub(crate) fn new(x: &'a [X], n_cols: usize, rows: &'a [usize]) -> Self {
+        FeatureView { x, n_cols, rows }
+    }
+
+    /// get the number of rows
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// return `true` if the view has no rows
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// get the number of features per row
+    pub fn n_cols(&self) -> usize {
+        self.n_cols
+    }
+
+    /// get the indices of the rows in the data set
+    pub fn indices(&self) -> &'a [usize] {
+        self.rows
+    }
+
+    /// get the `i`th row of the view
+    pub fn get(&self, i: usize) -> &'a [X] {
+        let row = self.rows[i];
+        &self.x[row * self.n_cols..(row + 1) * self.n_cols]
+    }
+
+    /// iterate over the rows; views can be iterated any number of times
+    pub fn iter(&self) -> impl Iterator<Item = &'a [X]> {
+        let view = *self;
+        (0..self.len()).map(move |i| view.get(i))
+    }
+
+    /// copy the rows, e.g. for flows that keep the training data
+    pub fn to_vec(&self) -> Vec<Vec<X>>
+    where
+        X: Clone,
+    {
+        self.iter().map(<[X]>::to_vec).collect()
+    }
+}
+
+/// The training rows of a fold: feature rows together with their targets
+#[derive(Debug)]
+pub struct TrainingView<'a, X, Y> {
+    features: FeatureView<'a, X>,
+    y: &'a [Y],
+}
+
+impl<'a, X, Y> Clone for TrainingView<'a, X, Y> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, X, Y> Copy for TrainingView<'a, X, Y> {}
+
+impl<'a, X, Y> TrainingView<'a, X, Y> {
+    /// create a view of feature rows and the targets of all rows in the data set
+    pub(crate) fn new(features: FeatureView<'a, X>, y: &'a [Y]) -> Self {
+        TrainingView { features, y }
+    }
+
+    /// get the number of rows
+    pub fn len(&self) -> usize {
+        self.features.len()
+    }
+
+    /// return `true` if the view has no rows
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+
+    /// get the feature rows without the targets
+    pub fn features(&self) -> FeatureView<'a, X> {
+        self.features
+    }
+
+    /// get the target of the `i`th row of the view
+    pub fn target(&self, i: usize) -> &'a Y {
+        &self.y[self.features.rows[i]]
+    }
+
+    /// get the features and the target of the `i`th row of the view
+    pub fn get(&self, i: usize) -> (&'a [X], &'a Y) {
+        (self.features.get(i), self.target(i))
+    }
+
+    /// iterate over the targets
+    pub fn targets(&self) -> impl Iterator<Item = &'a Y> {
+        let view = *self;
+        (0..self.len()).map(move |i| view.target(i))
+    }
+
+    /// iterate over pairs of feature rows and targets
+    pub fn iter(&self) -> impl Iterator<Item = (&'a [X], &'a Y)> {
+        let view = *self;
+        (0..self.len()).map(move |i| view.get(i))
+    }
+}
+
+#[test]
+fn views() {
+    let x = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+    let y = ["a", "b", "c"];
+    let rows = [2, 0];
+
+    let train = TrainingView::new(FeatureView::new(&x, 2, &rows), &y);
+    assert_eq!(train.len(), 2);
+    assert_eq!(train.get(0), (&x[4..], &"c"));
+    assert_eq!(train.targets().collect::<Vec<_>>(), vec![&"c", &"a"]);
+    assert_eq!(train.iter().count(), 2);
+    assert_eq!(train.iter().count(), 2);
+    assert_eq!(
+        train.features().to_vec(),
+        vec![vec![5.0, 6.0], vec![1.0, 2.0]]
+    );
+    assert_eq!(train.features().indices(), &rows);
+    assert!(FeatureView::new(&x, 2, &[]).is_empty());
+}
@@ -0,0 +1,27 @@
+//! End-to-end runs of the example flows: fetch task, split, train, measure and report.
+
+extern crate openml;
+
+use openml::example_flows::{knn_flow, linear_regression_flow};
+use openml::prelude::*;
+use openml::{
+    PredictiveAccuracy, RootMeanSquaredError, SupervisedClassification, SupervisedRegression,
+};
+
+#[test]
+fn knn_on_iris() {
+    let task = SupervisedClassification::from_openml(59).unwrap();
+    let result: PredictiveAccuracy<u8> = task.run(knn_flow(5));
+
+    println!("{}: accuracy {}", task.name(), result.result());
+    assert!(result.result() > 0.9);
+}
+
+#[test]
+fn linear_regression_on_liver_disorders() {
+    let task = SupervisedRegression::from_openml(52948).unwrap();
+    let result: RootMeanSquaredError<f64> = task.run(linear_regression_flow());
+
+    println!("{}: RMSE {}", task.name(), result.result());
+    assert!(result.result().is_finite());
+}